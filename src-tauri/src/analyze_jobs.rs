@@ -0,0 +1,138 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Mutex, OnceLock},
+};
+
+use futures::{
+    future::{AbortHandle, Abortable},
+    StreamExt,
+};
+use tauri::{AppHandle, Emitter};
+
+use crate::{
+    llm_recognition::analyze_filename,
+    settings::current_settings,
+    types::{AnalyzeFilesRequest, AnalyzeFilesSummary, AnalyzeProgress, LLMRequest, LLMResponse},
+};
+
+pub const ANALYZE_PROGRESS_EVENT: &str = "analyze-progress";
+
+// 正在运行的识别任务，取消时中止整个任务流，进行中的请求随之丢弃
+fn running_jobs() -> &'static Mutex<HashMap<String, AbortHandle>> {
+    static JOBS: OnceLock<Mutex<HashMap<String, AbortHandle>>> = OnceLock::new();
+    JOBS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// 以 job_id 登记任务并按 concurrency 并发执行 tasks，每完成一项以已完成数量调用 on_done；
+// 返回完成的数量和任务是否被取消
+pub async fn run_job<T, F>(
+    job_id: &str,
+    tasks: impl IntoIterator<Item = F>,
+    concurrency: usize,
+    mut on_done: impl FnMut(usize, T),
+) -> Result<(usize, bool), String>
+where
+    F: Future<Output = T>,
+{
+    let (abort_handle, registration) = AbortHandle::new_pair();
+    {
+        let mut jobs = running_jobs().lock().map_err(|_| "识别任务列表被锁定")?;
+        if jobs.contains_key(job_id) {
+            return Err(format!("识别任务已在运行: {}", job_id));
+        }
+        jobs.insert(job_id.to_string(), abort_handle);
+    }
+
+    let mut stream = Abortable::new(
+        futures::stream::iter(tasks).buffer_unordered(concurrency.max(1)),
+        registration,
+    );
+    let mut done = 0;
+    while let Some(output) = stream.next().await {
+        done += 1;
+        on_done(done, output);
+    }
+
+    if let Ok(mut jobs) = running_jobs().lock() {
+        jobs.remove(job_id);
+    }
+    Ok((done, stream.is_aborted()))
+}
+
+// 中止正在运行的任务，返回任务是否存在
+pub fn cancel_job(job_id: &str) -> Result<bool, String> {
+    let jobs = running_jobs().lock().map_err(|_| "识别任务列表被锁定")?;
+    match jobs.get(job_id) {
+        Some(handle) => {
+            handle.abort();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+// 并发识别一组文件，每完成一个发送 analyze-progress 事件
+#[tauri::command]
+pub async fn analyze_files(
+    app: AppHandle,
+    request: AnalyzeFilesRequest,
+) -> Result<AnalyzeFilesSummary, String> {
+    let job_id = request.job_id.clone();
+    let total = request.files.len();
+    let concurrency = request
+        .concurrency
+        .unwrap_or_else(|| current_settings().llm_concurrency);
+    let tasks = request.files.into_iter().map(|file| {
+        let llm_request = LLMRequest {
+            filename: file.name.clone(),
+            model_url: request.model_url.clone(),
+            model_name: request.model_name.clone(),
+            force_llm: request.force_llm,
+            bypass_cache: request.bypass_cache,
+        };
+        async move {
+            let result = analyze_filename(llm_request)
+                .await
+                .unwrap_or_else(|error| LLMResponse {
+                    success: false,
+                    data: None,
+                    error: Some(error),
+                    engine: None,
+                });
+            (file, result)
+        }
+    });
+
+    let mut succeeded = 0;
+    let (done, canceled) = run_job(&job_id, tasks, concurrency, |done, (file, result)| {
+        if result.success {
+            succeeded += 1;
+        }
+        let progress = AnalyzeProgress {
+            job_id: job_id.clone(),
+            done,
+            total,
+            file,
+            result,
+        };
+        if let Err(e) = app.emit(ANALYZE_PROGRESS_EVENT, progress) {
+            log::warn!("发送识别进度失败: {}", e);
+        }
+    })
+    .await?;
+
+    Ok(AnalyzeFilesSummary {
+        job_id,
+        done,
+        total,
+        succeeded,
+        canceled,
+    })
+}
+
+// 取消识别任务，返回任务是否存在
+#[tauri::command]
+pub async fn cancel_analyze_job(job_id: String) -> Result<bool, String> {
+    cancel_job(&job_id)
+}
@@ -0,0 +1,309 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::{
+    http::{fetch_text, http_client},
+    metadata_provider::{
+        non_empty, parse_year, MetadataProvider, EPISODE_TYPE_MAIN, EPISODE_TYPE_SPECIAL,
+    },
+    types::{MetadataEpisode, MetadataSubject, MetadataSubjectDetail, MetadataTag},
+};
+
+pub(crate) const CACHE_NAMESPACE: &str = "anidb";
+const IMAGE_BASE: &str = "https://cdn-eu.anidb.net/images/main/";
+const PROTOCOL_VERSION: &str = "1";
+// AniDB 要求每个客户端两次请求至少间隔 2 秒，过快会被封禁
+const REQUEST_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Deserialize)]
+struct AniDbAnime {
+    #[serde(rename = "@id")]
+    id: i64,
+    #[serde(rename = "type")]
+    anime_type: Option<String>,
+    episodecount: Option<i32>,
+    startdate: Option<String>,
+    picture: Option<String>,
+    #[serde(default)]
+    titles: AniDbTitles,
+    #[serde(default)]
+    tags: AniDbTags,
+    #[serde(default)]
+    episodes: AniDbEpisodes,
+}
+
+#[derive(Deserialize, Default)]
+struct AniDbTitles {
+    #[serde(rename = "title", default)]
+    items: Vec<AniDbTitle>,
+}
+
+#[derive(Deserialize)]
+struct AniDbTitle {
+    // xml:lang，反序列化时按去掉前缀的名称匹配
+    #[serde(rename = "@lang", default)]
+    lang: String,
+    #[serde(rename = "@type", default)]
+    title_type: String,
+    #[serde(rename = "$text", default)]
+    text: String,
+}
+
+#[derive(Deserialize, Default)]
+struct AniDbTags {
+    #[serde(rename = "tag", default)]
+    items: Vec<AniDbTag>,
+}
+
+#[derive(Deserialize)]
+struct AniDbTag {
+    #[serde(rename = "@weight", default)]
+    weight: u32,
+    name: String,
+}
+
+#[derive(Deserialize, Default)]
+struct AniDbEpisodes {
+    #[serde(rename = "episode", default)]
+    items: Vec<AniDbEpisode>,
+}
+
+#[derive(Deserialize)]
+struct AniDbEpisode {
+    #[serde(rename = "@id")]
+    id: i64,
+    epno: AniDbEpno,
+    airdate: Option<String>,
+    #[serde(rename = "title", default)]
+    titles: Vec<AniDbTitle>,
+}
+
+// type: 1 本篇，2 SP，3 OP/ED，4 预告，5 恶搞，6 其他；SP 的集数带前缀，如 S1
+#[derive(Deserialize)]
+struct AniDbEpno {
+    #[serde(rename = "@type")]
+    epno_type: i32,
+    #[serde(rename = "$text")]
+    value: String,
+}
+
+fn title_in<'a>(titles: &'a [AniDbTitle], lang: &str, types: &[&str]) -> Option<&'a str> {
+    titles
+        .iter()
+        .find(|t| t.lang == lang && (types.is_empty() || types.contains(&t.title_type.as_str())))
+        .map(|t| t.text.trim())
+        .filter(|t| !t.is_empty())
+}
+
+// 与 Bangumi 的放送类型写法保持一致
+fn platform(anime_type: Option<&str>) -> Option<String> {
+    let platform = match anime_type? {
+        "TV Series" => "TV",
+        "Movie" => "剧场版",
+        "OVA" => "OVA",
+        "Web" => "WEB",
+        other => other,
+    };
+    Some(platform.to_string())
+}
+
+// 转为 Bangumi 的章节类型编号
+fn episode_type(epno_type: i32) -> i32 {
+    match epno_type {
+        1 => EPISODE_TYPE_MAIN,
+        2 => EPISODE_TYPE_SPECIAL,
+        3 => 2,
+        4 => 4,
+        _ => 6,
+    }
+}
+
+// 解析 HTTP API 返回的 XML，出错时返回 <error>原因</error>
+fn parse_anime(xml: &str) -> Result<AniDbAnime, String> {
+    let trimmed = xml.trim_start_matches('\u{feff}').trim();
+    if let Some(rest) = trimmed.strip_prefix("<error") {
+        let reason = rest
+            .split_once('>')
+            .map_or(rest, |(_, r)| r)
+            .trim_end_matches("</error>");
+        return Err(format!("AniDB返回错误: {}", reason.trim()));
+    }
+    quick_xml::de::from_str(trimmed).map_err(|e| format!("解析AniDB响应失败: {}", e))
+}
+
+// 所有 AniDB 请求全局排队：每次预约下一个可用的时间点，到点后再发送
+async fn throttle() {
+    static NEXT_REQUEST: Mutex<Option<Instant>> = Mutex::new(None);
+    let wait = {
+        let mut next = NEXT_REQUEST.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let start = next.map_or(now, |next| next.max(now));
+        *next = Some(start + REQUEST_INTERVAL);
+        start - now
+    };
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+// AniDB HTTP API，只能按 aid 查询；需要在 AniDB 注册客户端，且请求频率受限
+pub struct AniDbProvider {
+    base_url: String,
+    client: String,
+    client_version: u32,
+}
+
+impl AniDbProvider {
+    pub fn new(base_url: &str, client: &str, client_version: u32) -> Self {
+        AniDbProvider {
+            base_url: base_url.trim().to_string(),
+            client: client.trim().to_string(),
+            client_version,
+        }
+    }
+
+    async fn anime(&self, aid: i64) -> Result<AniDbAnime, String> {
+        if self.client.is_empty() {
+            return Err("未设置 AniDB 客户端名称".to_string());
+        }
+        let client_version = self.client_version.to_string();
+        let aid = aid.to_string();
+        let builder = http_client().get(&self.base_url).query(&[
+            ("request", "anime"),
+            ("client", self.client.as_str()),
+            ("clientver", client_version.as_str()),
+            ("protover", PROTOCOL_VERSION),
+            ("aid", aid.as_str()),
+        ]);
+        throttle().await;
+        parse_anime(&fetch_text(builder, "AniDB").await?)
+    }
+
+    fn to_detail(anime: AniDbAnime) -> MetadataSubjectDetail {
+        let titles = &anime.titles.items;
+        let name = title_in(titles, "x-jat", &["main"])
+            .or_else(|| titles.first().map(|t| t.text.trim()))
+            .unwrap_or_default()
+            .to_string();
+        let name_cn = title_in(titles, "zh-Hans", &["official"])
+            .or_else(|| title_in(titles, "zh-Hans", &[]))
+            .map(str::to_string);
+        let mut aliases: Vec<String> = Vec::new();
+        for title in titles {
+            let title = title.text.trim();
+            if !title.is_empty()
+                && title != name
+                && Some(title) != name_cn.as_deref()
+                && !aliases.iter().any(|a| a == title)
+            {
+                aliases.push(title.to_string());
+            }
+        }
+        let air_date = anime.startdate.clone().and_then(non_empty);
+        let mut tags: Vec<MetadataTag> = anime
+            .tags
+            .items
+            .iter()
+            .map(|t| MetadataTag {
+                name: t.name.clone(),
+                count: t.weight,
+            })
+            .collect();
+        tags.sort_by_key(|t| std::cmp::Reverse(t.count));
+        MetadataSubjectDetail {
+            id: anime.id,
+            name,
+            name_cn,
+            cover_url: anime
+                .picture
+                .clone()
+                .and_then(non_empty)
+                .map(|p| format!("{}{}", IMAGE_BASE, p)),
+            episodes: anime.episodecount,
+            year: parse_year(air_date.as_deref()),
+            aliases,
+            tags,
+            platform: platform(anime.anime_type.as_deref()),
+            air_date,
+            episode_list: Vec::new(),
+        }
+    }
+
+    fn to_episodes(items: Vec<AniDbEpisode>) -> Vec<MetadataEpisode> {
+        let mut episodes: Vec<MetadataEpisode> = items
+            .into_iter()
+            .filter_map(|e| {
+                let number: f64 = e
+                    .epno
+                    .value
+                    .trim()
+                    .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+                    .parse()
+                    .ok()?;
+                let episode_type = episode_type(e.epno.epno_type);
+                Some(MetadataEpisode {
+                    id: e.id,
+                    episode_type,
+                    sort: number,
+                    ep: (episode_type == EPISODE_TYPE_MAIN).then_some(number),
+                    season: None,
+                    name: title_in(&e.titles, "ja", &[])
+                        .or_else(|| title_in(&e.titles, "x-jat", &[]))
+                        .or_else(|| title_in(&e.titles, "en", &[]))
+                        .map(str::to_string),
+                    name_cn: title_in(&e.titles, "zh-Hans", &[]).map(str::to_string),
+                    airdate: e.airdate.and_then(non_empty),
+                })
+            })
+            .collect();
+        episodes.sort_by(|a, b| {
+            a.episode_type
+                .cmp(&b.episode_type)
+                .then(a.sort.total_cmp(&b.sort))
+        });
+        episodes
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for AniDbProvider {
+    // HTTP API 没有关键词搜索，输入 aid 时直接返回该条目
+    async fn search(&self, keyword: &str, _limit: usize) -> Result<Vec<MetadataSubject>, String> {
+        let aid: i64 = keyword
+            .trim()
+            .parse()
+            .map_err(|_| "AniDB 不支持关键词搜索，请输入条目 ID (aid)".to_string())?;
+        let detail = self.detail(aid).await?;
+        Ok(vec![MetadataSubject {
+            id: detail.id,
+            name: detail.name,
+            name_cn: detail.name_cn,
+            subject_type: None,
+            date: detail.air_date,
+            platform: detail.platform,
+        }])
+    }
+
+    async fn detail(&self, id: i64) -> Result<MetadataSubjectDetail, String> {
+        Ok(Self::to_detail(self.anime(id).await?))
+    }
+
+    async fn episodes(&self, id: i64) -> Result<Vec<MetadataEpisode>, String> {
+        Ok(Self::to_episodes(self.anime(id).await?.episodes.items))
+    }
+
+    // 详情和章节来自同一个请求，只请求一次
+    async fn detail_with_episodes(&self, id: i64) -> Result<MetadataSubjectDetail, String> {
+        let mut anime = self.anime(id).await?;
+        let episodes = Self::to_episodes(std::mem::take(&mut anime.episodes.items));
+        Ok(MetadataSubjectDetail {
+            episode_list: episodes,
+            ..Self::to_detail(anime)
+        })
+    }
+}
@@ -0,0 +1,274 @@
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+use crate::{
+    http::{fetch_json, http_client},
+    metadata_provider::{non_empty, MetadataProvider, EPISODE_TYPE_MAIN},
+    types::{MetadataEpisode, MetadataSubject, MetadataSubjectDetail, MetadataTag},
+};
+
+pub(crate) const CACHE_NAMESPACE: &str = "anilist";
+
+const SEARCH_QUERY: &str = r#"
+query ($search: String, $perPage: Int) {
+  Page(perPage: $perPage) {
+    media(search: $search, type: ANIME) {
+      id
+      format
+      title { romaji english native }
+      startDate { year month day }
+    }
+  }
+}"#;
+
+const DETAIL_QUERY: &str = r#"
+query ($id: Int) {
+  Media(id: $id, type: ANIME) {
+    id
+    format
+    episodes
+    synonyms
+    title { romaji english native }
+    startDate { year month day }
+    coverImage { large }
+    tags { name rank }
+    streamingEpisodes { title }
+  }
+}"#;
+
+#[derive(Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct AniListTitle {
+    romaji: Option<String>,
+    english: Option<String>,
+    native: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct AniListDate {
+    year: Option<i32>,
+    month: Option<u32>,
+    day: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct AniListTag {
+    name: String,
+    #[serde(default)]
+    rank: u32,
+}
+
+#[derive(Deserialize)]
+struct AniListStreamingEpisode {
+    title: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AniListMedia {
+    id: i64,
+    format: Option<String>,
+    episodes: Option<i32>,
+    #[serde(default)]
+    synonyms: Vec<String>,
+    #[serde(default)]
+    title: AniListTitle,
+    #[serde(default)]
+    start_date: AniListDate,
+    cover_image: Option<Value>,
+    #[serde(default)]
+    tags: Vec<AniListTag>,
+    #[serde(default)]
+    streaming_episodes: Vec<AniListStreamingEpisode>,
+}
+
+#[derive(Deserialize)]
+struct PageData {
+    #[serde(rename = "Page")]
+    page: MediaPage,
+}
+
+#[derive(Deserialize)]
+struct MediaPage {
+    media: Vec<AniListMedia>,
+}
+
+#[derive(Deserialize)]
+struct MediaData {
+    #[serde(rename = "Media")]
+    media: AniListMedia,
+}
+
+// 与 Bangumi 的放送类型写法保持一致
+fn platform(format: Option<&str>) -> Option<String> {
+    let platform = match format? {
+        "TV" | "TV_SHORT" => "TV",
+        "MOVIE" => "剧场版",
+        "OVA" => "OVA",
+        "ONA" => "WEB",
+        "SPECIAL" => "Special",
+        "MUSIC" => "Music",
+        other => other,
+    };
+    Some(platform.to_string())
+}
+
+fn format_date(date: &AniListDate) -> Option<String> {
+    match (date.year?, date.month, date.day) {
+        (year, Some(month), Some(day)) => Some(format!("{}-{:02}-{:02}", year, month, day)),
+        (year, Some(month), None) => Some(format!("{}-{:02}", year, month)),
+        (year, None, _) => Some(year.to_string()),
+    }
+}
+
+fn display_name(title: &AniListTitle) -> String {
+    [&title.romaji, &title.english, &title.native]
+        .into_iter()
+        .flatten()
+        .find(|t| !t.trim().is_empty())
+        .cloned()
+        .unwrap_or_default()
+}
+
+// 流媒体分集标题形如 "Episode 3 - 标题"
+fn streaming_title(title: &str) -> Option<(u32, String)> {
+    let rest = title.trim().strip_prefix("Episode ")?;
+    let (number, name) = rest.split_once(" - ")?;
+    Some((number.trim().parse().ok()?, name.trim().to_string()))
+}
+
+// AniList GraphQL API，base_url 默认为 https://graphql.anilist.co
+pub struct AniListProvider {
+    base_url: String,
+}
+
+impl AniListProvider {
+    pub fn new(base_url: &str) -> Self {
+        AniListProvider {
+            base_url: base_url.trim().to_string(),
+        }
+    }
+
+    async fn query<T: DeserializeOwned>(&self, query: &str, variables: Value) -> Result<T, String> {
+        let body = json!({ "query": query, "variables": variables });
+        let response: GraphQlResponse<T> =
+            fetch_json(http_client().post(&self.base_url).json(&body), "AniList").await?;
+        if let Some(error) = response.errors.first() {
+            return Err(format!("AniList返回错误: {}", error.message));
+        }
+        response.data.ok_or("AniList响应中没有数据".to_string())
+    }
+
+    async fn media(&self, id: i64) -> Result<AniListMedia, String> {
+        let data: MediaData = self.query(DETAIL_QUERY, json!({ "id": id })).await?;
+        Ok(data.media)
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for AniListProvider {
+    async fn search(&self, keyword: &str, limit: usize) -> Result<Vec<MetadataSubject>, String> {
+        let data: PageData = self
+            .query(SEARCH_QUERY, json!({ "search": keyword, "perPage": limit }))
+            .await?;
+        Ok(data
+            .page
+            .media
+            .into_iter()
+            .map(|m| MetadataSubject {
+                id: m.id,
+                name: display_name(&m.title),
+                name_cn: None,
+                subject_type: None,
+                date: format_date(&m.start_date),
+                platform: platform(m.format.as_deref()),
+            })
+            .collect())
+    }
+
+    async fn detail(&self, id: i64) -> Result<MetadataSubjectDetail, String> {
+        let m = self.media(id).await?;
+        let name = display_name(&m.title);
+        let mut aliases: Vec<String> = Vec::new();
+        for alias in [m.title.english, m.title.native]
+            .into_iter()
+            .flatten()
+            .chain(m.synonyms)
+        {
+            let alias = alias.trim().to_string();
+            if !alias.is_empty() && alias != name && !aliases.contains(&alias) {
+                aliases.push(alias);
+            }
+        }
+        let air_date = format_date(&m.start_date);
+        Ok(MetadataSubjectDetail {
+            id: m.id,
+            name,
+            name_cn: None,
+            cover_url: m
+                .cover_image
+                .as_ref()
+                .and_then(|c| c["large"].as_str())
+                .map(str::to_string),
+            episodes: m.episodes,
+            year: m.start_date.year,
+            aliases,
+            tags: m
+                .tags
+                .into_iter()
+                .map(|t| MetadataTag {
+                    name: t.name,
+                    count: t.rank,
+                })
+                .collect(),
+            platform: platform(m.format.as_deref()),
+            air_date,
+            episode_list: Vec::new(),
+        })
+    }
+
+    // AniList 没有章节接口，按集数生成列表，标题取自流媒体分集信息（通常为英文）
+    async fn episodes(&self, id: i64) -> Result<Vec<MetadataEpisode>, String> {
+        let m = self.media(id).await?;
+        let titles: Vec<(u32, String)> = m
+            .streaming_episodes
+            .iter()
+            .filter_map(|e| e.title.as_deref().and_then(streaming_title))
+            .collect();
+        let count = m
+            .episodes
+            .map(|n| n.max(0) as u32)
+            .or_else(|| titles.iter().map(|(n, _)| *n).max())
+            .unwrap_or(0);
+        Ok((1..=count)
+            .map(|n| MetadataEpisode {
+                // 没有章节 ID，以集数代替
+                id: n as i64,
+                episode_type: EPISODE_TYPE_MAIN,
+                sort: n as f64,
+                ep: Some(n as f64),
+                season: None,
+                name: titles
+                    .iter()
+                    .find(|(number, _)| *number == n)
+                    .and_then(|(_, title)| non_empty(title.clone())),
+                name_cn: None,
+                airdate: None,
+            })
+            .collect())
+    }
+}
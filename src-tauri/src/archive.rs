@@ -0,0 +1,342 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{self, Read},
+    path::Path,
+};
+
+use sevenz_rust::{Password, SevenZReader};
+use zip::ZipArchive;
+
+use crate::{
+    subtitle_encoding::detect_encoding,
+    types::{ArchiveEntry, ArchiveExtraction},
+    utils::{get_extension, is_subtitle_file},
+};
+
+// 单个字幕文件的大小上限，超过的视为异常文件（如压缩炸弹）不解压
+pub const MAX_ENTRY_SIZE: u64 = 50 * 1024 * 1024;
+
+enum ArchiveKind {
+    Zip,
+    SevenZ,
+    Rar,
+}
+
+fn archive_kind(path: &Path) -> Result<ArchiveKind, String> {
+    match get_extension(&path.to_string_lossy()).as_str() {
+        "zip" => Ok(ArchiveKind::Zip),
+        "7z" => Ok(ArchiveKind::SevenZ),
+        "rar" => Ok(ArchiveKind::Rar),
+        _ => Err(format!("不支持的压缩包格式: {}", path.display())),
+    }
+}
+
+fn archive_error(e: impl std::fmt::Display) -> String {
+    format!("读取压缩包失败: {}", e)
+}
+
+// 包内路径统一以 / 分隔，去掉空段、. 和 ..，防止解压到目标文件夹之外
+pub fn normalize_entry_name(name: &str) -> String {
+    name.split(['/', '\\'])
+        .filter(|s| !s.is_empty() && *s != "." && *s != "..")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// zip 中未标记 UTF-8 的文件名按压缩时系统的代码页保存（简体中文 Windows 为 GBK）；
+// 同一个包内的文件名编码相同，合在一起检测更准确
+pub fn decode_entry_names(raw: &[Vec<u8>]) -> Vec<String> {
+    let legacy: Vec<u8> = raw
+        .iter()
+        .filter(|name| std::str::from_utf8(name).is_err())
+        .flat_map(|name| name.iter().copied().chain([b'\n']))
+        .collect();
+    let encoding = detect_encoding(&legacy).encoding;
+    raw.iter()
+        .map(|name| match std::str::from_utf8(name) {
+            Ok(name) => normalize_entry_name(name),
+            Err(_) => normalize_entry_name(&encoding.decode_without_bom_handling(name).0),
+        })
+        .collect()
+}
+
+fn open_zip(path: &Path) -> Result<ZipArchive<File>, String> {
+    let file = File::open(path).map_err(|e| format!("打开压缩包失败: {}", e))?;
+    ZipArchive::new(file).map_err(archive_error)
+}
+
+// zip 中每一项解码后的名称、大小和是否为文件夹，按包内顺序排列
+fn zip_entries(archive: &mut ZipArchive<File>) -> Result<Vec<(String, u64, bool)>, String> {
+    let mut raw = Vec::new();
+    let mut info = Vec::new();
+    for index in 0..archive.len() {
+        let file = archive.by_index_raw(index).map_err(archive_error)?;
+        raw.push(file.name_raw().to_vec());
+        info.push((file.size(), file.is_dir()));
+    }
+    Ok(decode_entry_names(&raw)
+        .into_iter()
+        .zip(info)
+        .map(|(name, (size, is_dir))| (name, size, is_dir))
+        .collect())
+}
+
+// 列出压缩包中的文件（不含文件夹）
+pub fn list_entries(path: &Path) -> Result<Vec<ArchiveEntry>, String> {
+    let files: Vec<(String, u64)> = match archive_kind(path)? {
+        ArchiveKind::Zip => zip_entries(&mut open_zip(path)?)?
+            .into_iter()
+            .filter(|(_, _, is_dir)| !is_dir)
+            .map(|(name, size, _)| (name, size))
+            .collect(),
+        ArchiveKind::SevenZ => {
+            let reader = SevenZReader::open(path, Password::empty()).map_err(archive_error)?;
+            reader
+                .archive()
+                .files
+                .iter()
+                .filter(|f| !f.is_directory())
+                .map(|f| (normalize_entry_name(f.name()), f.size()))
+                .collect()
+        }
+        ArchiveKind::Rar => {
+            let archive = unrar::Archive::new(path)
+                .open_for_listing()
+                .map_err(archive_error)?;
+            let mut files = Vec::new();
+            for header in archive {
+                let header = header.map_err(archive_error)?;
+                if header.is_file() {
+                    files.push((
+                        normalize_entry_name(&header.filename.to_string_lossy()),
+                        header.unpacked_size,
+                    ));
+                }
+            }
+            files
+        }
+    };
+    // 分卷的 rar 中跨卷的文件会出现多次
+    let mut seen = HashSet::new();
+    Ok(files
+        .into_iter()
+        .filter(|(name, _)| !name.is_empty() && seen.insert(name.clone()))
+        .map(|(name, size)| ArchiveEntry {
+            is_subtitle: is_subtitle_file(&name),
+            name,
+            size,
+        })
+        .collect())
+}
+
+fn too_large(size: u64) -> String {
+    format!(
+        "文件过大 ({} MB)，超过 {} MB 的上限",
+        size.div_ceil(1024 * 1024),
+        MAX_ENTRY_SIZE / 1024 / 1024
+    )
+}
+
+// 最多读取 MAX_ENTRY_SIZE 字节；文件头中的大小可能被篡改，实际读到的数据超出上限时同样拒绝
+fn read_limited(reader: impl Read, declared: u64) -> io::Result<Result<Vec<u8>, String>> {
+    if declared > MAX_ENTRY_SIZE {
+        return Ok(Err(too_large(declared)));
+    }
+    let mut data = Vec::new();
+    reader.take(MAX_ENTRY_SIZE + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > MAX_ENTRY_SIZE {
+        return Ok(Err(too_large(data.len() as u64)));
+    }
+    Ok(Ok(data))
+}
+
+// 读出 wanted 中列出的文件内容
+fn read_entries(
+    path: &Path,
+    wanted: &HashSet<String>,
+) -> Result<HashMap<String, Result<Vec<u8>, String>>, String> {
+    let mut contents = HashMap::new();
+    match archive_kind(path)? {
+        ArchiveKind::Zip => {
+            let mut archive = open_zip(path)?;
+            let entries = zip_entries(&mut archive)?;
+            for (index, (name, _, is_dir)) in entries.into_iter().enumerate() {
+                if is_dir || !wanted.contains(&name) {
+                    continue;
+                }
+                let data = archive
+                    .by_index(index)
+                    .map_err(archive_error)
+                    .and_then(|file| {
+                        let size = file.size();
+                        read_limited(file, size).map_err(archive_error)?
+                    });
+                contents.insert(name, data);
+            }
+        }
+        ArchiveKind::SevenZ => {
+            let mut reader = SevenZReader::open(path, Password::empty()).map_err(archive_error)?;
+            // 固实压缩的包只能按顺序解压，不需要的文件也要读完
+            reader
+                .for_each_entries(|entry, data| {
+                    let name = normalize_entry_name(entry.name());
+                    if !entry.is_directory() && wanted.contains(&name) {
+                        let result = read_limited(&mut *data, entry.size())?;
+                        contents.insert(name, result);
+                    }
+                    // 未读完的部分（包括超过上限时）需要跳过，才能继续解压后面的文件
+                    io::copy(data, &mut io::sink())?;
+                    Ok(true)
+                })
+                .map_err(archive_error)?;
+        }
+        ArchiveKind::Rar => {
+            let mut archive = unrar::Archive::new(path)
+                .open_for_processing()
+                .map_err(archive_error)?;
+            while let Some(header) = archive.read_header().map_err(archive_error)? {
+                let name = normalize_entry_name(&header.entry().filename.to_string_lossy());
+                let size = header.entry().unpacked_size;
+                archive = if header.entry().is_file() && wanted.contains(&name) {
+                    if size > MAX_ENTRY_SIZE {
+                        contents.insert(name, Err(too_large(size)));
+                        header.skip().map_err(archive_error)?
+                    } else {
+                        let (data, rest) = header.read().map_err(archive_error)?;
+                        contents.insert(name, Ok(data));
+                        rest
+                    }
+                } else {
+                    header.skip().map_err(archive_error)?
+                };
+            }
+        }
+    }
+    Ok(contents)
+}
+
+// 替换文件名中不能使用的字符
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+// 解压后的文件名：包内的文件夹结构不保留，文件直接放在目标文件夹中；
+// 不同文件夹中有同名文件时，把所在文件夹的名称加在扩展名前，如 CHT/01.ass → 01.CHT.ass
+pub fn extraction_names(entries: &[String]) -> Vec<String> {
+    let base = |entry: &str| entry.rsplit('/').next().unwrap_or(entry).to_string();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for entry in entries {
+        *counts.entry(base(entry).to_lowercase()).or_insert(0) += 1;
+    }
+    entries
+        .iter()
+        .map(|entry| {
+            let name = base(entry);
+            let folder = entry
+                .rsplit('/')
+                .nth(1)
+                .filter(|_| counts[&name.to_lowercase()] > 1);
+            let name = match (folder, name.rsplit_once('.')) {
+                (Some(folder), Some((stem, ext))) => format!("{}.{}.{}", stem, folder, ext),
+                (Some(folder), None) => format!("{}.{}", name, folder),
+                (None, _) => name,
+            };
+            sanitize_name(&name)
+        })
+        .collect()
+}
+
+// 把选中的字幕解压到 dest 中，每个文件单独报告结果；不是字幕的文件不解压
+pub fn extract_entries(
+    path: &Path,
+    entries: &[String],
+    dest: &Path,
+    overwrite: bool,
+) -> Result<Vec<ArchiveExtraction>, String> {
+    let entries: Vec<String> = entries.iter().map(|e| normalize_entry_name(e)).collect();
+    let wanted: HashSet<String> = entries
+        .iter()
+        .filter(|e| is_subtitle_file(e))
+        .cloned()
+        .collect();
+    let mut contents = read_entries(path, &wanted)?;
+    fs::create_dir_all(dest).map_err(|e| format!("创建文件夹失败: {}", e))?;
+
+    let mut written = HashSet::new();
+    let names = extraction_names(&entries);
+    Ok(entries
+        .into_iter()
+        .zip(names)
+        .map(|(entry, name)| {
+            let target = dest.join(&name);
+            let result = (|| {
+                if !is_subtitle_file(&entry) {
+                    return Err("不是字幕文件".to_string());
+                }
+                let data = contents
+                    .remove(&entry)
+                    .ok_or("压缩包中没有该文件".to_string())??;
+                if !written.insert(name.to_lowercase()) {
+                    return Err(format!("与包内其他文件重名: {}", name));
+                }
+                if target.exists() && !overwrite {
+                    return Err(format!("目标文件已存在: {}", target.display()));
+                }
+                fs::write(&target, data).map_err(|e| format!("写入文件失败: {}", e))?;
+                Ok(target.to_string_lossy().to_string())
+            })();
+            match result {
+                Ok(target) => ArchiveExtraction {
+                    entry,
+                    target: Some(target),
+                    error: None,
+                },
+                Err(e) => ArchiveExtraction {
+                    entry,
+                    target: None,
+                    error: Some(e),
+                },
+            }
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn list_archive_entries(path: String) -> Result<Vec<ArchiveEntry>, String> {
+    list_entries(Path::new(&path))
+}
+
+// 解压字幕包，未指定 entries 时解压包内全部字幕；dest_dir 默认为压缩包所在的文件夹
+#[tauri::command]
+pub async fn extract_archive_subtitles(
+    path: String,
+    entries: Option<Vec<String>>,
+    dest_dir: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<Vec<ArchiveExtraction>, String> {
+    let archive = Path::new(&path);
+    let entries = match entries {
+        Some(entries) => entries,
+        None => list_entries(archive)?
+            .into_iter()
+            .filter(|e| e.is_subtitle)
+            .map(|e| e.name)
+            .collect(),
+    };
+    let dest = match &dest_dir {
+        Some(dir) => Path::new(dir),
+        None => archive
+            .parent()
+            .ok_or(format!("无法确定解压位置: {}", path))?,
+    };
+    extract_entries(archive, &entries, dest, overwrite.unwrap_or(false))
+}
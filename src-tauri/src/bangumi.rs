@@ -0,0 +1,240 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    http::{fetch_json, http_client},
+    metadata_provider::{non_empty, parse_year, MetadataProvider},
+    settings::current_settings,
+    types::{MetadataEpisode, MetadataSubject, MetadataSubjectDetail, MetadataTag},
+};
+
+pub(crate) const CACHE_NAMESPACE: &str = "bangumi";
+// 条目类型：2 为动画
+const SUBJECT_TYPE_ANIME: i32 = 2;
+const EPISODE_PAGE_SIZE: usize = 100;
+// 信息框中作为别名收集的字段
+const ALIAS_KEYS: [&str; 4] = ["别名", "英文名", "日文名", "罗马字"];
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct V0Images {
+    large: String,
+    common: String,
+}
+
+#[derive(Deserialize)]
+struct V0InfoboxItem {
+    key: String,
+    value: Value,
+}
+
+#[derive(Deserialize)]
+struct V0Subject {
+    id: i64,
+    #[serde(rename = "type")]
+    subject_type: Option<i32>,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    name_cn: String,
+    date: Option<String>,
+    #[serde(default)]
+    platform: String,
+    #[serde(default)]
+    images: Option<V0Images>,
+    #[serde(default)]
+    infobox: Vec<V0InfoboxItem>,
+    #[serde(default)]
+    tags: Vec<MetadataTag>,
+    eps: Option<i32>,
+    total_episodes: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct V0Paged<T> {
+    data: Vec<T>,
+    #[serde(default)]
+    total: usize,
+}
+
+#[derive(Deserialize)]
+struct V0Episode {
+    id: i64,
+    #[serde(rename = "type", default)]
+    episode_type: i32,
+    #[serde(default)]
+    sort: f64,
+    ep: Option<f64>,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    name_cn: String,
+    #[serde(default)]
+    airdate: String,
+}
+
+#[derive(Deserialize)]
+struct V0RelatedSubject {
+    id: i64,
+    #[serde(rename = "type")]
+    subject_type: Option<i32>,
+    #[serde(default)]
+    relation: String,
+}
+
+// 关联条目，relation 为 前传、续集、番外篇 等
+#[derive(Serialize, Deserialize)]
+pub(crate) struct RelatedSubject {
+    pub id: i64,
+    pub relation: String,
+}
+
+// 信息框的值可能是字符串，也可能是 [{"v": "..."}] 形式的列表
+fn infobox_values(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| item.get("v").and_then(Value::as_str))
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn collect_aliases(subject: &V0Subject) -> Vec<String> {
+    let mut aliases: Vec<String> = Vec::new();
+    for item in subject
+        .infobox
+        .iter()
+        .filter(|item| ALIAS_KEYS.contains(&item.key.as_str()))
+    {
+        for alias in infobox_values(&item.value) {
+            let alias = alias.trim();
+            if alias.is_empty()
+                || alias == subject.name
+                || alias == subject.name_cn
+                || aliases.iter().any(|a| a == alias)
+            {
+                continue;
+            }
+            aliases.push(alias.to_string());
+        }
+    }
+    aliases
+}
+
+// Bangumi v0 API，base_url 默认为 https://api.bgm.tv
+pub struct BangumiProvider {
+    base_url: String,
+}
+
+impl BangumiProvider {
+    pub fn new(base_url: &str) -> Self {
+        BangumiProvider {
+            base_url: base_url.trim().trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn from_settings() -> Self {
+        Self::new(&current_settings().bangumi_api_url)
+    }
+
+    // 获取关联的动画条目
+    pub(crate) async fn related(&self, subject_id: i64) -> Result<Vec<RelatedSubject>, String> {
+        let url = format!("{}/v0/subjects/{}/subjects", self.base_url, subject_id);
+        let related: Vec<V0RelatedSubject> = fetch_json(http_client().get(url), "Bangumi").await?;
+        Ok(related
+            .into_iter()
+            .filter(|r| r.subject_type == Some(SUBJECT_TYPE_ANIME))
+            .map(|r| RelatedSubject {
+                id: r.id,
+                relation: r.relation,
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for BangumiProvider {
+    async fn search(&self, keyword: &str, limit: usize) -> Result<Vec<MetadataSubject>, String> {
+        let url = format!("{}/v0/search/subjects?limit={}", self.base_url, limit);
+        let body = json!({
+            "keyword": keyword,
+            "filter": { "type": [SUBJECT_TYPE_ANIME] }
+        });
+        let page: V0Paged<V0Subject> =
+            fetch_json(http_client().post(url).json(&body), "Bangumi").await?;
+        Ok(page
+            .data
+            .into_iter()
+            .filter(|s| s.id != 0 && !s.name.is_empty())
+            .map(|s| MetadataSubject {
+                id: s.id,
+                name: s.name,
+                name_cn: non_empty(s.name_cn),
+                subject_type: s.subject_type,
+                date: s.date.and_then(non_empty),
+                platform: non_empty(s.platform),
+            })
+            .collect())
+    }
+
+    async fn detail(&self, id: i64) -> Result<MetadataSubjectDetail, String> {
+        let url = format!("{}/v0/subjects/{}", self.base_url, id);
+        let subject: V0Subject = fetch_json(http_client().get(url), "Bangumi").await?;
+        let aliases = collect_aliases(&subject);
+        let air_date = subject.date.and_then(non_empty);
+        let cover_url = subject
+            .images
+            .and_then(|images| non_empty(images.large).or_else(|| non_empty(images.common)));
+        Ok(MetadataSubjectDetail {
+            id: subject.id,
+            name: subject.name,
+            name_cn: non_empty(subject.name_cn),
+            cover_url,
+            episodes: subject.eps.or(subject.total_episodes),
+            year: parse_year(air_date.as_deref()),
+            aliases,
+            tags: subject.tags,
+            platform: non_empty(subject.platform),
+            air_date,
+            episode_list: Vec::new(),
+        })
+    }
+
+    // 分页获取条目下的全部章节（含 SP、OP/ED 等），按类型和 sort 排序
+    async fn episodes(&self, id: i64) -> Result<Vec<MetadataEpisode>, String> {
+        let mut episodes = Vec::new();
+        let mut offset = 0;
+        loop {
+            let url = format!(
+                "{}/v0/episodes?subject_id={}&limit={}&offset={}",
+                self.base_url, id, EPISODE_PAGE_SIZE, offset
+            );
+            let page: V0Paged<V0Episode> = fetch_json(http_client().get(url), "Bangumi").await?;
+            let count = page.data.len();
+            episodes.extend(page.data.into_iter().map(|e| MetadataEpisode {
+                id: e.id,
+                episode_type: e.episode_type,
+                sort: e.sort,
+                ep: e.ep,
+                season: None,
+                name: non_empty(e.name),
+                name_cn: non_empty(e.name_cn),
+                airdate: non_empty(e.airdate),
+            }));
+            offset += count;
+            if count == 0 || offset >= page.total {
+                break;
+            }
+        }
+        episodes.sort_by(|a, b| {
+            a.episode_type
+                .cmp(&b.episode_type)
+                .then(a.sort.total_cmp(&b.sort))
+        });
+        Ok(episodes)
+    }
+}
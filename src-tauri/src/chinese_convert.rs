@@ -0,0 +1,285 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use crate::{
+    subtitle::rewrite_ass_events,
+    subtitle_encoding::decode_subtitle,
+    types::{ChineseConversion, ScriptConversion, SubtitleFormat},
+    utils::detect_language_tag,
+};
+
+// OpenCC 格式的词典：每行为 "原文<Tab>候选1 候选2"，第一个候选为默认结果。
+// 词典是参照 OpenCC 整理的子集，来源与许可见 data/opencc/README.md；
+// 繁转简的词典由简转繁的词典反转得到，TS 开头的文件只收录反转后不正确或缺少的条目
+const ST_CHARACTERS: &str = include_str!("../data/opencc/STCharacters.txt");
+const ST_PHRASES: &str = include_str!("../data/opencc/STPhrases.txt");
+const TS_CHARACTERS: &str = include_str!("../data/opencc/TSCharacters.txt");
+const TS_PHRASES: &str = include_str!("../data/opencc/TSPhrases.txt");
+
+// 文件名中的语言标记与转换后的标记，按简体、繁体成对排列
+const TAG_PAIRS: [(&str, &str); 9] = [
+    ("chs", "cht"),
+    ("sc", "tc"),
+    ("gb", "big5"),
+    ("zh-cn", "zh-tw"),
+    ("zh-hans", "zh-hant"),
+    ("chs&jpn", "cht&jpn"),
+    ("chs_jpn", "cht_jpn"),
+    ("jpsc", "jptc"),
+    ("sc_jp", "tc_jp"),
+];
+
+pub struct Converter {
+    chars: HashMap<char, char>,
+    phrases: HashMap<String, String>,
+    // 词组的最大字数，用于最长匹配
+    max_phrase: usize,
+}
+
+fn dictionary_entries(data: &str) -> impl Iterator<Item = (&str, Vec<&str>)> {
+    data.lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, values) = line.split_once('\t')?;
+            let values: Vec<&str> = values.split_whitespace().collect();
+            (!values.is_empty()).then_some((key.trim(), values))
+        })
+}
+
+fn single_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    let c = chars.next()?;
+    chars.next().is_none().then_some(c)
+}
+
+impl Converter {
+    fn new(chars: HashMap<char, char>, phrases: HashMap<String, String>) -> Self {
+        let max_phrase = phrases.keys().map(|k| k.chars().count()).max().unwrap_or(1);
+        Converter {
+            chars,
+            phrases,
+            max_phrase,
+        }
+    }
+
+    fn simplified_to_traditional() -> Self {
+        let chars = dictionary_entries(ST_CHARACTERS)
+            .filter_map(|(key, values)| Some((single_char(key)?, single_char(values[0])?)))
+            .collect();
+        let phrases = dictionary_entries(ST_PHRASES)
+            .map(|(key, values)| (key.to_string(), values[0].to_string()))
+            .collect();
+        Converter::new(chars, phrases)
+    }
+
+    fn traditional_to_simplified() -> Self {
+        let mut chars = HashMap::new();
+        for (key, values) in dictionary_entries(ST_CHARACTERS) {
+            let Some(simplified) = single_char(key) else {
+                continue;
+            };
+            for traditional in values.iter().filter_map(|v| single_char(v)) {
+                chars.entry(traditional).or_insert(simplified);
+            }
+        }
+        let mut phrases: HashMap<String, String> = dictionary_entries(ST_PHRASES)
+            .flat_map(|(key, values)| {
+                values
+                    .into_iter()
+                    .map(move |v| (v.to_string(), key.to_string()))
+            })
+            .collect();
+        for (key, values) in dictionary_entries(TS_CHARACTERS) {
+            if let (Some(k), Some(v)) = (single_char(key), single_char(values[0])) {
+                chars.insert(k, v);
+            }
+        }
+        for (key, values) in dictionary_entries(TS_PHRASES) {
+            phrases.insert(key.to_string(), values[0].to_string());
+        }
+        Converter::new(chars, phrases)
+    }
+
+    // 词组按最长匹配优先，其余逐字转换；返回转换结果和改动的字数
+    pub fn convert(&self, text: &str) -> (String, usize) {
+        let chars: Vec<char> = text.chars().collect();
+        let mut out = String::with_capacity(text.len());
+        let mut changed = 0;
+        let mut i = 0;
+        'outer: while i < chars.len() {
+            let longest = self.max_phrase.min(chars.len() - i);
+            for len in (2..=longest).rev() {
+                let key: String = chars[i..i + len].iter().collect();
+                if let Some(phrase) = self.phrases.get(&key) {
+                    changed += key
+                        .chars()
+                        .zip(phrase.chars())
+                        .filter(|(a, b)| a != b)
+                        .count();
+                    out.push_str(phrase);
+                    i += len;
+                    continue 'outer;
+                }
+            }
+            let c = self.chars.get(&chars[i]).copied().unwrap_or(chars[i]);
+            if c != chars[i] {
+                changed += 1;
+            }
+            out.push(c);
+            i += 1;
+        }
+        (out, changed)
+    }
+
+    // 只转换 ASS 文本中覆盖标签以外的部分，{\fn微软雅黑} 等标签保持不变
+    pub fn convert_ass_text(&self, text: &str) -> (String, usize) {
+        let mut out = String::with_capacity(text.len());
+        let mut changed = 0;
+        let mut rest = text;
+        while !rest.is_empty() {
+            let (plain, tail) = match rest.find('{') {
+                Some(start) => match rest[start..].find('}') {
+                    Some(end) => (&rest[..start], &rest[start..start + end + 1]),
+                    None => (rest, ""),
+                },
+                None => (rest, ""),
+            };
+            let (converted, n) = self.convert(plain);
+            out.push_str(&converted);
+            out.push_str(tail);
+            changed += n;
+            rest = &rest[plain.len() + tail.len()..];
+        }
+        (out, changed)
+    }
+}
+
+pub fn converter(direction: ChineseConversion) -> &'static Converter {
+    static S2T: OnceLock<Converter> = OnceLock::new();
+    static T2S: OnceLock<Converter> = OnceLock::new();
+    match direction {
+        ChineseConversion::S2t => S2T.get_or_init(Converter::simplified_to_traditional),
+        ChineseConversion::T2s => T2S.get_or_init(Converter::traditional_to_simplified),
+    }
+}
+
+// 转换字幕文本：ASS/SSA 只转换 Dialogue/Comment 的文本字段，样式名、字体名等保持不变
+pub fn convert_subtitle_text(
+    text: &str,
+    format: SubtitleFormat,
+    direction: ChineseConversion,
+) -> (String, usize) {
+    let converter = converter(direction);
+    match format {
+        SubtitleFormat::Srt | SubtitleFormat::Vtt => converter.convert(text),
+        SubtitleFormat::Ass | SubtitleFormat::Ssa => {
+            let mut changed = 0;
+            let out = rewrite_ass_events(text, |format, fields| {
+                let Some(index) = format.iter().position(|f| f == "text") else {
+                    return false;
+                };
+                let Some(field) = fields.get_mut(index) else {
+                    return false;
+                };
+                let (converted, n) = converter.convert_ass_text(field);
+                *field = converted;
+                changed += n;
+                n > 0
+            });
+            (out, changed)
+        }
+    }
+}
+
+fn matching_case(tag: &str, original: &str) -> String {
+    if original.chars().any(|c| c.is_ascii_lowercase()) {
+        tag.to_string()
+    } else {
+        tag.to_uppercase()
+    }
+}
+
+// 生成文件名：已有简繁标记时替换为对应的标记（video.chs.ass → video.cht.ass），否则在扩展名前加上 chs/cht
+pub fn converted_path(path: &str, direction: ChineseConversion) -> PathBuf {
+    let path = Path::new(path);
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let (stem, ext) = name.rsplit_once('.').unwrap_or((name, ""));
+    let swapped = detect_language_tag(name).and_then(|tag| {
+        let lower = tag.to_lowercase();
+        let target = TAG_PAIRS.iter().find_map(|(s, t)| match direction {
+            ChineseConversion::S2t if *s == lower => Some(*t),
+            ChineseConversion::T2s if *t == lower => Some(*s),
+            _ => None,
+        })?;
+        let base = &stem[..stem.len() - tag.len()];
+        Some(format!("{}{}", base, matching_case(target, &tag)))
+    });
+    let stem = swapped.unwrap_or_else(|| {
+        let tag = match direction {
+            ChineseConversion::S2t => "cht",
+            ChineseConversion::T2s => "chs",
+        };
+        format!("{}.{}", stem, tag)
+    });
+    let file_name = if ext.is_empty() {
+        stem
+    } else {
+        format!("{}.{}", stem, ext)
+    };
+    path.with_file_name(file_name)
+}
+
+// 在原字幕旁生成转换后的文件（UTF-8），原文件保留
+pub fn convert_file(path: &str, direction: ChineseConversion, overwrite: bool) -> ScriptConversion {
+    let mut report = ScriptConversion {
+        source: path.to_string(),
+        ..Default::default()
+    };
+    let result = (|| {
+        let format =
+            SubtitleFormat::from_path(path).ok_or(format!("不支持的字幕格式: {}", path))?;
+        let bytes = fs::read(path).map_err(|e| format!("读取字幕失败: {}", e))?;
+        let (text, detected) = decode_subtitle(&bytes);
+        if !detected.is_reliable() {
+            return Err("无法识别字幕编码，未转换".to_string());
+        }
+        let (converted, changed) = convert_subtitle_text(&text, format, direction);
+        if changed == 0 {
+            return Err("没有需要转换的文字".to_string());
+        }
+        let target = converted_path(path, direction);
+        if target.exists() && !overwrite {
+            return Err(format!("目标文件已存在: {}", target.display()));
+        }
+        fs::write(&target, converted).map_err(|e| format!("写入字幕失败: {}", e))?;
+        Ok((target.to_string_lossy().to_string(), changed))
+    })();
+    match result {
+        Ok((target, changed)) => {
+            report.target = Some(target);
+            report.changed = changed;
+        }
+        Err(e) => report.error = Some(e),
+    }
+    report
+}
+
+// 简繁转换，overwrite 默认为 false
+#[tauri::command]
+pub async fn convert_chinese_script(
+    paths: Vec<String>,
+    direction: ChineseConversion,
+    overwrite: Option<bool>,
+) -> Result<Vec<ScriptConversion>, String> {
+    Ok(paths
+        .iter()
+        .map(|path| convert_file(path, direction, overwrite.unwrap_or(false)))
+        .collect())
+}
@@ -0,0 +1,152 @@
+use std::{sync::OnceLock, time::Duration};
+
+use reqwest::{header::RETRY_AFTER, Client, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+
+use crate::settings::current_settings;
+
+const USER_AGENT: &str = concat!(
+    "anime-renamer-tauri/",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/iLoveCYaRon/anime-renamer-tauri)"
+);
+
+// 单次重试等待的上限，Retry-After 过大时也按此截断
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+// 请求类别，决定使用设置中的哪一个超时
+#[derive(Clone, Copy, Debug)]
+pub enum RequestKind {
+    Llm,
+    Api,
+}
+
+// 单次请求的超时与重试次数，默认取自设置
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub timeout: Duration,
+    pub max_retries: u32,
+    // 第 n 次重试前等待 base_delay_ms * 2^n 毫秒
+    pub base_delay_ms: u64,
+}
+
+impl RetryPolicy {
+    pub fn from_settings(kind: RequestKind) -> Self {
+        let settings = current_settings();
+        let timeout_secs = match kind {
+            RequestKind::Llm => settings.llm_timeout_secs,
+            RequestKind::Api => settings.api_timeout_secs,
+        };
+        RetryPolicy {
+            timeout: Duration::from_secs(timeout_secs.max(1)),
+            max_retries: settings.http_max_retries,
+            base_delay_ms: settings.http_retry_base_ms,
+        }
+    }
+}
+
+// 全局复用的 HTTP 客户端，超时按请求单独设置
+pub fn http_client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        Client::builder()
+            .user_agent(USER_AGENT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+// 仅支持秒数形式的 Retry-After
+fn retry_after(response: &Response) -> Option<Duration> {
+    let secs = response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(secs).min(MAX_RETRY_DELAY))
+}
+
+fn backoff(base_ms: u64, attempt: u32) -> Duration {
+    let factor = 2u64.saturating_pow(attempt);
+    Duration::from_millis(base_ms.saturating_mul(factor)).min(MAX_RETRY_DELAY)
+}
+
+// 按设置中的超时与重试次数发送请求
+pub async fn send_with_retry(
+    builder: RequestBuilder,
+    kind: RequestKind,
+) -> Result<Response, reqwest::Error> {
+    send_with_policy(builder, RetryPolicy::from_settings(kind)).await
+}
+
+// 发送请求，连接失败、429 和 5xx 时按指数退避重试；重试用尽后返回最后一次的结果
+pub async fn send_with_policy(
+    builder: RequestBuilder,
+    policy: RetryPolicy,
+) -> Result<Response, reqwest::Error> {
+    let RetryPolicy {
+        timeout,
+        max_retries,
+        base_delay_ms,
+    } = policy;
+
+    let mut attempt = 0;
+    loop {
+        // 请求体无法复制时只发送一次
+        let Some(request) = builder.try_clone() else {
+            return builder.timeout(timeout).send().await;
+        };
+        let delay = match request.timeout(timeout).send().await {
+            Ok(response) if is_retryable_status(response.status()) && attempt < max_retries => {
+                log::warn!("请求返回 {}，准备重试", response.status());
+                retry_after(&response).unwrap_or_else(|| backoff(base_delay_ms, attempt))
+            }
+            Err(e) if e.is_connect() && attempt < max_retries => {
+                log::warn!("连接失败: {}，准备重试", e);
+                backoff(base_delay_ms, attempt)
+            }
+            result => return result,
+        };
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+// 发送接口请求并检查状态码，source 为错误信息中的接口名称
+async fn fetch_ok(builder: RequestBuilder, source: &str) -> Result<Response, String> {
+    let response = send_with_retry(builder, RequestKind::Api)
+        .await
+        .map_err(|e| format!("请求{}失败: {}", source, e))?;
+    if !response.status().is_success() {
+        return Err(format!("{}返回错误状态码: {}", source, response.status()));
+    }
+    Ok(response)
+}
+
+// 发送接口请求并解析 JSON 响应
+pub async fn fetch_json<T: DeserializeOwned>(
+    builder: RequestBuilder,
+    source: &str,
+) -> Result<T, String> {
+    fetch_ok(builder, source)
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("解析{}响应失败: {}", source, e))
+}
+
+// 发送接口请求并返回响应文本，用于 XML 等非 JSON 接口
+pub async fn fetch_text(builder: RequestBuilder, source: &str) -> Result<String, String> {
+    fetch_ok(builder, source)
+        .await?
+        .text()
+        .await
+        .map_err(|e| format!("读取{}响应失败: {}", source, e))
+}
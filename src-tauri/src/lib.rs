@@ -1,22 +1,61 @@
+pub mod analyze_jobs;
+pub mod anidb;
+pub mod anilist;
+pub mod archive;
+pub mod bangumi;
+pub mod chinese_convert;
+pub mod http;
+pub mod llm_cache;
+pub mod llm_output;
+pub mod llm_provider;
 mod llm_recognition;
-mod rename;
+pub mod matching;
+pub mod metadata_cache;
+pub mod metadata_provider;
+pub mod naming;
+pub mod parser;
+pub mod rename;
+pub mod rename_journal;
+pub mod scanner;
+pub mod season_mapping;
 mod settings;
-mod types;
+pub mod subject_ranking;
+pub mod subtitle;
+pub mod subtitle_encoding;
+pub mod subtitle_timing;
+pub mod tmdb;
+pub mod types;
 mod utils;
 
-use std::fs;
-
 use tauri_plugin_dialog::DialogExt;
 
 use crate::{
-    llm_recognition::{
-        analyze_filename, batch_analyze_filenames, get_bangumi_subject_detail,
-        search_bangumi_subjects,
+    analyze_jobs::{analyze_files, cancel_analyze_job},
+    archive::{extract_archive_subtitles, list_archive_entries},
+    chinese_convert::convert_chinese_script,
+    llm_cache::clear_recognition_cache,
+    llm_recognition::{analyze_filename, batch_analyze_filenames},
+    metadata_cache::{clear_metadata_cache, get_cover_image},
+    matching::match_episodes,
+    metadata_provider::{
+        get_metadata_detail, get_metadata_episodes, lookup_episode_titles, search_metadata,
+    },
+    naming::render_name,
+    parser::parse_anime_filename,
+    rename::{
+        execute_rename_plan, get_dropped_files, plan_rename, plan_video_rename,
+        rename_subtitle_files,
     },
-    rename::{get_dropped_files, rename_subtitle_files},
+    rename_journal::{list_rename_history, redo_rename_batch, undo_rename_batch},
+    season_mapping::{get_season_map, map_episode_numbers},
     settings::{load_settings, save_settings},
-    types::{DirectoryPickResult, FileInfo},
-    utils::{is_subtitle_file, is_video_file},
+    subject_ranking::rank_metadata_candidates,
+    subtitle::convert_subtitles,
+    subtitle_encoding::convert_subtitle_encoding,
+    subtitle_timing::{retime_paired_subtitles, retime_subtitles},
+    scanner::{resolve_options, scan_dir},
+    types::{DirectoryPickResult, FileInfo, ScanOptions},
+    utils::{is_archive_file, is_subtitle_file, is_video_file},
 };
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -36,13 +75,39 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .invoke_handler(tauri::generate_handler![
             get_dropped_files,
+            match_episodes,
             rename_subtitle_files,
+            convert_subtitle_encoding,
+            convert_subtitles,
+            retime_subtitles,
+            retime_paired_subtitles,
+            convert_chinese_script,
+            list_archive_entries,
+            extract_archive_subtitles,
+            plan_rename,
+            plan_video_rename,
+            execute_rename_plan,
+            list_rename_history,
+            undo_rename_batch,
+            redo_rename_batch,
             pick_files_and_get_info,
             pick_directory_and_get_info,
             analyze_filename,
+            analyze_files,
+            cancel_analyze_job,
+            parse_anime_filename,
+            render_name,
             batch_analyze_filenames,
-            search_bangumi_subjects,
-            get_bangumi_subject_detail,
+            clear_recognition_cache,
+            search_metadata,
+            get_metadata_detail,
+            get_metadata_episodes,
+            lookup_episode_titles,
+            get_cover_image,
+            clear_metadata_cache,
+            rank_metadata_candidates,
+            get_season_map,
+            map_episode_numbers,
             load_settings,
             save_settings
         ])
@@ -59,7 +124,7 @@ async fn pick_files_and_get_info(app: tauri::AppHandle) -> Result<Vec<FileInfo>,
             "视频/字幕",
             &[
                 "mp4", "mkv", "avi", "mov", "wmv", "flv", "webm", "m4v", "rmvb", "3gp", "srt",
-                "ass", "ssa", "sub", "idx", "vtt", "txt", "smi", "sbv", "dfxp",
+                "ass", "ssa", "sub", "idx", "vtt", "txt", "smi", "sbv", "dfxp", "zip", "7z", "rar",
             ],
         )
         .blocking_pick_files(); // 多选
@@ -71,11 +136,13 @@ async fn pick_files_and_get_info(app: tauri::AppHandle) -> Result<Vec<FileInfo>,
             if let Some(name) = path_buf.file_name().and_then(|n| n.to_str()) {
                 let is_video = is_video_file(name);
                 let is_sub = is_subtitle_file(name);
-                if is_video || is_sub {
+                let is_archive = is_archive_file(name);
+                if is_video || is_sub || is_archive {
                     infos.push(FileInfo {
                         name: name.to_string(),
                         path: path_buf.to_string_lossy().to_string(),
                         is_video,
+                        is_archive,
                     });
                 }
             }
@@ -84,49 +151,21 @@ async fn pick_files_and_get_info(app: tauri::AppHandle) -> Result<Vec<FileInfo>,
     Ok(infos)
 }
 
+// options 为空时使用设置中的扫描选项
 #[tauri::command]
-async fn pick_directory_and_get_info(_: tauri::AppHandle) -> Result<DirectoryPickResult, String> {
-    let dir = rfd::FileDialog::new().pick_folder();
-    let mut infos: Vec<FileInfo> = Vec::new();
-
-    fn scan_dir(root: &std::path::Path, infos: &mut Vec<FileInfo>) -> std::io::Result<()> {
-        for entry in fs::read_dir(root)? {
-            let entry = entry?;
-            let p = entry.path();
-            if p.is_dir() {
-                continue;
-            } else if p.is_file() {
-                if let Some(name) = p.file_name().and_then(|n| n.to_str()) {
-                    let is_video = is_video_file(name);
-                    // 只选择视频文件，不选字幕文件
-                    if is_video {
-                        infos.push(FileInfo {
-                            name: name.to_string(),
-                            path: p.to_string_lossy().to_string(),
-                            is_video,
-                        });
-                    }
-                }
-            }
-        }
-        Ok(())
-    }
-
-    if dir.is_none() {
+async fn pick_directory_and_get_info(
+    _: tauri::AppHandle,
+    options: Option<ScanOptions>,
+) -> Result<DirectoryPickResult, String> {
+    let Some(root) = rfd::FileDialog::new().pick_folder() else {
         return Ok(DirectoryPickResult {
             files: Vec::new(),
             canceled: true,
         });
-    }
-
-    if let Some(root) = dir.as_ref() {
-        if let Err(e) = scan_dir(root.as_path(), &mut infos) {
-            return Err(format!("扫描文件夹失败: {}", e));
-        }
-    }
+    };
 
     Ok(DirectoryPickResult {
-        files: infos,
+        files: scan_dir(&root, &resolve_options(options))?,
         canceled: false,
     })
 }
@@ -0,0 +1,145 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::Write,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    settings::current_settings,
+    types::{AnimeInfo, LlmCacheEntry},
+    utils::llm_cache_path,
+};
+
+// 首次使用时从磁盘加载，之后在内存中维护并写回
+static CACHE: Mutex<Option<HashMap<String, LlmCacheEntry>>> = Mutex::new(None);
+
+pub(crate) fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn load_cache() -> HashMap<String, LlmCacheEntry> {
+    let Ok(path) = llm_cache_path() else {
+        return HashMap::new();
+    };
+    // 缓存文件损坏时直接丢弃，不影响识别
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(cache: &HashMap<String, LlmCacheEntry>) -> Result<(), String> {
+    let path = llm_cache_path()?;
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string(cache).map_err(|e| format!("序列化识别缓存失败: {}", e))?;
+    let mut f = fs::File::create(&tmp).map_err(|e| format!("创建识别缓存失败: {}", e))?;
+    f.write_all(json.as_bytes())
+        .map_err(|e| format!("保存识别缓存失败: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("保存识别缓存失败: {}", e))
+}
+
+fn is_expired(entry: &LlmCacheEntry, ttl_days: u64, now: u64) -> bool {
+    ttl_days > 0 && now.saturating_sub(entry.created_at) > ttl_days * 24 * 60 * 60
+}
+
+// 缓存键：提示词版本变化或换模型后旧结果自动失效
+pub fn cache_key(prompt_version: &str, model_url: &str, model_name: &str, input: &str) -> String {
+    format!(
+        "{}\n{}\n{}\n{}",
+        prompt_version,
+        model_url.trim(),
+        model_name.trim(),
+        input
+    )
+}
+
+// 查找未过期的缓存，ttl_days 为 0 时不过期
+pub fn lookup_entry(
+    cache: &HashMap<String, LlmCacheEntry>,
+    key: &str,
+    ttl_days: u64,
+    now: u64,
+) -> Option<AnimeInfo> {
+    let entry = cache.get(key)?;
+    if is_expired(entry, ttl_days, now) {
+        return None;
+    }
+    Some(entry.data.clone())
+}
+
+// 插入一条缓存，同时清除过期的记录，超出条目上限时淘汰最早的记录
+pub fn insert_entry(
+    cache: &mut HashMap<String, LlmCacheEntry>,
+    key: String,
+    data: AnimeInfo,
+    ttl_days: u64,
+    max: usize,
+    now: u64,
+) {
+    cache.insert(
+        key,
+        LlmCacheEntry {
+            created_at: now,
+            data,
+        },
+    );
+
+    cache.retain(|_, entry| !is_expired(entry, ttl_days, now));
+    if cache.len() > max {
+        let mut by_age: Vec<(u64, String)> = cache
+            .iter()
+            .map(|(k, entry)| (entry.created_at, k.clone()))
+            .collect();
+        by_age.sort();
+        for (_, k) in by_age.into_iter().take(cache.len() - max) {
+            cache.remove(&k);
+        }
+    }
+}
+
+pub(crate) fn get_cached(key: &str) -> Option<AnimeInfo> {
+    let ttl_days = current_settings().llm_cache_ttl_days;
+    let mut guard = CACHE.lock().ok()?;
+    let cache = guard.get_or_insert_with(load_cache);
+    lookup_entry(cache, key, ttl_days, now_secs())
+}
+
+// 写入缓存并保存到磁盘，写盘失败只记录日志
+pub(crate) fn put_cached(key: String, data: AnimeInfo) {
+    let settings = current_settings();
+    let Ok(mut guard) = CACHE.lock() else {
+        return;
+    };
+    let cache = guard.get_or_insert_with(load_cache);
+    insert_entry(
+        cache,
+        key,
+        data,
+        settings.llm_cache_ttl_days,
+        settings.llm_cache_max_entries,
+        now_secs(),
+    );
+
+    if let Err(e) = save_cache(cache) {
+        log::warn!("{}", e);
+    }
+}
+
+// 清空 LLM 识别缓存，返回清除的条目数
+#[tauri::command]
+pub async fn clear_recognition_cache() -> Result<usize, String> {
+    let mut guard = CACHE.lock().map_err(|_| "识别缓存被锁定")?;
+    let cache = guard.get_or_insert_with(load_cache);
+    let count = cache.len();
+    cache.clear();
+    let path = llm_cache_path()?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("删除识别缓存失败: {}", e))?;
+    }
+    Ok(count)
+}
@@ -0,0 +1,153 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde_json::{json, Map, Value};
+
+use crate::{parser::canonical_codec, types::AnimeInfo};
+
+// 单文件识别的输出结构，供支持结构化输出的接口约束模型
+pub fn anime_info_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "title": { "type": "string" },
+            "episode": { "type": "string" },
+            "codec": { "type": "string" },
+            "group": { "type": "string" }
+        },
+        "required": ["title", "episode", "codec", "group"],
+        "additionalProperties": false
+    })
+}
+
+// 批量推测标题的输出结构
+pub fn title_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "title": { "type": "string" }
+        },
+        "required": ["title"],
+        "additionalProperties": false
+    })
+}
+
+fn think_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?is)<(seed:think|think|thinking|reasoning)>.*?</(seed:think|think|thinking|reasoning)>")
+            .unwrap()
+    })
+}
+
+fn think_end_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)</(seed:think|think|thinking|reasoning)>").unwrap())
+}
+
+// 去除思考链；部分模板省略了开头的 <think>，此时丢弃最后一个结束标签之前的内容
+pub fn strip_think(content: &str) -> String {
+    let cleaned = think_re().replace_all(content, "");
+    match think_end_re().find_iter(&cleaned).last() {
+        Some(m) => cleaned[m.end()..].to_string(),
+        None => cleaned.to_string(),
+    }
+}
+
+// 从 start 处的 { 开始查找配对的 }，跳过字符串中的括号
+fn balanced_end(text: &str, start: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text[start..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(start + i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// 找到输出中第一个完整且合法的 JSON 对象，忽略前后的说明文字和代码块标记
+pub fn first_json_object(content: &str) -> Option<Map<String, Value>> {
+    content.match_indices('{').find_map(|(start, _)| {
+        let end = balanced_end(content, start)?;
+        match serde_json::from_str::<Value>(&content[start..end]) {
+            Ok(Value::Object(map)) => Some(map),
+            _ => None,
+        }
+    })
+}
+
+// 字符串或数字字段统一转为去空白的字符串，空值视为缺失
+fn string_field(map: &Map<String, Value>, key: &str) -> Result<Option<String>, String> {
+    match map.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => {
+            let s = s.trim();
+            Ok((!s.is_empty()).then(|| s.to_string()))
+        }
+        Some(Value::Number(n)) => Ok(Some(n.to_string())),
+        Some(other) => Err(format!("字段 {} 类型错误: {}", key, other)),
+    }
+}
+
+// 集数补零为两位，带小数的集数（如 6.5）只补整数部分
+pub fn normalize_episode(raw: &str) -> String {
+    let raw = raw
+        .trim()
+        .trim_start_matches(['E', 'e', '第'])
+        .trim_end_matches(['话', '話', '集']);
+    let (int, frac) = match raw.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (raw, None),
+    };
+    match (int.parse::<u32>(), frac) {
+        (Ok(n), Some(frac)) if frac.chars().all(|c| c.is_ascii_digit()) => {
+            format!("{:02}.{}", n, frac)
+        }
+        (Ok(n), None) => format!("{:02}", n),
+        _ => raw.to_string(),
+    }
+}
+
+// 按输出结构校验模型返回的 JSON，并规范化集数和编码名称
+pub fn validate_anime_info(map: &Map<String, Value>) -> Result<AnimeInfo, String> {
+    let title = string_field(map, "title")?.ok_or("缺少 title 字段")?;
+    let episode = string_field(map, "episode")?.map(|e| normalize_episode(&e));
+    let codec =
+        string_field(map, "codec")?.map(|c| canonical_codec(&c).map(str::to_string).unwrap_or(c));
+    let group = string_field(map, "group")?;
+    let year = string_field(map, "year")?.and_then(|y| y.parse::<i32>().ok());
+    Ok(AnimeInfo {
+        title,
+        episode,
+        codec,
+        group,
+        year,
+        ..Default::default()
+    })
+}
+
+// 解析模型输出：去除思考链，取第一个 JSON 对象并校验
+pub fn parse_anime_info(content: &str) -> Result<AnimeInfo, String> {
+    let cleaned = strip_think(content);
+    let map = first_json_object(&cleaned).ok_or("LLM输出中没有找到 JSON 对象")?;
+    validate_anime_info(&map)
+}
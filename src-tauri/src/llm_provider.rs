@@ -0,0 +1,208 @@
+use serde_json::{json, Value};
+
+use crate::{
+    http::{http_client, send_with_retry, RequestKind},
+    settings::current_settings,
+    types::LlmProviderKind,
+};
+
+// 一次对话请求，各接口格式共用
+#[derive(Clone, Copy)]
+pub struct ChatRequest<'a> {
+    pub model_url: &'a str,
+    pub model_name: &'a str,
+    pub system: &'a str,
+    pub user: &'a str,
+    pub temperature: f32,
+    pub max_tokens: u32,
+    // 期望的输出 JSON Schema，接口支持结构化输出时用于约束模型
+    pub schema: Option<&'a Value>,
+}
+
+// 不同 LLM 后端的请求/响应格式
+pub trait LlmProvider: Send + Sync {
+    fn body(&self, chat: &ChatRequest) -> Value;
+    fn headers(&self, api_key: &str) -> Vec<(&'static str, String)>;
+    // 从响应 JSON 中取出模型输出的文本
+    fn content(&self, response: &Value) -> Option<String>;
+}
+
+fn bearer_header(api_key: &str) -> Vec<(&'static str, String)> {
+    if api_key.is_empty() {
+        Vec::new()
+    } else {
+        vec![("Authorization", format!("Bearer {}", api_key))]
+    }
+}
+
+// OpenAI 兼容的 /v1/chat/completions（LM Studio、vLLM、Ollama 兼容接口等）
+pub struct OpenAiProvider;
+
+impl LlmProvider for OpenAiProvider {
+    fn body(&self, chat: &ChatRequest) -> Value {
+        let mut body = json!({
+            "model": chat.model_name,
+            "messages": [
+                { "role": "system", "content": chat.system },
+                { "role": "user", "content": chat.user }
+            ],
+            "temperature": chat.temperature,
+            "max_tokens": chat.max_tokens
+        });
+        if let Some(schema) = chat.schema {
+            body["response_format"] = json!({
+                "type": "json_schema",
+                "json_schema": { "name": "result", "strict": true, "schema": schema }
+            });
+        }
+        body
+    }
+
+    fn headers(&self, api_key: &str) -> Vec<(&'static str, String)> {
+        bearer_header(api_key)
+    }
+
+    fn content(&self, response: &Value) -> Option<String> {
+        response["choices"][0]["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+    }
+}
+
+// Ollama 原生 /api/chat
+pub struct OllamaProvider;
+
+impl LlmProvider for OllamaProvider {
+    fn body(&self, chat: &ChatRequest) -> Value {
+        let mut body = json!({
+            "model": chat.model_name,
+            "messages": [
+                { "role": "system", "content": chat.system },
+                { "role": "user", "content": chat.user }
+            ],
+            "stream": false,
+            "options": {
+                "temperature": chat.temperature,
+                "num_predict": chat.max_tokens
+            }
+        });
+        if let Some(schema) = chat.schema {
+            body["format"] = schema.clone();
+        }
+        body
+    }
+
+    fn headers(&self, api_key: &str) -> Vec<(&'static str, String)> {
+        bearer_header(api_key)
+    }
+
+    fn content(&self, response: &Value) -> Option<String> {
+        response["message"]["content"].as_str().map(str::to_string)
+    }
+}
+
+// llama.cpp server 的 /completion，没有对话结构，系统提示与用户输入拼接为一段文本
+pub struct LlamaCppProvider;
+
+impl LlmProvider for LlamaCppProvider {
+    fn body(&self, chat: &ChatRequest) -> Value {
+        let mut body = json!({
+            "prompt": format!("{}\n\n{}\n", chat.system.trim(), chat.user),
+            "temperature": chat.temperature,
+            "n_predict": chat.max_tokens,
+            "stream": false
+        });
+        if let Some(schema) = chat.schema {
+            body["json_schema"] = schema.clone();
+        }
+        body
+    }
+
+    fn headers(&self, api_key: &str) -> Vec<(&'static str, String)> {
+        bearer_header(api_key)
+    }
+
+    fn content(&self, response: &Value) -> Option<String> {
+        response["content"].as_str().map(str::to_string)
+    }
+}
+
+// Messages 格式的 /v1/messages，max_tokens 为必填且有上限；没有 JSON 模式，依赖提示词和输出解析
+pub struct MessagesProvider;
+
+const MESSAGES_MAX_TOKENS: u32 = 8192;
+
+impl LlmProvider for MessagesProvider {
+    fn body(&self, chat: &ChatRequest) -> Value {
+        json!({
+            "model": chat.model_name,
+            "system": chat.system,
+            "messages": [
+                { "role": "user", "content": chat.user }
+            ],
+            "temperature": chat.temperature,
+            "max_tokens": chat.max_tokens.min(MESSAGES_MAX_TOKENS)
+        })
+    }
+
+    fn headers(&self, api_key: &str) -> Vec<(&'static str, String)> {
+        let mut headers = vec![("anthropic-version", "2023-06-01".to_string())];
+        if !api_key.is_empty() {
+            headers.push(("x-api-key", api_key.to_string()));
+        }
+        headers
+    }
+
+    fn content(&self, response: &Value) -> Option<String> {
+        let text: String = response["content"]
+            .as_array()?
+            .iter()
+            .filter(|block| block["type"] == "text")
+            .filter_map(|block| block["text"].as_str())
+            .collect();
+        Some(text)
+    }
+}
+
+pub fn provider_for(kind: LlmProviderKind) -> &'static dyn LlmProvider {
+    match kind {
+        LlmProviderKind::OpenAi => &OpenAiProvider,
+        LlmProviderKind::Ollama => &OllamaProvider,
+        LlmProviderKind::LlamaCpp => &LlamaCppProvider,
+        LlmProviderKind::Messages => &MessagesProvider,
+    }
+}
+
+// 按设置中选择的接口格式发送对话请求，返回模型输出的文本
+pub async fn send_chat(chat: &ChatRequest<'_>) -> Result<String, String> {
+    let settings = current_settings();
+    let provider = provider_for(settings.llm_provider);
+    // 部分兼容接口不支持结构化输出，可在设置中关闭
+    let chat = &ChatRequest {
+        schema: chat.schema.filter(|_| settings.llm_structured_output),
+        ..*chat
+    };
+
+    let mut builder = http_client()
+        .post(chat.model_url)
+        .header("Content-Type", "application/json")
+        .json(&provider.body(chat));
+    for (name, value) in provider.headers(settings.llm_api_key.trim()) {
+        builder = builder.header(name, value);
+    }
+
+    let response = send_with_retry(builder, RequestKind::Llm)
+        .await
+        .map_err(|e| format!("请求LLM模型失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("LLM模型返回错误状态码: {}", response.status()));
+    }
+
+    let response_json: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("解析LLM响应失败: {}", e))?;
+    provider
+        .content(&response_json)
+        .ok_or("无法从LLM响应中获取内容".to_string())
+}
@@ -1,18 +1,61 @@
-use std::time::Duration;
-
-use regex::Regex;
-use serde_json;
-
 use crate::{
-    types::{
-        AnimeInfo, BangumiSubject, BangumiSubjectDetail, BatchLLMRequest, BatchLLMResponse, LLMRequest, LLMResponse,
-    },
-    utils::is_subtitle_file,
+    llm_cache::{cache_key, get_cached, put_cached},
+    llm_output::{anime_info_schema, parse_anime_info, title_schema},
+    llm_provider::{send_chat, ChatRequest},
+    parser::parse_filename,
+    settings::current_settings,
+    types::{BatchLLMRequest, BatchLLMResponse, LLMRequest, LLMResponse},
 };
 
+// 修改提示词后需要更新版本号，使旧的缓存结果失效
+const ANALYZE_PROMPT_VERSION: &str = "analyze-v2";
+const BATCH_PROMPT_VERSION: &str = "batch-v2";
+
+// 输出只是一段短 JSON；Ollama 的 num_predict、llama.cpp 的 n_predict 会按此值预留生成长度，不能过大
+const ANALYZE_MAX_TOKENS: u32 = 512;
+const BATCH_MAX_TOKENS: u32 = 2048;
+
 // 分析单个文件名，调用 LLM
 #[tauri::command]
 pub async fn analyze_filename(request: LLMRequest) -> Result<LLMResponse, String> {
+    // 先用规则解析，置信度足够时不再调用 LLM
+    if !request.force_llm {
+        let parsed = parse_filename(&request.filename);
+        if parsed.confidence >= current_settings().parser_confidence_threshold {
+            return Ok(LLMResponse {
+                success: true,
+                data: Some(parsed.info),
+                error: None,
+                engine: Some("parser".to_string()),
+            });
+        }
+    }
+
+    let key = cache_key(
+        ANALYZE_PROMPT_VERSION,
+        &request.model_url,
+        &request.model_name,
+        &request.filename,
+    );
+    if !request.bypass_cache {
+        if let Some(info) = get_cached(&key) {
+            return Ok(LLMResponse {
+                success: true,
+                data: Some(info),
+                error: None,
+                engine: Some("cache".to_string()),
+            });
+        }
+    }
+
+    let response = request_analysis(&request).await?;
+    if let (true, Some(info)) = (response.success, &response.data) {
+        put_cached(key, info.clone());
+    }
+    Ok(response)
+}
+
+async fn request_analysis(request: &LLMRequest) -> Result<LLMResponse, String> {
     let prompt = r#"
 你是动漫视频信息抽取专家，请仅返回 JSON，不要额外说明。
 提取要求
@@ -25,83 +68,46 @@ pub async fn analyze_filename(request: LLMRequest) -> Result<LLMResponse, String
 "#
     .to_string();
     let user_content = format!("这是视频文件名，请提取相关信息：{}", request.filename);
-    let request_body = serde_json::json!({
-        "model": request.model_name,
-        "messages": [
-            { "role": "system", "content": prompt },
-            { "role": "user", "content": user_content }
-        ],
-        "temperature": 0.1,
-        "max_tokens": 500000
-    });
-
-    // 构建HTTP客户端
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(300))
-        .build()
-        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
-
-    // 调用 LLM 模型
-    let response = client
-        .post(&request.model_url)
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("请求LLM模型失败: {}", e))?;
-
-    if !response.status().is_success() {
-        return Ok(LLMResponse {
-            success: false,
-            data: None,
-            error: Some(format!("LLM模型返回错误状态码: {}", response.status())),
-        });
-    }
-
-    // 解析响应
-    let response_json: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析LLM响应失败: {}", e))?;
-
-    // 获取 content 字段
-    let content = response_json["choices"][0]["message"]["content"]
-        .as_str()
-        .ok_or("无法从LLM响应中获取内容")?;
+    let content = match send_chat(&ChatRequest {
+        model_url: &request.model_url,
+        model_name: &request.model_name,
+        system: &prompt,
+        user: &user_content,
+        temperature: 0.1,
+        max_tokens: ANALYZE_MAX_TOKENS,
+        schema: Some(&anime_info_schema()),
+    })
+    .await
+    {
+        Ok(content) => content,
+        Err(error) => {
+            return Ok(LLMResponse {
+                success: false,
+                data: None,
+                error: Some(error),
+                engine: None,
+            })
+        }
+    };
 
     println!("LLM响应内容: {}", content);
 
-    // 清理思考链 - 去除 <seed:think>xxx</seed:think> 结构
-    let re = Regex::new(r"(?s)<seed:think>.*?</seed:think>").unwrap();
-    let cleaned_content = re.replace_all(content, "").to_string();
-    match serde_json::from_str::<AnimeInfo>(&cleaned_content) {
+    match parse_anime_info(&content) {
         Ok(anime_info) => Ok(LLMResponse {
             success: true,
             data: Some(anime_info),
             error: None,
+            engine: Some("llm".to_string()),
+        }),
+        Err(error) => Ok(LLMResponse {
+            success: false,
+            data: None,
+            error: Some(format!(
+                "解析LLM响应格式失败: {}，原始输出: {}",
+                error, content
+            )),
+            engine: None,
         }),
-        Err(_) => {
-            // 如果直接解析失败，尝试去除代码块后再解析
-            let final_content = cleaned_content
-                .trim()
-                .trim_start_matches("```json")
-                .trim_start_matches("```")
-                .trim_end_matches("```")
-                .trim();
-
-            match serde_json::from_str::<AnimeInfo>(final_content) {
-                Ok(anime_info) => Ok(LLMResponse {
-                    success: true,
-                    data: Some(anime_info),
-                    error: None,
-                }),
-                Err(_) => Ok(LLMResponse {
-                    success: false,
-                    data: None,
-                    error: Some(format!("解析LLM响应格式失败: {}", content)),
-                }),
-            }
-        }
     }
 }
 
@@ -116,6 +122,30 @@ pub async fn batch_analyze_filenames(request: BatchLLMRequest) -> Result<BatchLL
         });
     }
 
+    let key = cache_key(
+        BATCH_PROMPT_VERSION,
+        &request.model_url,
+        &request.model_name,
+        &request.filenames.join("\n"),
+    );
+    if !request.bypass_cache {
+        if let Some(info) = get_cached(&key) {
+            return Ok(BatchLLMResponse {
+                success: true,
+                data: Some(info),
+                error: None,
+            });
+        }
+    }
+
+    let response = request_batch_analysis(&request).await?;
+    if let (true, Some(info)) = (response.success, &response.data) {
+        put_cached(key, info.clone());
+    }
+    Ok(response)
+}
+
+async fn request_batch_analysis(request: &BatchLLMRequest) -> Result<BatchLLMResponse, String> {
     let prompt = r#"
 你是动漫信息聚合专家，需根据一组文件名推断它们对应的同一部动画标题。
 
@@ -142,257 +172,42 @@ pub async fn batch_analyze_filenames(request: BatchLLMRequest) -> Result<BatchLL
         filenames_text
     );
 
-    let request_body = serde_json::json!({
-        "model": request.model_name,
-        "messages": [
-            { "role": "system", "content": prompt },
-            { "role": "user", "content": user_content }
-        ],
-        "temperature": 0.3,
-        "max_tokens": 200000
-    });
-
-    // 构建HTTP客户端
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(300))
-        .build()
-        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
-
-    // 调用 LLM 模型
-    let response = client
-        .post(&request.model_url)
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("请求LLM模型失败: {}", e))?;
-
-    if !response.status().is_success() {
-        return Ok(BatchLLMResponse {
-            success: false,
-            data: None,
-            error: Some(format!("LLM模型返回错误状态码: {}", response.status())),
-        });
-    }
-
-    // 解析响应
-    let response_json: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("解析LLM响应失败: {}", e))?;
-
-    // 获取 content 字段
-    let content = response_json["choices"][0]["message"]["content"]
-        .as_str()
-        .ok_or("无法从LLM响应中获取内容")?;
+    let content = match send_chat(&ChatRequest {
+        model_url: &request.model_url,
+        model_name: &request.model_name,
+        system: prompt,
+        user: &user_content,
+        temperature: 0.3,
+        max_tokens: BATCH_MAX_TOKENS,
+        schema: Some(&title_schema()),
+    })
+    .await
+    {
+        Ok(content) => content,
+        Err(error) => {
+            return Ok(BatchLLMResponse {
+                success: false,
+                data: None,
+                error: Some(error),
+            })
+        }
+    };
 
     println!("批量LLM响应内容: {}", content);
 
-    // 清理思考链 - 去除 <seed:think>xxx</seed:think> 结构
-    let re = Regex::new(r"(?s)<seed:think>.*?</seed:think>").unwrap();
-    let cleaned_content = re.replace_all(content, "").to_string();
-    match serde_json::from_str::<AnimeInfo>(&cleaned_content) {
+    match parse_anime_info(&content) {
         Ok(result) => Ok(BatchLLMResponse {
             success: true,
             data: Some(result),
             error: None,
         }),
-        Err(_) => {
-            // 如果直接解析失败，尝试去除代码块后再解析
-            let final_content = cleaned_content
-                .trim()
-                .trim_start_matches("```json")
-                .trim_start_matches("```")
-                .trim_end_matches("```")
-                .trim();
-
-            match serde_json::from_str::<AnimeInfo>(final_content) {
-                Ok(result) => Ok(BatchLLMResponse {
-                    success: true,
-                    data: Some(result),
-                    error: None,
-                }),
-                Err(_) => Ok(BatchLLMResponse {
-                    success: false,
-                    data: None,
-                    error: Some(format!("解析LLM响应格式失败: {}", content)),
-                }),
-            }
-        }
-    }
-}
-
-#[tauri::command]
-pub async fn search_bangumi_subjects(
-    query: String,
-    limit: Option<usize>,
-) -> Result<Vec<BangumiSubject>, String> {
-    let q = query.trim();
-    if q.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    let max = limit.unwrap_or(10);
-    let encoded = urlencoding::encode(q);
-    let url = format!(
-        "https://api.bgm.tv/search/subject/{}?type=2&responseGroup=small&max_results={}",
-        encoded, max
-    );
-
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()
-        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
-
-    let resp = client
-        .get(url)
-        .send()
-        .await
-        .map_err(|e| format!("请求Bangumi失败: {}", e))?;
-    if !resp.status().is_success() {
-        return Err(format!("Bangumi返回错误状态码: {}", resp.status()));
-    }
-
-    let v: serde_json::Value = resp
-        .json()
-        .await
-        .map_err(|e| format!("解析Bangumi响应失败: {}", e))?;
-
-    let mut items: Vec<BangumiSubject> = Vec::new();
-    if let Some(list) = v.get("list").and_then(|x| x.as_array()) {
-        for it in list {
-            let id = it.get("id").and_then(|x| x.as_i64()).unwrap_or(0);
-            let name = it
-                .get("name")
-                .and_then(|x| x.as_str())
-                .unwrap_or("")
-                .to_string();
-            let name_cn = it
-                .get("name_cn")
-                .and_then(|x| x.as_str())
-                .map(|s| s.to_string());
-            let subject_type = it.get("type").and_then(|x| x.as_i64()).map(|n| n as i32);
-            let date = it
-                .get("date")
-                .or_else(|| it.get("air_date"))
-                .and_then(|x| x.as_str())
-                .map(|s| s.to_string());
-            if id != 0 && !name.is_empty() {
-                items.push(BangumiSubject {
-                    id,
-                    name,
-                    name_cn,
-                    subject_type,
-                    date,
-                });
-            }
-        }
-    } else if let Some(arr) = v.as_array() {
-        for it in arr {
-            let id = it.get("id").and_then(|x| x.as_i64()).unwrap_or(0);
-            let name = it
-                .get("name")
-                .and_then(|x| x.as_str())
-                .unwrap_or("")
-                .to_string();
-            let name_cn = it
-                .get("name_cn")
-                .and_then(|x| x.as_str())
-                .map(|s| s.to_string());
-            let subject_type = it.get("type").and_then(|x| x.as_i64()).map(|n| n as i32);
-            let date = it
-                .get("date")
-                .or_else(|| it.get("air_date"))
-                .and_then(|x| x.as_str())
-                .map(|s| s.to_string());
-            if id != 0 && !name.is_empty() {
-                items.push(BangumiSubject {
-                    id,
-                    name,
-                    name_cn,
-                    subject_type,
-                    date,
-                });
-            }
-        }
-    }
-
-    Ok(items)
-}
-
-#[tauri::command]
-pub async fn get_bangumi_subject_detail(id: i64) -> Result<BangumiSubjectDetail, String> {
-    let url = format!("https://api.bgm.tv/subject/{}", id);
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()
-        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
-
-    let resp = client
-        .get(url)
-        .send()
-        .await
-        .map_err(|e| format!("请求Bangumi失败: {}", e))?;
-    if !resp.status().is_success() {
-        return Err(format!("Bangumi返回错误状态码: {}", resp.status()));
+        Err(error) => Ok(BatchLLMResponse {
+            success: false,
+            data: None,
+            error: Some(format!(
+                "解析LLM响应格式失败: {}，原始输出: {}",
+                error, content
+            )),
+        }),
     }
-
-    let v: serde_json::Value = resp
-        .json()
-        .await
-        .map_err(|e| format!("解析Bangumi响应失败: {}", e))?;
-
-    let id_v = v.get("id").and_then(|x| x.as_i64()).unwrap_or(id);
-    let name = v
-        .get("name")
-        .and_then(|x| x.as_str())
-        .unwrap_or("")
-        .to_string();
-    let name_cn = v
-        .get("name_cn")
-        .and_then(|x| x.as_str())
-        .map(|s| s.to_string());
-
-    let cover_url = v
-        .get("images")
-        .and_then(|imgs| imgs.get("large").or_else(|| imgs.get("common")))
-        .and_then(|x| x.as_str())
-        .map(|s| s.to_string())
-        .or_else(|| {
-            v.get("cover")
-                .and_then(|x| x.as_str())
-                .map(|s| s.to_string())
-        });
-
-    let episodes = v
-        .get("eps")
-        .and_then(|x| x.as_i64())
-        .map(|n| n as i32)
-        .or_else(|| {
-            v.get("total_episodes")
-                .and_then(|x| x.as_i64())
-                .map(|n| n as i32)
-        })
-        .or_else(|| {
-            v.get("episodes")
-                .and_then(|x| x.as_array())
-                .map(|arr| arr.len() as i32)
-        });
-
-    let date_str = v
-        .get("date")
-        .or_else(|| v.get("air_date"))
-        .and_then(|x| x.as_str());
-    let year = date_str
-        .and_then(|s| s.get(0..4))
-        .and_then(|y| y.parse::<i32>().ok());
-
-    Ok(BangumiSubjectDetail {
-        id: id_v,
-        name,
-        name_cn,
-        cover_url,
-        episodes,
-        year,
-    })
 }
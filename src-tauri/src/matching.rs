@@ -0,0 +1,170 @@
+use std::{collections::BTreeMap, path::Path, sync::OnceLock};
+
+use regex::Regex;
+
+use crate::{
+    types::{AmbiguousEpisode, EpisodeMatchResult, EpisodePair, FileInfo, UnmatchedFile},
+    utils::detect_language_tag,
+};
+
+// 内置的集数匹配规则，在自定义正则失败后依次尝试
+fn fallback_patterns() -> &'static [Regex] {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            // S01E01
+            r"(?i)S\d{1,2}E(\d{1,4})",
+            // 第01话 / 第01話 / 第01集
+            r"第\s*(\d{1,4})\s*[话話集]",
+            // [01] / [01v2] / [12END]
+            r"(?i)\[(\d{1,3})(?:v\d+)?(?:\s?END|完)?\]",
+            // Title - 01 / Title - 01v2
+            r"(?i)\s-\s(\d{1,4})(?:v\d+)?(?:[\s\[(.]|$)",
+            // EP01 / Ep.01 / E01
+            r"(?i)(?:^|[^a-z0-9])(?:EP[\s._]?|E)(\d{1,4})(?:v\d+)?(?:[^a-z0-9]|$)",
+        ]
+        .iter()
+        .map(|p| Regex::new(p).unwrap())
+        .collect()
+    })
+}
+
+// 数字集数统一补零为两位，方便不同写法（1 / 01 / 001）互相匹配
+fn normalize_episode(raw: &str) -> String {
+    let raw = raw.trim();
+    match raw.parse::<u32>() {
+        Ok(n) => format!("{:02}", n),
+        Err(_) => raw.to_string(),
+    }
+}
+
+fn capture_episode(re: &Regex, text: &str) -> Option<String> {
+    let caps = re.captures(text)?;
+    let m = caps.get(1).or_else(|| caps.get(0))?;
+    let ep = normalize_episode(m.as_str());
+    if ep.is_empty() {
+        None
+    } else {
+        Some(ep)
+    }
+}
+
+// 从文件名中提取集数：先用自定义正则，再依次尝试内置规则
+pub fn extract_episode(filename: &str, custom: Option<&Regex>) -> Option<String> {
+    let stem = Path::new(filename)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(filename);
+
+    if let Some(re) = custom {
+        if let Some(ep) = capture_episode(re, stem) {
+            return Some(ep);
+        }
+    }
+    fallback_patterns()
+        .iter()
+        .find_map(|re| capture_episode(re, stem))
+}
+
+// 集数排序：数字按数值，其余按字符串
+fn episode_sort_key(ep: &str) -> (u32, String) {
+    (ep.parse::<u32>().unwrap_or(u32::MAX), ep.to_string())
+}
+
+// 按集数配对视频与字幕
+pub fn pair_episodes(
+    video_files: &[FileInfo],
+    subtitle_files: &[FileInfo],
+    custom: Option<&Regex>,
+) -> EpisodeMatchResult {
+    let mut result = EpisodeMatchResult::default();
+    let mut groups: BTreeMap<(u32, String), (Vec<FileInfo>, Vec<FileInfo>)> = BTreeMap::new();
+
+    for video in video_files {
+        match extract_episode(&video.name, custom) {
+            Some(ep) => groups
+                .entry(episode_sort_key(&ep))
+                .or_default()
+                .0
+                .push(video.clone()),
+            None => result.unmatched_videos.push(UnmatchedFile {
+                episode: None,
+                file: video.clone(),
+            }),
+        }
+    }
+    for subtitle in subtitle_files {
+        match extract_episode(&subtitle.name, custom) {
+            Some(ep) => groups
+                .entry(episode_sort_key(&ep))
+                .or_default()
+                .1
+                .push(subtitle.clone()),
+            None => result.unmatched_subtitles.push(UnmatchedFile {
+                episode: None,
+                file: subtitle.clone(),
+            }),
+        }
+    }
+
+    for ((_, episode), (videos, subtitles)) in groups {
+        // 同一集的多个字幕只要语言标记互不相同，就视为多轨道而非歧义
+        let mut tags: Vec<Option<String>> = subtitles
+            .iter()
+            .map(|s| detect_language_tag(&s.name).map(|t| t.to_lowercase()))
+            .collect();
+        tags.sort();
+        tags.dedup();
+        let duplicate_tracks = tags.len() != subtitles.len();
+
+        if videos.len() > 1 || duplicate_tracks {
+            result.ambiguous.push(AmbiguousEpisode {
+                episode,
+                videos,
+                subtitles,
+            });
+        } else if videos.is_empty() {
+            result
+                .unmatched_subtitles
+                .extend(subtitles.into_iter().map(|file| UnmatchedFile {
+                    episode: Some(episode.clone()),
+                    file,
+                }));
+        } else if subtitles.is_empty() {
+            result
+                .unmatched_videos
+                .extend(videos.into_iter().map(|file| UnmatchedFile {
+                    episode: Some(episode.clone()),
+                    file,
+                }));
+        } else {
+            result.pairs.push(EpisodePair {
+                episode,
+                video: videos.into_iter().next().unwrap(),
+                subtitles,
+            });
+        }
+    }
+
+    result
+}
+
+// 按集数配对视频与字幕文件，episode_regex 为设置中的自定义正则
+#[tauri::command]
+pub async fn match_episodes(
+    video_files: Vec<FileInfo>,
+    subtitle_files: Vec<FileInfo>,
+    episode_regex: Option<String>,
+) -> Result<EpisodeMatchResult, String> {
+    let custom = match episode_regex.as_deref().map(str::trim) {
+        Some(pattern) if !pattern.is_empty() => {
+            Some(Regex::new(pattern).map_err(|e| format!("剧集匹配正则无效: {}", e))?)
+        }
+        _ => None,
+    };
+    Ok(pair_episodes(
+        &video_files,
+        &subtitle_files,
+        custom.as_ref(),
+    ))
+}
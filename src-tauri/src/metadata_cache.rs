@@ -0,0 +1,359 @@
+use std::{
+    fs,
+    future::Future,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use base64::Engine;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    http::{http_client, send_with_retry, RequestKind},
+    llm_cache::now_secs,
+    metadata_provider::MetadataProvider,
+    settings::current_settings,
+    types::{MetadataEpisode, MetadataSubject, MetadataSubjectDetail},
+    utils::metadata_cache_dir,
+};
+
+const COVER_DIR: &str = "covers";
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<T> {
+    // 文件名是键的哈希，保存原始键以排除哈希冲突
+    key: String,
+    created_at: u64,
+    data: T,
+}
+
+// 搜索结果按关键词缓存，limit 不超过缓存时的数量时直接截取
+#[derive(Serialize, Deserialize)]
+struct CachedSearch {
+    limit: usize,
+    subjects: Vec<MetadataSubject>,
+}
+
+// 缓存的有效期与离线模式，默认取自设置
+#[derive(Clone, Copy, Debug)]
+pub struct CachePolicy {
+    // 为 0 时不过期
+    pub ttl_days: u64,
+    pub offline_mode: bool,
+    // 当前时间（Unix 秒），用于判断是否过期和记录写入时间
+    pub now: u64,
+}
+
+impl CachePolicy {
+    pub fn from_settings() -> Self {
+        let settings = current_settings();
+        CachePolicy {
+            ttl_days: settings.metadata_cache_ttl_days,
+            offline_mode: settings.offline_mode,
+            now: now_secs(),
+        }
+    }
+}
+
+// FNV-1a，保证不同版本间文件名稳定
+fn fnv1a(key: &str) -> u64 {
+    key.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn entry_path(namespace: &str, category: &str, key: &str) -> Result<PathBuf, String> {
+    Ok(metadata_cache_dir()?
+        .join(namespace)
+        .join(category)
+        .join(format!("{:016x}.json", fnv1a(key))))
+}
+
+fn is_expired(created_at: u64, ttl_days: u64, now: u64) -> bool {
+    ttl_days > 0 && now.saturating_sub(created_at) > ttl_days * 24 * 60 * 60
+}
+
+// 缓存文件损坏或键不一致时视为未命中
+fn read_entry<T: DeserializeOwned>(path: &Path, key: &str) -> Option<CacheEntry<T>> {
+    let content = fs::read_to_string(path).ok()?;
+    let entry: CacheEntry<T> = serde_json::from_str(&content).ok()?;
+    (entry.key == key).then_some(entry)
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("创建元数据缓存目录失败: {}", e))?;
+    }
+    let tmp = path.with_extension("tmp");
+    let mut f = fs::File::create(&tmp).map_err(|e| format!("创建元数据缓存失败: {}", e))?;
+    f.write_all(bytes)
+        .map_err(|e| format!("保存元数据缓存失败: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("保存元数据缓存失败: {}", e))
+}
+
+fn write_entry<T: Serialize>(path: &Path, entry: &CacheEntry<T>) -> Result<(), String> {
+    let json = serde_json::to_vec(entry).map_err(|e| format!("序列化元数据缓存失败: {}", e))?;
+    write_file(path, &json)
+}
+
+// 读取缓存，未命中或过期时请求并写入；请求失败时退回过期的缓存，离线模式下只读缓存
+pub(crate) async fn cached<T, F>(
+    namespace: &str,
+    category: &str,
+    key: &str,
+    fetch: F,
+) -> Result<T, String>
+where
+    T: Serialize + DeserializeOwned,
+    F: Future<Output = Result<T, String>>,
+{
+    cached_if(namespace, category, key, |_| true, fetch).await
+}
+
+// usable 判断未过期的缓存能否满足本次请求，不满足时按过期处理
+async fn cached_if<T, F>(
+    namespace: &str,
+    category: &str,
+    key: &str,
+    usable: impl Fn(&T) -> bool,
+    fetch: F,
+) -> Result<T, String>
+where
+    T: Serialize + DeserializeOwned,
+    F: Future<Output = Result<T, String>>,
+{
+    let path = entry_path(namespace, category, key).ok();
+    cached_at(
+        path.as_deref(),
+        key,
+        CachePolicy::from_settings(),
+        usable,
+        fetch,
+    )
+    .await
+}
+
+// 以 path 为缓存文件执行 cached_if 的逻辑，path 为空时不读写缓存
+pub async fn cached_at<T, F>(
+    path: Option<&Path>,
+    key: &str,
+    policy: CachePolicy,
+    usable: impl Fn(&T) -> bool,
+    fetch: F,
+) -> Result<T, String>
+where
+    T: Serialize + DeserializeOwned,
+    F: Future<Output = Result<T, String>>,
+{
+    let entry = path.and_then(|p| read_entry::<T>(p, key));
+    let now = policy.now;
+    let entry = match entry {
+        Some(entry)
+            if policy.offline_mode
+                || (!is_expired(entry.created_at, policy.ttl_days, now) && usable(&entry.data)) =>
+        {
+            return Ok(entry.data)
+        }
+        other => other,
+    };
+    if policy.offline_mode {
+        return Err(format!("离线模式下没有缓存的数据: {}", key));
+    }
+    match fetch.await {
+        Ok(data) => {
+            let entry = CacheEntry {
+                key: key.to_string(),
+                created_at: now,
+                data,
+            };
+            if let Some(path) = path {
+                if let Err(e) = write_entry(path, &entry) {
+                    log::warn!("{}", e);
+                }
+            }
+            Ok(entry.data)
+        }
+        Err(e) => match entry {
+            Some(stale) => {
+                log::warn!("{}，使用过期的缓存", e);
+                Ok(stale.data)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+// 为元数据来源加一层磁盘缓存，namespace 区分不同来源
+pub struct CachedProvider<P> {
+    namespace: &'static str,
+    inner: P,
+}
+
+impl<P: MetadataProvider> CachedProvider<P> {
+    pub fn new(namespace: &'static str, inner: P) -> Self {
+        CachedProvider { namespace, inner }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    pub fn namespace(&self) -> &'static str {
+        self.namespace
+    }
+}
+
+#[async_trait]
+impl<P: MetadataProvider> MetadataProvider for CachedProvider<P> {
+    async fn search(&self, keyword: &str, limit: usize) -> Result<Vec<MetadataSubject>, String> {
+        let key = keyword.trim().to_lowercase();
+        // 缓存时请求的数量不够时重新搜索
+        let search: CachedSearch = cached_if(
+            self.namespace,
+            "search",
+            &key,
+            |cached: &CachedSearch| cached.limit >= limit,
+            async {
+                Ok(CachedSearch {
+                    limit,
+                    subjects: self.inner.search(keyword, limit).await?,
+                })
+            },
+        )
+        .await?;
+        Ok(search.subjects.into_iter().take(limit).collect())
+    }
+
+    async fn detail(&self, id: i64) -> Result<MetadataSubjectDetail, String> {
+        cached(
+            self.namespace,
+            "subject",
+            &id.to_string(),
+            self.inner.detail(id),
+        )
+        .await
+    }
+
+    async fn episodes(&self, id: i64) -> Result<Vec<MetadataEpisode>, String> {
+        cached(
+            self.namespace,
+            "episodes",
+            &id.to_string(),
+            self.inner.episodes(id),
+        )
+        .await
+    }
+
+    // 两项缓存分别判断；都未命中时只向来源请求一次，结果拆开写入两项缓存
+    async fn detail_with_episodes(&self, id: i64) -> Result<MetadataSubjectDetail, String> {
+        let key = id.to_string();
+        let mut fetched_episodes: Option<Vec<MetadataEpisode>> = None;
+        let detail = cached(self.namespace, "subject", &key, async {
+            let detail = self.inner.detail_with_episodes(id).await?;
+            fetched_episodes = Some(detail.episode_list);
+            Ok(MetadataSubjectDetail {
+                episode_list: Vec::new(),
+                ..detail
+            })
+        })
+        .await?;
+        let episodes = cached(self.namespace, "episodes", &key, async {
+            match fetched_episodes {
+                Some(episodes) => Ok(episodes),
+                None => self.inner.episodes(id).await,
+            }
+        })
+        .await?;
+        Ok(MetadataSubjectDetail {
+            episode_list: episodes,
+            ..detail
+        })
+    }
+}
+
+fn cover_path(url: &str) -> Result<PathBuf, String> {
+    let ext = url
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.split('?').next())
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .filter(|ext| matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "webp" | "gif"))
+        .unwrap_or_else(|| "jpg".to_string());
+    Ok(metadata_cache_dir()?
+        .join(COVER_DIR)
+        .join(format!("{:016x}.{}", fnv1a(url), ext)))
+}
+
+fn mime_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("png") => "image/png",
+        Some("webp") => "image/webp",
+        Some("gif") => "image/gif",
+        _ => "image/jpeg",
+    }
+}
+
+// 封面地址对应的图片不会变化，下载后一直使用本地文件
+async fn cover_bytes(url: &str) -> Result<(Vec<u8>, &'static str), String> {
+    let path = cover_path(url)?;
+    let mime = mime_type(&path);
+    if let Ok(bytes) = fs::read(&path) {
+        return Ok((bytes, mime));
+    }
+    if current_settings().offline_mode {
+        return Err("离线模式下没有缓存的封面".to_string());
+    }
+    let response = send_with_retry(http_client().get(url), RequestKind::Api)
+        .await
+        .map_err(|e| format!("下载封面失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("下载封面失败，状态码: {}", response.status()));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("下载封面失败: {}", e))?
+        .to_vec();
+    if let Err(e) = write_file(&path, &bytes) {
+        log::warn!("{}", e);
+    }
+    Ok((bytes, mime))
+}
+
+// 返回封面的 data URL，优先使用本地缓存
+#[tauri::command]
+pub async fn get_cover_image(url: String) -> Result<String, String> {
+    let (bytes, mime) = cover_bytes(url.trim()).await?;
+    Ok(format!(
+        "data:{};base64,{}",
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+// 清空元数据与封面缓存，返回删除的文件数
+#[tauri::command]
+pub async fn clear_metadata_cache() -> Result<usize, String> {
+    fn remove_dir(dir: &Path) -> Result<usize, String> {
+        let mut count = 0;
+        let entries = fs::read_dir(dir).map_err(|e| format!("读取元数据缓存失败: {}", e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                count += remove_dir(&path)?;
+            } else {
+                fs::remove_file(&path).map_err(|e| format!("删除元数据缓存失败: {}", e))?;
+                count += 1;
+            }
+        }
+        fs::remove_dir(dir).map_err(|e| format!("删除元数据缓存失败: {}", e))?;
+        Ok(count)
+    }
+    let dir = metadata_cache_dir()?;
+    if !dir.exists() {
+        return Ok(0);
+    }
+    remove_dir(&dir)
+}
@@ -0,0 +1,180 @@
+use async_trait::async_trait;
+
+use crate::{
+    anidb::{self, AniDbProvider},
+    anilist::{self, AniListProvider},
+    bangumi::{self, BangumiProvider},
+    metadata_cache::CachedProvider,
+    settings::current_settings,
+    tmdb::{self, TmdbProvider},
+    types::{
+        EpisodeTitle, EpisodeTitleQuery, MetadataEpisode, MetadataProviderKind, MetadataSubject,
+        MetadataSubjectDetail, Settings,
+    },
+};
+
+// 章节类型：0 本篇，1 SP
+pub const EPISODE_TYPE_MAIN: i32 = 0;
+pub const EPISODE_TYPE_SPECIAL: i32 = 1;
+
+// 元数据来源，各实现把接口数据转换为统一的条目/章节结构
+#[async_trait]
+pub trait MetadataProvider: Send + Sync {
+    async fn search(&self, keyword: &str, limit: usize) -> Result<Vec<MetadataSubject>, String>;
+    async fn detail(&self, id: i64) -> Result<MetadataSubjectDetail, String>;
+    // 本篇与 SP 的章节列表，按类型和 sort 排序
+    async fn episodes(&self, id: i64) -> Result<Vec<MetadataEpisode>, String>;
+
+    // 详情与章节列表一起获取；同一个接口同时返回两者的来源应覆盖此方法，只请求一次
+    async fn detail_with_episodes(&self, id: i64) -> Result<MetadataSubjectDetail, String> {
+        let (detail, episodes) = futures::try_join!(self.detail(id), self.episodes(id))?;
+        Ok(MetadataSubjectDetail {
+            episode_list: episodes,
+            ..detail
+        })
+    }
+}
+
+// 各来源都经过磁盘缓存，离线模式下只读取缓存
+pub fn provider_for(kind: MetadataProviderKind, settings: &Settings) -> Box<dyn MetadataProvider> {
+    match kind {
+        MetadataProviderKind::Bangumi => Box::new(CachedProvider::new(
+            bangumi::CACHE_NAMESPACE,
+            BangumiProvider::new(&settings.bangumi_api_url),
+        )),
+        MetadataProviderKind::AniList => Box::new(CachedProvider::new(
+            anilist::CACHE_NAMESPACE,
+            AniListProvider::new(&settings.anilist_api_url),
+        )),
+        MetadataProviderKind::Tmdb => Box::new(CachedProvider::new(
+            tmdb::CACHE_NAMESPACE,
+            TmdbProvider::new(&settings.tmdb_api_url, &settings.tmdb_api_key),
+        )),
+        MetadataProviderKind::AniDb => Box::new(CachedProvider::new(
+            anidb::CACHE_NAMESPACE,
+            AniDbProvider::new(
+                &settings.anidb_api_url,
+                &settings.anidb_client,
+                settings.anidb_client_version,
+            ),
+        )),
+    }
+}
+
+// 未指定来源时使用设置中选择的来源
+pub(crate) fn resolve_provider(kind: Option<MetadataProviderKind>) -> Box<dyn MetadataProvider> {
+    let settings = current_settings();
+    provider_for(kind.unwrap_or(settings.metadata_provider), &settings)
+}
+
+pub(crate) fn non_empty(s: String) -> Option<String> {
+    let s = s.trim();
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+// 从 2023-09-29 形式的日期中取年份
+pub(crate) fn parse_year(date: Option<&str>) -> Option<i32> {
+    date.and_then(|s| s.get(0..4)).and_then(|y| y.parse().ok())
+}
+
+// 集数字符串转为数字，兼容 "01"、"6.5"、"SP02"、"第3话" 等写法
+fn episode_number(raw: &str) -> Option<f64> {
+    let digits: String = raw
+        .trim()
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    digits.parse().ok()
+}
+
+// 为识别出的集数匹配章节：本篇先按季内集数 ep 再按 sort 查找（兼容绝对集数），SP 只在类型 1 的章节中按 sort 查找
+pub fn match_episode_titles(
+    episodes: &[MetadataEpisode],
+    queries: &[EpisodeTitleQuery],
+) -> Vec<EpisodeTitle> {
+    queries
+        .iter()
+        .map(|query| {
+            let episode_type = if query.special {
+                EPISODE_TYPE_SPECIAL
+            } else {
+                EPISODE_TYPE_MAIN
+            };
+            let candidates = || {
+                episodes.iter().filter(|e| {
+                    e.episode_type == episode_type
+                        && (query.special
+                            || e.season.is_none()
+                            || query.season.is_none()
+                            || e.season == query.season)
+                })
+            };
+            let matched = episode_number(&query.episode).and_then(|n| {
+                let by_ep = (!query.special)
+                    .then(|| candidates().find(|e| e.ep == Some(n)))
+                    .flatten();
+                by_ep.or_else(|| candidates().find(|e| e.sort == n))
+            });
+            EpisodeTitle {
+                episode: query.episode.clone(),
+                special: query.special,
+                sort: matched.map(|e| e.sort),
+                name: matched.and_then(|e| e.name.clone()),
+                name_cn: matched.and_then(|e| e.name_cn.clone()),
+            }
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn search_metadata(
+    provider: Option<MetadataProviderKind>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<MetadataSubject>, String> {
+    let q = query.trim();
+    if q.is_empty() {
+        return Ok(Vec::new());
+    }
+    resolve_provider(provider)
+        .search(q, limit.unwrap_or(10))
+        .await
+}
+
+// 获取条目详情，include_episodes 为 true 时同时获取章节列表
+#[tauri::command]
+pub async fn get_metadata_detail(
+    provider: Option<MetadataProviderKind>,
+    id: i64,
+    include_episodes: Option<bool>,
+) -> Result<MetadataSubjectDetail, String> {
+    let provider = resolve_provider(provider);
+    if include_episodes.unwrap_or(false) {
+        provider.detail_with_episodes(id).await
+    } else {
+        provider.detail(id).await
+    }
+}
+
+#[tauri::command]
+pub async fn get_metadata_episodes(
+    provider: Option<MetadataProviderKind>,
+    id: i64,
+) -> Result<Vec<MetadataEpisode>, String> {
+    resolve_provider(provider).episodes(id).await
+}
+
+// 查询条目中指定集数的中文/日文标题，未找到的集数标题为空
+#[tauri::command]
+pub async fn lookup_episode_titles(
+    provider: Option<MetadataProviderKind>,
+    subject_id: i64,
+    episodes: Vec<EpisodeTitleQuery>,
+) -> Result<Vec<EpisodeTitle>, String> {
+    if episodes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let list = resolve_provider(provider).episodes(subject_id).await?;
+    Ok(match_episode_titles(&list, &episodes))
+}
@@ -0,0 +1,339 @@
+use crate::{
+    settings::current_settings,
+    types::{MediaServerPreset, NamingInfo},
+    utils::is_valid_filename,
+};
+
+// 与之前前端固定格式一致：title.year.S01E01.group.codec.ext，有分集标题时追加 " - 第01话 标题"
+pub const DEFAULT_NAMING_TEMPLATE: &str =
+    "{title}{?.{year}}.S{season:02}E{episode:02}{? - {ep_label} {ep_title}}{?.{group}}{?.{codec}}.{ext}";
+
+// 各媒体服务器要求的目录结构，第 0 季（SP/OVA）放入 Specials
+pub fn preset_template(preset: MediaServerPreset, special: bool) -> &'static str {
+    use MediaServerPreset::*;
+    match (preset, special) {
+        (Jellyfin | Emby, false) => {
+            "{title}{? ({year})}/Season {season:02}/{title} - S{season:02}E{episode:02}{? - {ep_title}}.{ext}"
+        }
+        (Jellyfin | Emby, true) => {
+            "{title}{? ({year})}/Specials/{title} - S00E{episode:02}{? - {ep_title}}.{ext}"
+        }
+        (Plex, false) => {
+            "{title}{? ({year})}/Season {season:02}/{title}{? ({year})} - s{season:02}e{episode:02}{? - {ep_title}}.{ext}"
+        }
+        (Plex, true) => {
+            "{title}{? ({year})}/Specials/{title}{? ({year})} - s00e{episode:02}{? - {ep_title}}.{ext}"
+        }
+        (Kodi, false) => {
+            "{title}{? ({year})}/Season {season}/{title} S{season:02}E{episode:02}{? - {ep_title}}.{ext}"
+        }
+        (Kodi, true) => "{title}{? ({year})}/Specials/{title} S00E{episode:02}{? - {ep_title}}.{ext}",
+    }
+}
+
+const VARIABLES: [&str; 13] = [
+    "title",
+    "year",
+    "season",
+    "episode",
+    "ep_label",
+    "ep_title",
+    "group",
+    "codec",
+    "resolution",
+    "source",
+    "crc32",
+    "version",
+    "ext",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Filter {
+    Upper,
+    Lower,
+    Title,
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Var {
+        name: String,
+        width: Option<usize>,
+        filters: Vec<Filter>,
+    },
+    // {? ...} 内任一变量为空时整段省略
+    Cond(Vec<Node>),
+}
+
+struct TemplateParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl TemplateParser<'_> {
+    // 解析到字符串结尾（顶层）或遇到 }（条件段内）
+    fn parse_nodes(&mut self, in_cond: bool) -> Result<Vec<Node>, String> {
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+        loop {
+            let Some(c) = self.chars.next() else {
+                if in_cond {
+                    return Err("条件段缺少结尾的 }".to_string());
+                }
+                break;
+            };
+            match c {
+                '{' if self.chars.peek() == Some(&'{') => {
+                    self.chars.next();
+                    text.push('{');
+                }
+                '{' => {
+                    if !text.is_empty() {
+                        nodes.push(Node::Text(std::mem::take(&mut text)));
+                    }
+                    if self.chars.peek() == Some(&'?') {
+                        self.chars.next();
+                        nodes.push(Node::Cond(self.parse_nodes(true)?));
+                    } else {
+                        nodes.push(self.parse_var()?);
+                    }
+                }
+                '}' if in_cond => {
+                    if !text.is_empty() {
+                        nodes.push(Node::Text(text));
+                    }
+                    return Ok(nodes);
+                }
+                '}' if self.chars.peek() == Some(&'}') => {
+                    self.chars.next();
+                    text.push('}');
+                }
+                '}' => return Err("模板中存在多余的 }，字面量请写作 }}".to_string()),
+                _ => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+        Ok(nodes)
+    }
+
+    fn parse_var(&mut self) -> Result<Node, String> {
+        let mut spec = String::new();
+        loop {
+            match self.chars.next() {
+                Some('}') => break,
+                Some('{') => return Err(format!("变量 {{{}}} 中不能嵌套 {{", spec)),
+                Some(c) => spec.push(c),
+                None => return Err(format!("变量 {{{} 缺少结尾的 }}", spec)),
+            }
+        }
+
+        let mut parts = spec.split('|');
+        let head = parts.next().unwrap_or("").trim();
+        let (name, format) = match head.split_once(':') {
+            Some((name, format)) => (name.trim(), Some(format.trim())),
+            None => (head, None),
+        };
+        if !VARIABLES.contains(&name) {
+            return Err(format!("未知的模板变量: {}", name));
+        }
+        let width = match format {
+            Some(f) if f.starts_with('0') && f.len() > 1 => Some(
+                f[1..]
+                    .parse::<usize>()
+                    .map_err(|_| format!("无效的补零格式: {}", f))?,
+            ),
+            Some(f) => return Err(format!("无效的补零格式: {}", f)),
+            None => None,
+        };
+        let filters = parts
+            .map(|f| match f.trim() {
+                "upper" => Ok(Filter::Upper),
+                "lower" => Ok(Filter::Lower),
+                "title" => Ok(Filter::Title),
+                other => Err(format!("未知的模板过滤器: {}", other)),
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Node::Var {
+            name: name.to_string(),
+            width,
+            filters,
+        })
+    }
+}
+
+fn parse_template(template: &str) -> Result<Vec<Node>, String> {
+    TemplateParser {
+        chars: template.chars().peekable(),
+    }
+    .parse_nodes(false)
+}
+
+// 中文分集标记：本篇为 第01话，第 0 季为 SP01
+fn episode_label(info: &NamingInfo) -> Option<String> {
+    let episode = info
+        .episode
+        .as_deref()
+        .map(str::trim)
+        .filter(|e| !e.is_empty())?;
+    let episode = pad_number(episode, 2);
+    if info.season == Some(0) {
+        Some(format!("SP{}", episode))
+    } else {
+        Some(format!("第{}话", episode))
+    }
+}
+
+fn lookup(info: &NamingInfo, name: &str) -> Option<String> {
+    let value = match name {
+        "title" => Some(info.title.clone()),
+        "year" => info.year.map(|v| v.to_string()),
+        "season" => info.season.map(|v| v.to_string()),
+        "episode" => info.episode.clone(),
+        "ep_label" => episode_label(info),
+        "ep_title" => info.ep_title.clone(),
+        "group" => info.group.clone(),
+        "codec" => info.codec.clone(),
+        "resolution" => info.resolution.clone(),
+        "source" => info.source.clone(),
+        "crc32" => info.crc32.clone(),
+        "version" => info.version.map(|v| v.to_string()),
+        "ext" => info
+            .ext
+            .as_deref()
+            .map(|e| e.trim_start_matches('.').to_string()),
+        _ => None,
+    }?;
+    let value = value.trim().to_string();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+// 数字补零；带小数的集数（如 6.5）只补整数部分
+fn pad_number(value: &str, width: usize) -> String {
+    let (int, frac) = match value.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (value, None),
+    };
+    match int.parse::<u64>() {
+        Ok(n) => match frac {
+            Some(frac) => format!("{:0width$}.{}", n, frac, width = width),
+            None => format!("{:0width$}", n, width = width),
+        },
+        Err(_) => value.to_string(),
+    }
+}
+
+fn title_case(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut word_start = true;
+    for c in value.chars() {
+        if word_start {
+            out.extend(c.to_uppercase());
+        } else {
+            out.extend(c.to_lowercase());
+        }
+        word_start = c.is_whitespace() || matches!(c, '-' | '_' | '.');
+    }
+    out
+}
+
+// 变量值中的非法字符替换为全角字符，路径分隔符也不例外，避免标题被拆成目录
+fn sanitize_value(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !c.is_control())
+        .map(|c| match c {
+            '/' => '／',
+            '\\' => '＼',
+            ':' => '：',
+            '*' => '＊',
+            '?' => '？',
+            '"' => '＂',
+            '<' => '＜',
+            '>' => '＞',
+            '|' => '｜',
+            c => c,
+        })
+        .collect()
+}
+
+fn render_var(
+    info: &NamingInfo,
+    name: &str,
+    width: Option<usize>,
+    filters: &[Filter],
+) -> Option<String> {
+    let mut value = lookup(info, name)?;
+    if let Some(width) = width {
+        value = pad_number(&value, width);
+    }
+    for filter in filters {
+        value = match filter {
+            Filter::Upper => value.to_uppercase(),
+            Filter::Lower => value.to_lowercase(),
+            Filter::Title => title_case(&value),
+        };
+    }
+    Some(sanitize_value(&value))
+}
+
+// 有变量为空时返回该变量名；条件段内的变量为空时只省略该段
+fn render_nodes<'a>(nodes: &'a [Node], info: &NamingInfo) -> Result<String, &'a str> {
+    let mut out = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var {
+                name,
+                width,
+                filters,
+            } => out.push_str(&render_var(info, name, *width, filters).ok_or(name.as_str())?),
+            Node::Cond(children) => {
+                if let Ok(value) = render_nodes(children, info) {
+                    out.push_str(&value);
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+// 按模板生成相对路径，模板中的 / 表示目录层级
+pub fn render_template(template: &str, info: &NamingInfo) -> Result<String, String> {
+    let nodes = parse_template(template)?;
+    let rendered = render_nodes(&nodes, info).map_err(|name| format!("缺少变量 {}", name))?;
+
+    let mut segments = Vec::new();
+    for segment in rendered.split(['/', '\\']) {
+        // 去掉首尾空白和结尾的点，并合并连续空格
+        let segment = segment.split_whitespace().collect::<Vec<_>>().join(" ");
+        let segment = segment.trim_end_matches('.').trim_end();
+        if segment.is_empty() {
+            continue;
+        }
+        if !is_valid_filename(segment) {
+            return Err(format!("模板生成的名称无效: {}", segment));
+        }
+        segments.push(segment.to_string());
+    }
+    if segments.is_empty() {
+        return Err("模板生成的名称为空".to_string());
+    }
+    Ok(segments.join("/"))
+}
+
+// 按命名模板生成文件名，未指定模板时使用设置中的默认模板
+#[tauri::command]
+pub async fn render_name(template: Option<String>, info: NamingInfo) -> Result<String, String> {
+    let template = match template {
+        Some(t) if !t.trim().is_empty() => t,
+        _ => current_settings().naming_template,
+    };
+    render_template(&template, &info)
+}
@@ -0,0 +1,592 @@
+// 基于规则的动画文件名解析，思路参考 anitomy：
+// 先按括号切分为片段，识别括号内的元数据，再从剩余文本中提取标题与集数
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::{
+    types::{AnimeInfo, ParsedFilename},
+    utils::{is_subtitle_file, is_video_file},
+};
+
+struct Segment {
+    text: String,
+    bracketed: bool,
+}
+
+// 解析过程中累积的结果
+#[derive(Default)]
+struct Parsed {
+    info: AnimeInfo,
+    // 集数是否来自明确的标记（[01]、- 01、S01E01 等）
+    explicit_episode: bool,
+    has_metadata: bool,
+}
+
+fn re(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).unwrap())
+}
+
+fn resolution_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    re(
+        &CELL,
+        r"(?i)^(?:(\d{3,4})[pi]|\d{3,4}[x×](\d{3,4})|([24])k)$",
+    )
+}
+
+fn episode_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    re(&CELL, r"(?i)^(\d{1,4})(?:v(\d))?(?:\s?END|完)?$")
+}
+
+fn special_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    re(&CELL, r"(?i)^(SP|OVA|OAD|Special)\s?(\d{1,3})?(?:v(\d))?$")
+}
+
+fn season_episode_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    re(&CELL, r"(?i)^S(\d{1,2})E(\d{1,4})(?:v(\d))?$")
+}
+
+fn episode_marker_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    re(&CELL, r"(?i)^(?:EP?\.?|#)(\d{1,4})(?:v(\d))?$")
+}
+
+fn cn_episode_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    re(&CELL, r"第\s*(\d{1,4})\s*[话話集]")
+}
+
+fn cn_season_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    re(&CELL, r"第\s*([0-9一二三四五六七八九十]+)\s*季")
+}
+
+fn season_word_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    re(
+        &CELL,
+        r"(?i)(?:\bSeason\s*(\d{1,2})\b|\b(\d{1,2})(?:st|nd|rd|th)\s+Season\b|\bS(\d{1,2})\b)",
+    )
+}
+
+fn range_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    re(&CELL, r"^\d{1,4}\s?[-~]\s?\d{1,4}")
+}
+
+fn crc_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    re(&CELL, r"^[0-9A-Fa-f]{8}$")
+}
+
+fn year_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    re(&CELL, r"^(19[5-9]\d|20\d{2})$")
+}
+
+pub(crate) fn canonical_codec(token: &str) -> Option<&'static str> {
+    match token.to_lowercase().as_str() {
+        "x264" | "h264" | "h.264" | "avc" | "avc1" => Some("AVC"),
+        "x265" | "h265" | "h.265" | "hevc" => Some("HEVC"),
+        "av1" => Some("AV1"),
+        "vp9" => Some("VP9"),
+        "xvid" | "divx" => Some("XviD"),
+        "mpeg2" | "mpeg-2" => Some("MPEG-2"),
+        _ => None,
+    }
+}
+
+fn canonical_source(token: &str) -> Option<&'static str> {
+    match token.to_lowercase().as_str() {
+        "bd" | "bluray" | "blu-ray" | "bdmv" | "bdremux" => Some("BD"),
+        "bdrip" => Some("BDRip"),
+        "web" | "web-dl" | "webdl" => Some("WEB-DL"),
+        "webrip" => Some("WEBRip"),
+        "tv" | "tvrip" | "hdtv" => Some("TV"),
+        "dvd" | "dvdrip" => Some("DVD"),
+        _ => None,
+    }
+}
+
+// 不需要输出、但能说明该片段是元数据的关键词
+fn is_ignored_keyword(token: &str) -> bool {
+    let lower = token.to_lowercase();
+    let keywords = [
+        "flac",
+        "aac",
+        "ac3",
+        "eac3",
+        "dts",
+        "opus",
+        "mp3",
+        "8bit",
+        "10bit",
+        "8-bit",
+        "10-bit",
+        "hi10p",
+        "ma10p",
+        "hi444pp",
+        "ma444-10p",
+        "yuv420p10",
+        "mkv",
+        "mp4",
+        "avi",
+        "assx2",
+        "ass",
+        "srt",
+        "chs",
+        "cht",
+        "gb",
+        "big5",
+        "jp",
+        "jpn",
+        "eng",
+        "chs&jpn",
+        "cht&jpn",
+        "baha",
+        "bilibili",
+        "cr",
+        "abema",
+        "amzn",
+        "nf",
+        "dsnp",
+        "b-global",
+        "multi-subs",
+        "multiple",
+        "subtitle",
+        "dual",
+        "audio",
+        "remux",
+        "uncensored",
+        "简体",
+        "繁体",
+        "繁體",
+        "简繁",
+        "简日",
+        "繁日",
+        "简体内嵌",
+        "繁体内嵌",
+        "繁體內嵌",
+        "简繁内封",
+        "简日内嵌",
+        "繁日内嵌",
+        "内嵌",
+        "内封",
+        "外挂",
+        "字幕",
+        "中文字幕",
+    ];
+    keywords.contains(&lower.as_str())
+}
+
+fn normalize_resolution(token: &str) -> Option<String> {
+    let caps = resolution_re().captures(token)?;
+    if let Some(p) = caps.get(1).or_else(|| caps.get(2)) {
+        return Some(format!("{}p", p.as_str()));
+    }
+    caps.get(3).map(|k| format!("{}K", k.as_str()))
+}
+
+// 识别单个元数据关键词，返回是否识别成功
+fn apply_keyword(token: &str, parsed: &mut Parsed) -> bool {
+    let token = token.trim_matches(|c: char| c == '.' || c == '-' || c == '_');
+    if token.is_empty() {
+        return false;
+    }
+    if let Some(resolution) = normalize_resolution(token) {
+        parsed.info.resolution.get_or_insert(resolution);
+        return true;
+    }
+    if let Some(codec) = canonical_codec(token) {
+        parsed.info.codec.get_or_insert(codec.to_string());
+        return true;
+    }
+    if let Some(source) = canonical_source(token) {
+        parsed.info.source.get_or_insert(source.to_string());
+        return true;
+    }
+    if is_ignored_keyword(token) {
+        return true;
+    }
+    // 组合写法，如 HEVC-10bit、AVC-8bit
+    if token.contains('-') {
+        let parts: Vec<&str> = token.split('-').collect();
+        if parts.len() > 1 && parts.iter().all(|p| is_metadata_token(p)) {
+            for p in parts {
+                apply_keyword(p, parsed);
+            }
+            return true;
+        }
+    }
+    false
+}
+
+fn is_metadata_token(token: &str) -> bool {
+    let mut scratch = Parsed::default();
+    apply_keyword(token, &mut scratch)
+}
+
+fn set_episode(parsed: &mut Parsed, episode: &str, version: Option<&str>, explicit: bool) {
+    if parsed.info.episode.is_some() {
+        return;
+    }
+    let episode = match episode.parse::<u32>() {
+        Ok(n) => format!("{:02}", n),
+        Err(_) => episode.to_string(),
+    };
+    parsed.info.episode = Some(episode);
+    parsed.info.version = version.and_then(|v| v.parse().ok());
+    parsed.explicit_episode = explicit;
+}
+
+fn chinese_number(text: &str) -> Option<u32> {
+    if let Ok(n) = text.parse::<u32>() {
+        return Some(n);
+    }
+    let digit = |c: char| "零一二三四五六七八九".chars().position(|d| d == c);
+    let chars: Vec<char> = text.chars().collect();
+    match chars.as_slice() {
+        ['十'] => Some(10),
+        ['十', d] => digit(*d).map(|d| 10 + d as u32),
+        [d, '十'] => digit(*d).map(|d| d as u32 * 10),
+        [a, '十', b] => Some(digit(*a)? as u32 * 10 + digit(*b)? as u32),
+        [d] => digit(*d).map(|d| d as u32),
+        _ => None,
+    }
+}
+
+fn split_segments(stem: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut closing: Option<char> = None;
+    for c in stem.chars() {
+        match closing {
+            None => {
+                let close = match c {
+                    '[' => Some(']'),
+                    '(' => Some(')'),
+                    '【' => Some('】'),
+                    '（' => Some('）'),
+                    _ => None,
+                };
+                if let Some(close) = close {
+                    if !current.is_empty() {
+                        segments.push(Segment {
+                            text: std::mem::take(&mut current),
+                            bracketed: false,
+                        });
+                    }
+                    closing = Some(close);
+                } else {
+                    current.push(c);
+                }
+            }
+            Some(close) if c == close => {
+                segments.push(Segment {
+                    text: std::mem::take(&mut current),
+                    bracketed: true,
+                });
+                closing = None;
+            }
+            Some(_) => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        // 未闭合的括号按普通文本处理
+        segments.push(Segment {
+            text: current,
+            bracketed: false,
+        });
+    }
+    segments
+}
+
+// 处理括号片段，返回 true 表示是元数据片段
+fn parse_bracket(text: &str, parsed: &mut Parsed) -> bool {
+    let text = text.trim();
+    if let Some(caps) = episode_re().captures(text) {
+        let ep = caps.get(1).unwrap().as_str();
+        if year_re().is_match(ep) {
+            parsed.info.year.get_or_insert(ep.parse().unwrap_or(0));
+        } else {
+            set_episode(parsed, ep, caps.get(2).map(|m| m.as_str()), true);
+        }
+        return true;
+    }
+    if let Some(caps) = special_re().captures(text) {
+        parsed.info.season.get_or_insert(0);
+        set_episode(
+            parsed,
+            caps.get(2).map_or("01", |m| m.as_str()),
+            caps.get(3).map(|m| m.as_str()),
+            true,
+        );
+        return true;
+    }
+    if let Some(caps) = season_episode_re().captures(text) {
+        parsed.info.season = caps.get(1).and_then(|m| m.as_str().parse().ok());
+        set_episode(
+            parsed,
+            caps.get(2).unwrap().as_str(),
+            caps.get(3).map(|m| m.as_str()),
+            true,
+        );
+        return true;
+    }
+    if crc_re().is_match(text) && text.chars().any(|c| c.is_ascii_digit()) {
+        parsed.info.crc32 = Some(text.to_uppercase());
+        return true;
+    }
+    // 合集范围，如 [01-12]
+    if range_re().is_match(text) {
+        return true;
+    }
+
+    let mut matched = 0;
+    let mut total = 0;
+    for token in text.split(|c: char| c.is_whitespace() || c == '_' || c == '+' || c == ',') {
+        if token.is_empty() {
+            continue;
+        }
+        total += 1;
+        if apply_keyword(token, parsed) {
+            matched += 1;
+        }
+    }
+    matched > 0 && matched * 2 >= total
+}
+
+// 处理标题区域的普通文本，提取标题、季数与集数
+fn parse_plain(text: &str, parsed: &mut Parsed) -> String {
+    let mut text = text.trim().to_string();
+    // 点号/下划线分隔的写法（Show.Name.S01E02.1080p）
+    if !text.contains(' ') && (text.contains('.') || text.contains('_')) {
+        text = text.replace(['.', '_'], " ");
+    }
+
+    if let Some(caps) = cn_episode_re().captures(&text) {
+        let m = caps.get(0).unwrap();
+        let ep = caps.get(1).unwrap().as_str().to_string();
+        set_episode(parsed, &ep, None, true);
+        text = format!("{} {}", &text[..m.start()], &text[m.end()..]);
+    }
+    if let Some(caps) = cn_season_re().captures(&text) {
+        let m = caps.get(0).unwrap();
+        parsed.info.season = chinese_number(caps.get(1).unwrap().as_str());
+        text = format!("{} {}", &text[..m.start()], &text[m.end()..]);
+    }
+
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let mut title_end = tokens.len();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        let next = tokens.get(i + 1).copied();
+        let mut consumed = false;
+
+        if let Some(caps) = season_episode_re().captures(token) {
+            parsed.info.season = caps.get(1).and_then(|m| m.as_str().parse().ok());
+            set_episode(
+                parsed,
+                caps.get(2).unwrap().as_str(),
+                caps.get(3).map(|m| m.as_str()),
+                true,
+            );
+            consumed = true;
+        } else if token == "-" {
+            if let Some(caps) = next.and_then(|n| episode_re().captures(n)) {
+                let ep = caps.get(1).unwrap().as_str();
+                if !year_re().is_match(ep) {
+                    set_episode(parsed, ep, caps.get(2).map(|m| m.as_str()), true);
+                    title_end = title_end.min(i);
+                    i += 2;
+                    continue;
+                }
+            }
+        } else if let Some(caps) = episode_marker_re().captures(token) {
+            if i > 0 {
+                set_episode(
+                    parsed,
+                    caps.get(1).unwrap().as_str(),
+                    caps.get(2).map(|m| m.as_str()),
+                    true,
+                );
+                consumed = true;
+            }
+        } else if let Some(caps) = special_re().captures(token) {
+            if i > 0 && caps.get(2).is_some() {
+                parsed.info.season.get_or_insert(0);
+                set_episode(
+                    parsed,
+                    caps.get(2).unwrap().as_str(),
+                    caps.get(3).map(|m| m.as_str()),
+                    true,
+                );
+                consumed = true;
+            }
+        } else if i > 0 && apply_keyword(token, parsed) {
+            parsed.has_metadata = true;
+            consumed = true;
+        } else if i > 0 && year_re().is_match(token) && title_end == tokens.len() {
+            // 标题后的年份，如 Show 2019 S01E01
+            if next.is_some_and(|n| season_episode_re().is_match(n) || is_metadata_token(n)) {
+                parsed.info.year = token.parse().ok();
+                consumed = true;
+            }
+        } else if i > 0 && token.contains('-') && parsed.has_metadata {
+            // 发布组写在编码之后，如 x264-GROUP
+            if let Some((left, right)) = token.rsplit_once('-') {
+                if apply_keyword(left, parsed) && !right.is_empty() {
+                    parsed.info.group.get_or_insert(right.to_string());
+                    consumed = true;
+                }
+            }
+        }
+
+        if consumed {
+            title_end = title_end.min(i);
+        }
+        i += 1;
+    }
+
+    let mut title_tokens: Vec<&str> = tokens[..title_end].to_vec();
+    // 标题末尾的裸数字视为集数，如 "Show 01 [1080p]"
+    if parsed.info.episode.is_none() && title_tokens.len() > 1 {
+        if let Some(caps) = title_tokens.last().and_then(|t| episode_re().captures(t)) {
+            let ep = caps.get(1).unwrap().as_str().to_string();
+            if !year_re().is_match(&ep) {
+                let version = caps.get(2).map(|m| m.as_str().to_string());
+                set_episode(parsed, &ep, version.as_deref(), false);
+                title_tokens.pop();
+            }
+        }
+    }
+
+    let mut title = title_tokens.join(" ");
+    if let Some(caps) = season_word_re().captures(&title) {
+        let m = caps.get(0).unwrap();
+        let season = caps
+            .get(1)
+            .or_else(|| caps.get(2))
+            .or_else(|| caps.get(3))
+            .and_then(|s| s.as_str().parse().ok());
+        if season.is_some() {
+            parsed.info.season = season;
+            title = format!("{} {}", &title[..m.start()], &title[m.end()..]);
+        }
+    }
+    title
+}
+
+fn clean_title(title: &str) -> String {
+    // 中日/英文双标题时取第一个
+    let first = title
+        .split(" / ")
+        .next()
+        .unwrap_or(title)
+        .split(" | ")
+        .next()
+        .unwrap_or(title);
+    first
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c == '-' || c == '_' || c == '.' || c == ':' || c.is_whitespace())
+        .to_string()
+}
+
+// 解析文件名，返回识别结果及置信度（0-1）
+pub fn parse_filename(filename: &str) -> ParsedFilename {
+    let stem = if is_video_file(filename) || is_subtitle_file(filename) {
+        filename
+            .rsplit_once('.')
+            .map(|(stem, _)| stem)
+            .unwrap_or(filename)
+    } else {
+        filename
+    };
+
+    let segments = split_segments(stem);
+    let mut parsed = Parsed::default();
+    let mut title_candidate: Option<String> = None;
+    let mut bracket_title: Option<String> = None;
+
+    for (index, segment) in segments.iter().enumerate() {
+        if segment.bracketed {
+            let is_metadata = parse_bracket(&segment.text, &mut parsed);
+            if is_metadata {
+                parsed.has_metadata = true;
+                continue;
+            }
+            let text = segment.text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            // 开头的括号通常是字幕组/压制组
+            if index == 0 && parsed.info.group.is_none() {
+                parsed.info.group = Some(text.to_string());
+            } else if bracket_title.is_none() {
+                // [Group][Title][01] 这种全括号写法
+                bracket_title = Some(text.to_string());
+            }
+        } else if title_candidate.is_none() {
+            let text = segment.text.trim();
+            if text.is_empty() || text.chars().all(|c| !c.is_alphanumeric()) {
+                continue;
+            }
+            title_candidate = Some(parse_plain(text, &mut parsed));
+        } else {
+            // 标题之后的普通文本只识别元数据
+            for token in segment.text.split_whitespace() {
+                if apply_keyword(token, &mut parsed) {
+                    parsed.has_metadata = true;
+                }
+            }
+        }
+    }
+
+    let title = title_candidate
+        .filter(|t| !clean_title(t).is_empty())
+        .or(bracket_title)
+        .map(|t| clean_title(&t))
+        .unwrap_or_default();
+    parsed.info.title = title;
+
+    let mut confidence: f32 = 0.0;
+    if !parsed.info.title.is_empty() {
+        confidence += 0.4;
+        // 标题中仍然带有元数据关键词，说明切分可能有误
+        let suspicious = parsed
+            .info
+            .title
+            .split_whitespace()
+            .any(|t| is_metadata_token(t) || crc_re().is_match(t));
+        if !suspicious {
+            confidence += 0.1;
+        }
+    }
+    if parsed.info.episode.is_some() {
+        confidence += if parsed.explicit_episode { 0.3 } else { 0.2 };
+    }
+    if parsed.info.group.is_some() {
+        confidence += 0.1;
+    }
+    if parsed.has_metadata {
+        confidence += 0.1;
+    }
+
+    ParsedFilename {
+        info: parsed.info,
+        confidence: confidence.min(1.0),
+    }
+}
+
+// 不调用 LLM，直接用规则解析文件名
+#[tauri::command]
+pub async fn parse_anime_filename(filename: String) -> Result<ParsedFilename, String> {
+    Ok(parse_filename(&filename))
+}
@@ -1,129 +1,572 @@
-use std::{fs, path::Path};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 use crate::{
-    types::{FileInfo, RenameRequest, RenameResponse},
-    utils::{get_extension, is_subtitle_file, is_video_file},
+    naming::{preset_template, render_template},
+    rename_journal::record_batch,
+    scanner::{resolve_options, scan_paths},
+    settings::current_settings,
+    subtitle::convert_subtitle_file,
+    subtitle_encoding::convert_files,
+    types::{
+        FileInfo, NamingInfo, RenameOperation, RenamePlan, RenamePlanItem, RenameProblem,
+        RenameRequest, RenameResponse, ScanOptions, SubtitleConversion, SubtitleFormat,
+        SubtitleTrack, VideoRenameRequest,
+    },
+    utils::{
+        detect_language_tag, get_extension, has_full_path, is_valid_filename, rename_journal_path,
+    },
 };
 
+// 拖入的文件夹与选择文件夹使用同样的扫描选项
 #[tauri::command]
-pub async fn get_dropped_files(paths: Vec<String>) -> Result<Vec<FileInfo>, String> {
-    let mut file_infos = Vec::new();
+pub async fn get_dropped_files(
+    paths: Vec<String>,
+    options: Option<ScanOptions>,
+) -> Result<Vec<FileInfo>, String> {
+    let paths: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+    scan_paths(&paths, &resolve_options(options))
+}
+
+// 根据视频文件名生成字幕的新文件名
+fn subtitle_target_name(video_file: &FileInfo, subtitle_file: &FileInfo, suffix: &str) -> String {
+    // 提取视频文件名（去掉扩展名）
+    let video_filename = Path::new(&video_file.name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(&video_file.name)
+        .to_string();
+
+    // 提取字幕扩展名
+    let subtitle_ext = get_extension(&subtitle_file.name);
+
+    if suffix.is_empty() {
+        format!("{}.{}", video_filename, subtitle_ext)
+    } else {
+        format!("{}.{}.{}", video_filename, suffix, subtitle_ext)
+    }
+}
 
-    for path_str in paths {
-        let path = Path::new(&path_str);
-        if !path.exists() {
-            continue;
+// 两个路径是否指向同一个文件（大小写不敏感的文件系统上仅改大小写时成立）
+pub(crate) fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+// 按当前磁盘状态检查计划中每一项的问题
+pub fn validate_plan(items: &mut [RenamePlanItem], create_dirs: bool) {
+    let mut target_counts: HashMap<String, usize> = HashMap::new();
+    for item in items.iter() {
+        *target_counts
+            .entry(item.target_path.to_lowercase())
+            .or_insert(0) += 1;
+    }
+
+    for item in items.iter_mut() {
+        let mut problems = Vec::new();
+        if !is_valid_filename(&item.target_name) {
+            problems.push(RenameProblem::InvalidFilename);
         }
-        if path.is_file() {
-            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                let is_video = is_video_file(filename);
-                let is_sub = is_subtitle_file(filename);
-                if is_video || is_sub {
-                    file_infos.push(FileInfo {
-                        name: filename.to_string(),
-                        path: path_str.clone(),
-                        is_video,
-                    });
-                }
+        if has_full_path(&item.source_path) {
+            let source = Path::new(&item.source_path);
+            let target = Path::new(&item.target_path);
+            if !source.exists() {
+                problems.push(RenameProblem::MissingSource);
+            } else if target.exists() && !is_same_file(source, target) {
+                problems.push(RenameProblem::TargetExists);
+            }
+            if !create_dirs && target.parent().is_some_and(|dir| !dir.exists()) {
+                problems.push(RenameProblem::MissingTargetDir);
             }
         }
+        if target_counts
+            .get(&item.target_path.to_lowercase())
+            .is_some_and(|count| *count > 1)
+        {
+            problems.push(RenameProblem::DuplicateTarget);
+        }
+        item.problems = problems;
     }
-
-    Ok(file_infos)
 }
 
-// 重命名字幕文件
-#[tauri::command]
-pub async fn rename_subtitle_files(request: RenameRequest) -> Result<RenameResponse, String> {
-    let mut renamed_files = Vec::new();
+fn problem_message(item: &RenamePlanItem) -> String {
+    match item.problems.first() {
+        Some(RenameProblem::MissingSource) => format!("源文件不存在: {}", item.source_name),
+        Some(RenameProblem::TargetExists) => format!("目标文件 {} 已存在", item.target_name),
+        Some(RenameProblem::DuplicateTarget) => format!("目标文件名重复: {}", item.target_name),
+        Some(RenameProblem::InvalidFilename) => format!("目标文件名不合法: {}", item.target_name),
+        Some(RenameProblem::MissingTargetDir) => {
+            format!("目标目录不存在: {}", item.target_path)
+        }
+        None => String::new(),
+    }
+}
 
-    // 检查视频文件与字幕文件数量是否匹配
-    if request.video_files.len() != request.subtitle_files.len() {
-        return Ok(RenameResponse {
-            success: false,
-            message: format!(
+// 未指定轨道时按下标一一对应
+pub(crate) fn resolve_tracks(request: &RenameRequest) -> Result<Vec<SubtitleTrack>, String> {
+    Ok(if request.tracks.is_empty() {
+        // 检查视频文件与字幕文件数量是否匹配
+        if request.video_files.len() != request.subtitle_files.len() {
+            return Err(format!(
                 "视频文件数量({})与字幕文件数量({})不匹配",
                 request.video_files.len(),
                 request.subtitle_files.len()
-            ),
-            renamed_files,
+            ));
+        }
+        request
+            .subtitle_files
+            .iter()
+            .enumerate()
+            .map(|(video_index, subtitle)| SubtitleTrack {
+                video_index,
+                subtitle: subtitle.clone(),
+                suffix: None,
+            })
+            .collect()
+    } else {
+        request.tracks.clone()
+    })
+}
+
+// 根据请求生成重命名计划，不修改磁盘
+pub fn build_plan(request: &RenameRequest) -> Result<RenamePlan, String> {
+    let tracks = resolve_tracks(request)?;
+
+    let mut items = Vec::with_capacity(tracks.len());
+    for track in &tracks {
+        let video_file = request
+            .video_files
+            .get(track.video_index)
+            .ok_or(format!("字幕 {} 对应的视频不存在", track.subtitle.name))?;
+        let subtitle_file = &track.subtitle;
+
+        // 后缀优先级：轨道指定 > 文件名中已有的语言标记 > 请求的统一后缀
+        let suffix = track
+            .suffix
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .or_else(|| {
+                if request.keep_language_tag {
+                    detect_language_tag(&subtitle_file.name)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_else(|| request.suffix.trim().to_string());
+
+        let target_name = subtitle_target_name(video_file, subtitle_file, &suffix);
+        // 仅提供文件名时，目标路径即为新文件名
+        let target_path = match Path::new(&subtitle_file.path).parent() {
+            Some(parent) if has_full_path(&subtitle_file.path) => {
+                parent.join(&target_name).to_string_lossy().to_string()
+            }
+            _ => target_name.clone(),
+        };
+        items.push(RenamePlanItem {
+            source_name: subtitle_file.name.clone(),
+            source_path: subtitle_file.path.clone(),
+            target_name,
+            target_path,
+            problems: Vec::new(),
         });
     }
 
-    // 检查字幕文件是否存在（仅在给出完整路径时检查）
-    for subtitle_file in &request.subtitle_files {
-        let path = Path::new(&subtitle_file.path);
-        if subtitle_file.path.contains('/') || subtitle_file.path.contains('\\') {
-            if !path.exists() {
-                return Ok(RenameResponse {
-                    success: false,
-                    message: format!("字幕文件不存在: {}", subtitle_file.name),
-                    renamed_files,
-                });
-            }
+    validate_plan(&mut items, false);
+    let valid = items.iter().all(|item| item.problems.is_empty());
+    Ok(RenamePlan {
+        items,
+        valid,
+        create_dirs: false,
+        subtitle_encoding: request.subtitle_encoding,
+        subtitle_format: request.subtitle_format,
+    })
+}
+
+// 附属文件沿用视频新文件名，保留原文件名中视频名之后的部分（如 .chs.ass）
+fn companion_target_name(video_path: &Path, video_target: &str, companion: &Path) -> String {
+    let target_stem = Path::new(video_target)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(video_target);
+    let companion_name = companion
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let video_stem = video_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+
+    let rest = match companion_name.strip_prefix(video_stem) {
+        Some(rest) if !video_stem.is_empty() && rest.starts_with('.') => rest.to_string(),
+        _ => match detect_language_tag(companion_name) {
+            Some(tag) => format!(".{}.{}", tag, get_extension(companion_name)),
+            None => format!(".{}", get_extension(companion_name)),
+        },
+    };
+    format!("{}{}", target_stem, rest)
+}
+
+// 单季作品通常识别不出季数，按第 1 季处理；分集不是数字（如 SP、OVA）时无法判断是否为特别篇，保持为空
+fn with_default_season(info: &NamingInfo) -> NamingInfo {
+    let mut info = info.clone();
+    let numeric = info
+        .episode
+        .as_deref()
+        .map_or(true, |e| e.trim().chars().all(|c| c.is_ascii_digit()));
+    if info.season.is_none() && numeric {
+        info.season = Some(1);
+    }
+    info
+}
+
+// 按命名模板或媒体服务器预设生成视频（及附属文件）的重命名/移动计划
+pub fn build_video_plan(request: &VideoRenameRequest) -> Result<RenamePlan, String> {
+    let settings = current_settings();
+    let preset = request.preset.or(settings.naming_preset);
+    let template = request
+        .template
+        .clone()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or(settings.naming_template);
+    let target_root = request
+        .target_root
+        .clone()
+        .filter(|r| !r.trim().is_empty())
+        .map(PathBuf::from);
+
+    let mut items = Vec::new();
+    let mut companion_items = Vec::new();
+    for item in &request.items {
+        let source = Path::new(&item.source_path);
+        let info = with_default_season(&item.info);
+        let template = match preset {
+            Some(preset) => preset_template(preset, info.season == Some(0)),
+            None => template.as_str(),
+        };
+        let relative = render_template(template, &info)?;
+        let root = match &target_root {
+            Some(root) => root.clone(),
+            None => source.parent().map(Path::to_path_buf).unwrap_or_default(),
+        };
+        let target = root.join(&relative);
+        let target_name = file_name_of(&relative);
+
+        for companion in &item.companions {
+            let companion = Path::new(companion);
+            let name = companion_target_name(source, &target_name, companion);
+            companion_items.push(RenamePlanItem {
+                source_name: file_name_of(&companion.to_string_lossy()),
+                source_path: companion.to_string_lossy().to_string(),
+                target_path: target.with_file_name(&name).to_string_lossy().to_string(),
+                target_name: name,
+                problems: Vec::new(),
+            });
         }
+        items.push(RenamePlanItem {
+            source_name: file_name_of(&item.source_path),
+            source_path: item.source_path.clone(),
+            target_name,
+            target_path: target.to_string_lossy().to_string(),
+            problems: Vec::new(),
+        });
+        items.append(&mut companion_items);
     }
 
-    // ���������Ļ�ļ�
-    for i in 0..request.subtitle_files.len() {
-        let video_file = &request.video_files[i];
-        let subtitle_file = &request.subtitle_files[i];
+    validate_plan(&mut items, request.create_dirs);
+    let valid = items.iter().all(|item| item.problems.is_empty());
+    Ok(RenamePlan {
+        items,
+        valid,
+        create_dirs: request.create_dirs,
+        subtitle_encoding: None,
+        subtitle_format: None,
+    })
+}
 
-        // 提取视频文件名（去掉扩展名）
-        let video_filename = Path::new(&video_file.name)
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or(&video_file.name)
-            .to_string();
+// 一组重命名中途失败后的回滚结果
+pub struct RollbackReport {
+    pub failed_index: usize,
+    pub error: std::io::Error,
+    pub rolled_back: Vec<RenameOperation>,
+    pub rollback_errors: Vec<String>,
+}
 
-        // 提取字幕扩展名
-        let subtitle_ext = get_extension(&subtitle_file.name);
+fn file_name_of(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path)
+        .to_string()
+}
 
-        // 生成新的文件名
-        let new_filename = if request.suffix.is_empty() {
-            format!("{}.{}", video_filename, subtitle_ext)
-        } else {
-            format!("{}.{}.{}", video_filename, request.suffix, subtitle_ext)
-        };
+#[cfg(windows)]
+const CROSS_DEVICE_ERROR: i32 = 17;
+#[cfg(not(windows))]
+const CROSS_DEVICE_ERROR: i32 = 18;
 
-        // 只有路径为完整路径时才执行实际重命名
-        let subtitle_path = Path::new(&subtitle_file.path);
-        if subtitle_file.path.contains('/') || subtitle_file.path.contains('\\') {
-            if let Some(parent) = subtitle_path.parent() {
-                let new_path = parent.join(&new_filename);
-
-                // 目标路径是否已存在
-                if new_path.exists() {
-                    return Ok(RenameResponse {
-                        success: false,
-                        message: format!("目标文件 {} 已存在", new_filename),
-                        renamed_files,
-                    });
-                }
+// 跨分区移动时 rename 会失败，退回为复制后删除源文件
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.raw_os_error() == Some(CROSS_DEVICE_ERROR) => {
+            fs::copy(from, to)?;
+            if let Err(e) = fs::remove_file(from) {
+                let _ = fs::remove_file(to);
+                return Err(e);
+            }
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+// 移动文件，目标目录不存在时先创建，新建的目录记录在 created_dirs 中
+fn apply_operation(op: &mut RenameOperation) -> io::Result<()> {
+    op.created_dirs.clear();
+    let to = Path::new(&op.to_path);
+    if let Some(dir) = to.parent() {
+        let missing: Vec<&Path> = dir
+            .ancestors()
+            .take_while(|d| !d.as_os_str().is_empty() && !d.exists())
+            .collect();
+        if !missing.is_empty() {
+            fs::create_dir_all(dir)?;
+            op.created_dirs = missing
+                .iter()
+                .rev()
+                .map(|d| d.to_string_lossy().to_string())
+                .collect();
+        }
+    }
+    let result = move_file(Path::new(&op.from_path), to);
+    if result.is_err() {
+        prune_empty_dirs(op);
+        op.created_dirs.clear();
+    }
+    result
+}
 
-                // ִ��������
-                match fs::rename(&subtitle_file.path, &new_path) {
+// 由深到浅删除本次操作新建、现已为空的目录，原本就存在的目录保留
+pub(crate) fn prune_empty_dirs(op: &RenameOperation) {
+    for dir in op.created_dirs.iter().rev() {
+        if fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+}
+
+// 依次执行一组重命名，任一失败时按相反顺序把已完成的部分恢复原名
+pub fn rename_transaction(ops: &mut [RenameOperation]) -> Result<(), RollbackReport> {
+    for i in 0..ops.len() {
+        if let Err(error) = apply_operation(&mut ops[i]) {
+            let mut rolled_back = Vec::new();
+            let mut rollback_errors = Vec::new();
+            for done in ops[..i].iter().rev() {
+                match move_file(Path::new(&done.to_path), Path::new(&done.from_path)) {
                     Ok(_) => {
-                        renamed_files.push(new_filename);
-                    }
-                    Err(e) => {
-                        return Ok(RenameResponse {
-                            success: false,
-                            message: format!("重命名文件失败: {} - {}", subtitle_file.name, e),
-                            renamed_files,
-                        });
+                        prune_empty_dirs(done);
+                        rolled_back.push(done.clone());
                     }
+                    Err(e) => rollback_errors.push(format!("{} - {}", done.to_path, e)),
                 }
             }
-        } else {
-            // 仅提供文件名时，只记录结果，不执行实际重命名
-            renamed_files.push(new_filename);
+            return Err(RollbackReport {
+                failed_index: i,
+                error,
+                rolled_back,
+                rollback_errors,
+            });
         }
     }
+    Ok(())
+}
+
+// 将回滚结果转换为失败响应
+pub fn rollback_response(ops: &[RenameOperation], report: RollbackReport) -> RenameResponse {
+    let mut message = format!(
+        "重命名文件失败: {} - {}，已回滚{}个文件",
+        file_name_of(&ops[report.failed_index].from_path),
+        report.error,
+        report.rolled_back.len()
+    );
+    if !report.rollback_errors.is_empty() {
+        message.push_str(&format!("，{}个文件回滚失败", report.rollback_errors.len()));
+    }
+    // 回滚失败的文件仍保持新名称
+    let renamed_files = ops[..report.failed_index]
+        .iter()
+        .filter(|op| {
+            !report
+                .rolled_back
+                .iter()
+                .any(|done| done.from_path == op.from_path)
+        })
+        .map(|op| file_name_of(&op.to_path))
+        .collect();
+    RenameResponse {
+        success: false,
+        message,
+        renamed_files,
+        rolled_back: report.rolled_back,
+        rollback_errors: report.rollback_errors,
+        ..Default::default()
+    }
+}
+
+// 按计划执行重命名，执行前重新检查，保证执行的就是预览的内容
+fn apply_plan(mut plan: RenamePlan) -> RenameResponse {
+    validate_plan(&mut plan.items, plan.create_dirs);
+    if let Some(item) = plan.items.iter().find(|item| !item.problems.is_empty()) {
+        return RenameResponse {
+            success: false,
+            message: problem_message(item),
+            ..Default::default()
+        };
+    }
 
-    Ok(RenameResponse {
+    // 只有路径为完整路径时才执行实际重命名
+    let mut ops: Vec<RenameOperation> = plan
+        .items
+        .iter()
+        .filter(|item| has_full_path(&item.source_path) && item.source_path != item.target_path)
+        .map(|item| RenameOperation {
+            from_path: item.source_path.clone(),
+            to_path: item.target_path.clone(),
+            created_dirs: Vec::new(),
+        })
+        .collect();
+
+    if let Err(report) = rename_transaction(&mut ops) {
+        return rollback_response(&ops, report);
+    }
+
+    let renamed_files: Vec<String> = plan
+        .items
+        .iter()
+        .map(|item| item.target_name.clone())
+        .collect();
+    let mut message = format!("成功重命名{}个文件", renamed_files.len());
+
+    // 编码转换失败不影响已完成的重命名，结果在 encoding_report 中返回
+    let encoding_report = match plan.subtitle_encoding {
+        Some(target) => {
+            let paths: Vec<String> = plan
+                .items
+                .iter()
+                .filter(|item| has_full_path(&item.target_path))
+                .map(|item| item.target_path.clone())
+                .collect();
+            convert_files(&paths, target)
+        }
+        None => Vec::new(),
+    };
+    let converted = encoding_report.iter().filter(|r| r.converted).count();
+    let failed = encoding_report.iter().filter(|r| r.error.is_some()).count();
+    if converted > 0 {
+        message.push_str(&format!("，转换{}个字幕编码", converted));
+    }
+    if failed > 0 {
+        message.push_str(&format!("，{}个字幕编码转换失败", failed));
+    }
+
+    // 在编码转换之后生成目标格式的字幕，原字幕保留；已是目标格式的跳过
+    let subtitle_conversions: Vec<SubtitleConversion> = match plan.subtitle_format {
+        Some(target) => plan
+            .items
+            .iter()
+            .filter(|item| has_full_path(&item.target_path))
+            .filter(|item| {
+                SubtitleFormat::from_path(&item.target_path).is_some_and(|f| f != target)
+            })
+            .map(|item| convert_subtitle_file(&item.target_path, target, true, false))
+            .collect(),
+        None => Vec::new(),
+    };
+    let generated = subtitle_conversions
+        .iter()
+        .filter(|r| r.target.is_some())
+        .count();
+    let failed = subtitle_conversions.len() - generated;
+    if let Some(target) = plan.subtitle_format.filter(|_| generated > 0) {
+        message.push_str(&format!(
+            "，生成{}个{}字幕",
+            generated,
+            target.extension().to_uppercase()
+        ));
+    }
+    if failed > 0 {
+        message.push_str(&format!("，{}个字幕格式转换失败", failed));
+    }
+
+    // 记录到重命名历史（包括生成的字幕，撤销时一并删除），写入失败不影响本次重命名结果
+    let batch_id = if ops.is_empty() {
+        None
+    } else {
+        let moved = ops
+            .iter()
+            .any(|op| Path::new(&op.from_path).parent() != Path::new(&op.to_path).parent());
+        let action = if moved { "移动" } else { "重命名" };
+        let created_files = subtitle_conversions
+            .iter()
+            .filter_map(|r| r.target.clone())
+            .collect();
+        let description = format!("{}{}个文件", action, ops.len());
+        match rename_journal_path()
+            .and_then(|journal| record_batch(&journal, description, ops, created_files))
+        {
+            Ok(id) => Some(id),
+            Err(e) => {
+                log::warn!("写入重命名历史失败: {}", e);
+                None
+            }
+        }
+    };
+
+    RenameResponse {
         success: true,
-        message: format!("成功重命名{}个文件", renamed_files.len()),
+        message,
         renamed_files,
-    })
+        batch_id,
+        encoding_report,
+        subtitle_conversions,
+        ..Default::default()
+    }
+}
+
+// 预览重命名结果，返回每个文件的源路径、目标路径及存在的问题
+#[tauri::command]
+pub async fn plan_rename(request: RenameRequest) -> Result<RenamePlan, String> {
+    build_plan(&request)
+}
+
+// 按命名模板或媒体服务器预设预览视频重命名，可包含目录移动
+#[tauri::command]
+pub async fn plan_video_rename(request: VideoRenameRequest) -> Result<RenamePlan, String> {
+    build_video_plan(&request)
+}
+
+// 执行 plan_rename / plan_video_rename 返回的计划
+#[tauri::command]
+pub async fn execute_rename_plan(plan: RenamePlan) -> Result<RenameResponse, String> {
+    Ok(apply_plan(plan))
+}
+
+// 重命名字幕文件
+#[tauri::command]
+pub async fn rename_subtitle_files(request: RenameRequest) -> Result<RenameResponse, String> {
+    match build_plan(&request) {
+        Ok(plan) => Ok(apply_plan(plan)),
+        Err(message) => Ok(RenameResponse {
+            success: false,
+            message,
+            ..Default::default()
+        }),
+    }
 }
@@ -0,0 +1,194 @@
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    rename::{is_same_file, prune_empty_dirs, rename_transaction, rollback_response},
+    types::{RenameBatch, RenameBatchStatus, RenameOperation, RenameResponse},
+    utils::rename_journal_path,
+};
+
+// 最多保留的历史批次数量，超出时丢弃最早的记录
+const MAX_BATCHES: usize = 200;
+
+// 串行化历史文件的读改写
+static JOURNAL_LOCK: Mutex<()> = Mutex::new(());
+
+fn load_journal(path: &Path) -> Result<Vec<RenameBatch>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("读取重命名历史失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析重命名历史失败: {}", e))
+}
+
+// 先写临时文件再替换，避免写到一半时崩溃导致历史损坏
+fn save_journal(path: &Path, batches: &[RenameBatch]) -> Result<(), String> {
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(batches)
+        .map_err(|e| format!("序列化重命名历史失败: {}", e))?;
+    let mut f = fs::File::create(&tmp).map_err(|e| format!("创建重命名历史失败: {}", e))?;
+    f.write_all(json.as_bytes())
+        .and_then(|_| f.sync_all())
+        .map_err(|e| format!("保存重命名历史失败: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("保存重命名历史失败: {}", e))
+}
+
+// 在 journal 指定的历史文件中记录一次成功的重命名批次，返回批次 ID
+pub fn record_batch(
+    journal: &Path,
+    description: String,
+    operations: Vec<RenameOperation>,
+    created_files: Vec<String>,
+) -> Result<u64, String> {
+    let _guard = JOURNAL_LOCK.lock().map_err(|_| "重命名历史被锁定")?;
+    let mut batches = load_journal(journal)?;
+    let id = batches.iter().map(|b| b.id).max().unwrap_or(0) + 1;
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    batches.push(RenameBatch {
+        id,
+        created_at,
+        description,
+        operations,
+        created_files,
+        status: RenameBatchStatus::Applied,
+    });
+    if batches.len() > MAX_BATCHES {
+        let overflow = batches.len() - MAX_BATCHES;
+        batches.drain(..overflow);
+    }
+    save_journal(journal, &batches)?;
+    Ok(id)
+}
+
+// 检查文件是否仍在历史记录预期的位置；仅改大小写时目标路径指向文件自身，不算占用
+fn check_positions(ops: &[RenameOperation]) -> Result<(), String> {
+    for op in ops {
+        let from = Path::new(&op.from_path);
+        let to = Path::new(&op.to_path);
+        if !from.exists() {
+            return Err(format!("文件不在预期位置: {}", op.from_path));
+        }
+        if to.exists() && !is_same_file(from, to) {
+            return Err(format!("目标路径已被占用: {}", op.to_path));
+        }
+    }
+    Ok(())
+}
+
+// 将批次切换到指定状态；撤销时按相反顺序执行反向操作
+pub fn switch_batch(
+    journal: &Path,
+    id: u64,
+    target: RenameBatchStatus,
+) -> Result<RenameResponse, String> {
+    let _guard = JOURNAL_LOCK.lock().map_err(|_| "重命名历史被锁定")?;
+    let mut batches = load_journal(journal)?;
+    let batch = batches
+        .iter_mut()
+        .find(|b| b.id == id)
+        .ok_or(format!("未找到重命名批次: {}", id))?;
+
+    if batch.status == target {
+        let message = match target {
+            RenameBatchStatus::Undone => "该批次已撤销",
+            RenameBatchStatus::Applied => "该批次已重做",
+        };
+        return Ok(RenameResponse {
+            success: false,
+            message: message.to_string(),
+            ..Default::default()
+        });
+    }
+
+    let mut ops: Vec<RenameOperation> = match target {
+        RenameBatchStatus::Undone => batch
+            .operations
+            .iter()
+            .rev()
+            .map(|op| RenameOperation {
+                from_path: op.to_path.clone(),
+                to_path: op.from_path.clone(),
+                created_dirs: Vec::new(),
+            })
+            .collect(),
+        RenameBatchStatus::Applied => batch.operations.clone(),
+    };
+
+    if let Err(message) = check_positions(&ops) {
+        return Ok(RenameResponse {
+            success: false,
+            message,
+            ..Default::default()
+        });
+    }
+    if let Err(report) = rename_transaction(&mut ops) {
+        return Ok(rollback_response(&ops, report));
+    }
+
+    // 撤销时删除本批次生成的文件，再清理整理时新建、现已为空的目录
+    let mut removed = 0;
+    if target == RenameBatchStatus::Undone {
+        for path in batch.created_files.drain(..) {
+            match fs::remove_file(&path) {
+                Ok(_) => removed += 1,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => log::warn!("删除生成的文件失败: {} - {}", path, e),
+            }
+        }
+        batch.operations.iter().rev().for_each(prune_empty_dirs);
+    } else {
+        // 重做时记录这次新建的目录，供下次撤销时清理
+        batch.operations = ops.clone();
+    }
+
+    batch.status = target;
+    save_journal(journal, &batches)?;
+
+    let renamed_files = ops
+        .iter()
+        .filter_map(|op| Path::new(&op.to_path).file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    let action = match target {
+        RenameBatchStatus::Undone => "撤销",
+        RenameBatchStatus::Applied => "重做",
+    };
+    let mut message = format!("已{}{}个文件的重命名", action, renamed_files.len());
+    if removed > 0 {
+        message.push_str(&format!("，删除{}个生成的文件", removed));
+    }
+    Ok(RenameResponse {
+        success: true,
+        message,
+        renamed_files,
+        batch_id: Some(id),
+        ..Default::default()
+    })
+}
+
+// 列出重命名历史，最新的在前
+#[tauri::command]
+pub async fn list_rename_history() -> Result<Vec<RenameBatch>, String> {
+    let _guard = JOURNAL_LOCK.lock().map_err(|_| "重命名历史被锁定")?;
+    let mut batches = load_journal(&rename_journal_path()?)?;
+    batches.reverse();
+    Ok(batches)
+}
+
+#[tauri::command]
+pub async fn undo_rename_batch(id: u64) -> Result<RenameResponse, String> {
+    switch_batch(&rename_journal_path()?, id, RenameBatchStatus::Undone)
+}
+
+#[tauri::command]
+pub async fn redo_rename_batch(id: u64) -> Result<RenameResponse, String> {
+    switch_batch(&rename_journal_path()?, id, RenameBatchStatus::Applied)
+}
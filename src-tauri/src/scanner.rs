@@ -0,0 +1,216 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+
+use crate::{
+    settings::current_settings,
+    types::{FileInfo, ScanOptions, Settings},
+    utils::{is_archive_file, is_subtitle_file, is_video_file},
+};
+
+impl From<&Settings> for ScanOptions {
+    fn from(settings: &Settings) -> Self {
+        ScanOptions {
+            max_depth: settings.scan_max_depth,
+            exclude: settings.scan_exclude.clone(),
+            follow_symlinks: settings.scan_follow_symlinks,
+            include_subtitles: settings.scan_include_subtitles,
+        }
+    }
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions::from(&Settings::default())
+    }
+}
+
+pub(crate) fn resolve_options(options: Option<ScanOptions>) -> ScanOptions {
+    options.unwrap_or_else(|| ScanOptions::from(&current_settings()))
+}
+
+struct ExcludeRule {
+    regex: Regex,
+    dir_only: bool,
+    negate: bool,
+}
+
+// 排除规则集合，按顺序匹配，最后一条匹配的规则生效；不区分大小写
+pub struct ExcludeSet {
+    rules: Vec<ExcludeRule>,
+}
+
+// 通配符转为正则：* 和 ? 不跨越 /，** 匹配任意层文件夹
+fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    out.push_str("(?:.*/)?");
+                } else {
+                    out.push_str(".*");
+                }
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    out
+}
+
+impl ExcludeSet {
+    pub fn new(patterns: &[String]) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for raw in patterns {
+            let pattern = raw.trim();
+            if pattern.is_empty() || pattern.starts_with('#') {
+                continue;
+            }
+            let (negate, pattern) = match pattern.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, pattern),
+            };
+            let (dir_only, pattern) = match pattern.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, pattern),
+            };
+            // 含 / 的规则相对所选文件夹匹配，否则匹配任意层级的名称
+            let anchored = pattern.contains('/');
+            let body = glob_to_regex(pattern.trim_start_matches('/'));
+            let source = if anchored {
+                format!("(?i)^{}$", body)
+            } else {
+                format!("(?i)(?:^|/){}$", body)
+            };
+            let regex = Regex::new(&source).map_err(|e| format!("排除规则 {} 无效: {}", raw, e))?;
+            rules.push(ExcludeRule {
+                regex,
+                dir_only,
+                negate,
+            });
+        }
+        Ok(ExcludeSet { rules })
+    }
+
+    // relative 为相对所选文件夹、以 / 分隔的路径
+    pub fn is_excluded(&self, relative: &str, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (is_dir || !rule.dir_only) && rule.regex.is_match(relative))
+            .is_some_and(|rule| !rule.negate)
+    }
+}
+
+// 字幕包与字幕一样受 include_subtitles 控制
+fn file_info(path: &Path, include_subtitles: bool) -> Option<FileInfo> {
+    let name = path.file_name()?.to_str()?;
+    let is_video = is_video_file(name);
+    let is_archive = is_archive_file(name);
+    if !(is_video || include_subtitles && (is_subtitle_file(name) || is_archive)) {
+        return None;
+    }
+    Some(FileInfo {
+        name: name.to_string(),
+        path: path.to_string_lossy().to_string(),
+        is_video,
+        is_archive,
+    })
+}
+
+struct Walker<'a> {
+    options: &'a ScanOptions,
+    exclude: ExcludeSet,
+    // 跟随符号链接时记录已进入的文件夹，防止链接成环
+    visited: HashSet<PathBuf>,
+    files: Vec<FileInfo>,
+}
+
+impl Walker<'_> {
+    fn walk(&mut self, dir: &Path, relative: &str, depth: u32) -> std::io::Result<()> {
+        if let Ok(canonical) = dir.canonicalize() {
+            if !self.visited.insert(canonical) {
+                return Ok(());
+            }
+        }
+        let mut entries: Vec<_> = fs::read_dir(dir)?.flatten().collect();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let path = entry.path();
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            let child = if relative.is_empty() {
+                name
+            } else {
+                format!("{}/{}", relative, name)
+            };
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            // 不跟随时跳过指向文件夹的链接，指向文件的链接照常读取
+            let is_dir = if file_type.is_symlink() {
+                match fs::metadata(&path) {
+                    Ok(meta) if meta.is_dir() && !self.options.follow_symlinks => continue,
+                    Ok(meta) => meta.is_dir(),
+                    Err(_) => continue,
+                }
+            } else {
+                file_type.is_dir()
+            };
+            if self.exclude.is_excluded(&child, is_dir) {
+                continue;
+            }
+            if is_dir {
+                if depth < self.options.max_depth {
+                    // 子文件夹无法读取时跳过，不影响其余结果
+                    if let Err(e) = self.walk(&path, &child, depth + 1) {
+                        log::warn!("扫描文件夹 {} 失败: {}", path.display(), e);
+                    }
+                }
+            } else if let Some(info) = file_info(&path, self.options.include_subtitles) {
+                self.files.push(info);
+            }
+        }
+        Ok(())
+    }
+}
+
+// 扫描文件夹中的视频（及字幕）文件，结果按路径排序
+pub fn scan_dir(root: &Path, options: &ScanOptions) -> Result<Vec<FileInfo>, String> {
+    let mut walker = Walker {
+        options,
+        exclude: ExcludeSet::new(&options.exclude)?,
+        visited: HashSet::new(),
+        files: Vec::new(),
+    };
+    walker
+        .walk(root, "", 0)
+        .map_err(|e| format!("扫描文件夹失败: {}", e))?;
+    Ok(walker.files)
+}
+
+// 处理选择或拖入的路径：文件直接加入，文件夹按选项扫描；重复的路径只保留一次
+pub fn scan_paths(paths: &[PathBuf], options: &ScanOptions) -> Result<Vec<FileInfo>, String> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            files.extend(scan_dir(path, options)?);
+        } else if path.is_file() {
+            // 直接选择的文件不受排除规则和字幕选项影响
+            files.extend(file_info(path, true));
+        }
+    }
+    let mut seen = HashSet::new();
+    files.retain(|f| seen.insert(f.path.clone()));
+    Ok(files)
+}
@@ -0,0 +1,192 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Mutex,
+};
+
+use crate::{
+    bangumi::{BangumiProvider, RelatedSubject, CACHE_NAMESPACE},
+    metadata_cache::{cached, CachedProvider},
+    metadata_provider::MetadataProvider,
+    types::{EpisodeMapping, MetadataSubjectDetail, SeasonBoundary},
+};
+
+const RELATION_PREQUEL: &str = "前传";
+const RELATION_SEQUEL: &str = "续集";
+// 防止关联数据成环或过长时无限请求
+const MAX_CHAIN_LEN: usize = 30;
+
+// 按条目 ID 缓存系列的分季信息，系列中每个条目都指向同一份结果
+static SEASON_MAPS: Mutex<Option<HashMap<i64, Vec<SeasonBoundary>>>> = Mutex::new(None);
+
+// 只有 TV/WEB 动画计为一季，剧场版、OVA 等只用于继续查找前传/续集
+fn is_series(detail: &MetadataSubjectDetail) -> bool {
+    matches!(detail.platform.as_deref(), None | Some("TV") | Some("WEB"))
+}
+
+// 查找前传或续集，多个候选时优先 TV/WEB 条目
+async fn next_in_chain(
+    bangumi: &CachedProvider<BangumiProvider>,
+    subject_id: i64,
+    relation: &str,
+) -> Result<Option<MetadataSubjectDetail>, String> {
+    let related: Vec<RelatedSubject> = cached(
+        bangumi.namespace(),
+        "related",
+        &subject_id.to_string(),
+        bangumi.inner().related(subject_id),
+    )
+    .await?;
+    let mut fallback = None;
+    for r in related.iter().filter(|r| r.relation == relation) {
+        let detail = bangumi.detail(r.id).await?;
+        if is_series(&detail) {
+            return Ok(Some(detail));
+        }
+        fallback.get_or_insert(detail);
+    }
+    Ok(fallback)
+}
+
+// 分季关系只有 Bangumi 提供；沿前传找到系列的第一部，再沿续集依次编号，按各季集数计算绝对集数的起点
+async fn build_season_map(subject_id: i64) -> Result<Vec<SeasonBoundary>, String> {
+    let bangumi = CachedProvider::new(CACHE_NAMESPACE, BangumiProvider::from_settings());
+    let start = bangumi.detail(subject_id).await?;
+    let mut seen = HashSet::from([start.id]);
+    let mut chain = VecDeque::from([start]);
+
+    while chain.len() < MAX_CHAIN_LEN {
+        let first = chain.front().map_or(subject_id, |d| d.id);
+        match next_in_chain(&bangumi, first, RELATION_PREQUEL).await? {
+            Some(detail) if seen.insert(detail.id) => chain.push_front(detail),
+            _ => break,
+        }
+    }
+    while chain.len() < MAX_CHAIN_LEN {
+        let last = chain.back().map_or(subject_id, |d| d.id);
+        match next_in_chain(&bangumi, last, RELATION_SEQUEL).await? {
+            Some(detail) if seen.insert(detail.id) => chain.push_back(detail),
+            _ => break,
+        }
+    }
+
+    let mut boundaries: Vec<SeasonBoundary> = Vec::new();
+    let mut next_episode = 1;
+    for detail in chain.into_iter().filter(is_series) {
+        let episodes = detail.episodes.unwrap_or(0).max(0) as u32;
+        boundaries.push(SeasonBoundary {
+            subject_id: detail.id,
+            season: boundaries.len() as u32 + 1,
+            name: detail.name,
+            name_cn: detail.name_cn,
+            first_episode: next_episode,
+            episodes,
+        });
+        next_episode += episodes;
+    }
+    Ok(boundaries)
+}
+
+pub(crate) async fn season_map(subject_id: i64) -> Result<Vec<SeasonBoundary>, String> {
+    if let Ok(guard) = SEASON_MAPS.lock() {
+        if let Some(boundaries) = guard.as_ref().and_then(|maps| maps.get(&subject_id)) {
+            return Ok(boundaries.clone());
+        }
+    }
+    let boundaries = build_season_map(subject_id).await?;
+    if let Ok(mut guard) = SEASON_MAPS.lock() {
+        let maps = guard.get_or_insert_with(HashMap::new);
+        maps.insert(subject_id, boundaries.clone());
+        for boundary in &boundaries {
+            maps.insert(boundary.subject_id, boundaries.clone());
+        }
+    }
+    Ok(boundaries)
+}
+
+// 绝对集数转为 (季, 季内集数)；最后一季集数未知时不限上限
+pub fn absolute_to_seasonal(boundaries: &[SeasonBoundary], absolute: u32) -> Option<(u32, u32)> {
+    let last = boundaries.last()?;
+    boundaries
+        .iter()
+        .find(|b| {
+            absolute >= b.first_episode
+                && (absolute < b.first_episode + b.episodes
+                    || (b.episodes == 0 && b.season == last.season))
+        })
+        .map(|b| (b.season, absolute - b.first_episode + 1))
+}
+
+pub fn seasonal_to_absolute(
+    boundaries: &[SeasonBoundary],
+    season: u32,
+    episode: u32,
+) -> Option<u32> {
+    let b = boundaries.iter().find(|b| b.season == season)?;
+    (episode >= 1 && (b.episodes == 0 || episode <= b.episodes))
+        .then(|| b.first_episode + episode - 1)
+}
+
+// 拆出集数的整数部分，小数部分（如 6.5 的 .5）原样保留
+fn split_episode(raw: &str) -> Option<(u32, &str)> {
+    let raw = raw.trim();
+    let (int, frac) = match raw.find('.') {
+        Some(i) => raw.split_at(i),
+        None => (raw, ""),
+    };
+    Some((int.parse().ok()?, frac))
+}
+
+// 映射一个识别出的集数：不超过所选条目集数时视为季内集数，否则视为整个系列的绝对集数
+pub fn map_episode(
+    boundaries: &[SeasonBoundary],
+    subject_id: i64,
+    episode: &str,
+) -> EpisodeMapping {
+    let unmapped = EpisodeMapping {
+        episode: episode.to_string(),
+        ..Default::default()
+    };
+    let Some((number, frac)) = split_episode(episode) else {
+        return unmapped;
+    };
+    let selected = boundaries.iter().find(|b| b.subject_id == subject_id);
+    let seasonal = match selected {
+        Some(b) if b.episodes == 0 || number <= b.episodes => Some((b.season, number)),
+        _ => absolute_to_seasonal(boundaries, number),
+    };
+    let Some((season, seasonal_episode)) = seasonal else {
+        return unmapped;
+    };
+    EpisodeMapping {
+        subject_id: boundaries
+            .iter()
+            .find(|b| b.season == season)
+            .map(|b| b.subject_id),
+        season: Some(season),
+        seasonal_episode: Some(format!("{:02}{}", seasonal_episode, frac)),
+        absolute: seasonal_to_absolute(boundaries, season, seasonal_episode),
+        ..unmapped
+    }
+}
+
+// 获取条目所在系列的分季信息
+#[tauri::command]
+pub async fn get_season_map(subject_id: i64) -> Result<Vec<SeasonBoundary>, String> {
+    season_map(subject_id).await
+}
+
+// 将识别出的集数（季内或绝对集数）映射为系列中的季与季内集数
+#[tauri::command]
+pub async fn map_episode_numbers(
+    subject_id: i64,
+    episodes: Vec<String>,
+) -> Result<Vec<EpisodeMapping>, String> {
+    if episodes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let boundaries = season_map(subject_id).await?;
+    Ok(episodes
+        .iter()
+        .map(|episode| map_episode(&boundaries, subject_id, episode))
+        .collect())
+}
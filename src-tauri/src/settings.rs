@@ -1,12 +1,49 @@
 use std::{env, fs, io::Write, path::PathBuf};
 
-use crate::{types::Settings, utils::settings_path};
+use crate::{
+    naming::DEFAULT_NAMING_TEMPLATE,
+    types::{LlmProviderKind, MetadataProviderKind, Settings},
+    utils::settings_path,
+};
 
-fn default_settings() -> Settings {
-    Settings {
-        episode_regex: "\\[(\\d{2})\\]".to_string(),
-        model_url: "http://localhost:11434/v1/chat/completions".to_string(),
-        model_name: "qwen/qwen3-vl-8b".to_string(),
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            episode_regex: "\\[(\\d{2})\\]".to_string(),
+            model_url: "http://localhost:11434/v1/chat/completions".to_string(),
+            model_name: "qwen/qwen3-vl-8b".to_string(),
+            parser_confidence_threshold: 0.8,
+            naming_template: DEFAULT_NAMING_TEMPLATE.to_string(),
+            naming_preset: None,
+            media_library_root: None,
+            llm_cache_ttl_days: 30,
+            llm_cache_max_entries: 5000,
+            llm_concurrency: 2,
+            llm_provider: LlmProviderKind::default(),
+            llm_api_key: String::new(),
+            llm_structured_output: true,
+            llm_timeout_secs: 300,
+            api_timeout_secs: 10,
+            http_max_retries: 3,
+            http_retry_base_ms: 1000,
+            metadata_provider: MetadataProviderKind::default(),
+            bangumi_api_url: "https://api.bgm.tv".to_string(),
+            anilist_api_url: "https://graphql.anilist.co".to_string(),
+            tmdb_api_url: "https://api.themoviedb.org/3".to_string(),
+            tmdb_api_key: String::new(),
+            anidb_api_url: "http://api.anidb.net:9001/httpapi".to_string(),
+            anidb_client: String::new(),
+            anidb_client_version: 1,
+            metadata_cache_ttl_days: 7,
+            offline_mode: false,
+            scan_max_depth: 3,
+            scan_exclude: ["SPs/", "CDs/", "Scans/", "*sample*"]
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            scan_follow_symlinks: false,
+            scan_include_subtitles: true,
+        }
     }
 }
 
@@ -15,8 +52,7 @@ fn legacy_settings_path() -> Result<PathBuf, String> {
     Ok(cwd.join("settings.json"))
 }
 
-#[tauri::command]
-pub(crate) async fn load_settings() -> Result<Settings, String> {
+pub(crate) fn read_settings() -> Result<Settings, String> {
     let path = settings_path()?;
     let target = if path.exists() {
         path
@@ -25,7 +61,7 @@ pub(crate) async fn load_settings() -> Result<Settings, String> {
         if legacy.exists() {
             legacy
         } else {
-            return Ok(default_settings());
+            return Ok(Settings::default());
         }
     };
     let content = fs::read_to_string(&target).map_err(|e| format!("读取设置失败: {}", e))?;
@@ -33,6 +69,16 @@ pub(crate) async fn load_settings() -> Result<Settings, String> {
     Ok(s)
 }
 
+// 供后端内部使用，读取失败时退回默认设置
+pub(crate) fn current_settings() -> Settings {
+    read_settings().unwrap_or_default()
+}
+
+#[tauri::command]
+pub(crate) async fn load_settings() -> Result<Settings, String> {
+    read_settings()
+}
+
 #[tauri::command]
 pub(crate) async fn save_settings(settings: Settings) -> Result<bool, String> {
     let path = settings_path()?;
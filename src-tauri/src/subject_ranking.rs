@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    metadata_provider::{parse_year, resolve_provider},
+    parser::parse_filename,
+    types::{
+        CandidateRanking, MetadataProviderKind, MetadataSubject, MetadataSubjectDetail,
+        ScoredCandidate,
+    },
+};
+
+// 各项在总分中的权重；年份或集数缺失时按 0.5 计，不加分也不扣分
+const TITLE_WEIGHT: f32 = 0.7;
+const YEAR_WEIGHT: f32 = 0.15;
+const EPISODE_WEIGHT: f32 = 0.15;
+const NEUTRAL_SCORE: f32 = 0.5;
+// 最高分达到阈值且领先第二名足够多时自动选择
+pub const AUTO_SELECT_THRESHOLD: f32 = 0.8;
+pub const AUTO_SELECT_MARGIN: f32 = 0.1;
+
+// 比较前统一大小写、全角字符，并去掉空格与标点
+pub fn normalize_title(title: &str) -> String {
+    title
+        .chars()
+        .map(|c| match c {
+            '\u{ff01}'..='\u{ff5e}' => char::from_u32(c as u32 - 0xfee0).unwrap_or(c),
+            _ => c,
+        })
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != cb);
+            curr[j + 1] = substitute.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+// 归一化编辑距离转为相似度，1 表示完全相同
+pub fn title_similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = normalize_title(a).chars().collect();
+    let b: Vec<char> = normalize_title(b).chars().collect();
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    1.0 - edit_distance(&a, &b) as f32 / a.len().max(b.len()) as f32
+}
+
+// 从文件名中统计年份（取出现最多的）和本篇集数，集数解析不出时按文件数计
+pub fn files_summary(filenames: &[String]) -> (Option<i32>, usize) {
+    let mut years: HashMap<i32, usize> = HashMap::new();
+    let mut episodes = HashSet::new();
+    for name in filenames {
+        let info = parse_filename(name).info;
+        if let Some(year) = info.year {
+            *years.entry(year).or_default() += 1;
+        }
+        if let Some(episode) = info.episode.filter(|_| info.season != Some(0)) {
+            episodes.insert(episode);
+        }
+    }
+    let year = years
+        .into_iter()
+        .max_by_key(|(year, count)| (*count, *year))
+        .map(|(year, _)| year);
+    let count = if episodes.is_empty() {
+        filenames.len()
+    } else {
+        episodes.len()
+    };
+    (year, count)
+}
+
+fn year_score(files_year: Option<i32>, year: Option<i32>) -> Option<f32> {
+    // 年末开播或次年发售 BD 时文件名年份可能相差一年
+    match (files_year?, year?) {
+        (a, b) if a == b => Some(1.0),
+        (a, b) if (a - b).abs() == 1 => Some(0.5),
+        _ => Some(0.0),
+    }
+}
+
+fn episode_score(file_count: usize, episodes: Option<i32>) -> Option<f32> {
+    let episodes = episodes.filter(|n| *n > 0)? as usize;
+    if file_count == 0 {
+        return None;
+    }
+    // 文件少于总集数可能是连载中或未下载完整，文件多于总集数则多半不是这一部
+    Some(match file_count.cmp(&episodes) {
+        std::cmp::Ordering::Equal => 1.0,
+        std::cmp::Ordering::Less => 0.7,
+        std::cmp::Ordering::Greater => 0.0,
+    })
+}
+
+pub fn score_candidate(
+    detail: MetadataSubjectDetail,
+    title: &str,
+    files_year: Option<i32>,
+    file_count: usize,
+) -> ScoredCandidate {
+    let (title_score, matched_title) = std::iter::once(&detail.name)
+        .chain(detail.name_cn.as_ref())
+        .chain(detail.aliases.iter())
+        .map(|name| (title_similarity(title, name), name))
+        .fold((0.0, ""), |best, (score, name)| {
+            if score > best.0 {
+                (score, name.as_str())
+            } else {
+                best
+            }
+        });
+    let matched_title = matched_title.to_string();
+    let year_score = year_score(files_year, detail.year);
+    let episode_score = episode_score(file_count, detail.episodes);
+    let score = TITLE_WEIGHT * title_score
+        + YEAR_WEIGHT * year_score.unwrap_or(NEUTRAL_SCORE)
+        + EPISODE_WEIGHT * episode_score.unwrap_or(NEUTRAL_SCORE);
+    ScoredCandidate {
+        detail,
+        score,
+        title_score,
+        matched_title,
+        year_score,
+        episode_score,
+    }
+}
+
+// 按得分排序，并判断最高分是否足以自动选择
+pub fn rank_candidates(
+    details: Vec<MetadataSubjectDetail>,
+    title: &str,
+    filenames: &[String],
+) -> CandidateRanking {
+    let (files_year, file_count) = files_summary(filenames);
+    let mut candidates: Vec<ScoredCandidate> = details
+        .into_iter()
+        .map(|detail| score_candidate(detail, title, files_year, file_count))
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let selected = match candidates.as_slice() {
+        [first, rest @ ..]
+            if first.score >= AUTO_SELECT_THRESHOLD
+                && rest.first().map_or(true, |second| {
+                    first.score - second.score >= AUTO_SELECT_MARGIN
+                }) =>
+        {
+            Some(first.detail.id)
+        }
+        _ => None,
+    };
+    CandidateRanking {
+        candidates,
+        selected,
+    }
+}
+
+// 获取详情失败时只用搜索结果中的信息评分
+fn detail_from_subject(subject: MetadataSubject) -> MetadataSubjectDetail {
+    MetadataSubjectDetail {
+        id: subject.id,
+        name: subject.name,
+        name_cn: subject.name_cn,
+        cover_url: None,
+        episodes: None,
+        year: parse_year(subject.date.as_deref()),
+        aliases: Vec::new(),
+        tags: Vec::new(),
+        platform: subject.platform,
+        air_date: subject.date,
+        episode_list: Vec::new(),
+    }
+}
+
+// 搜索识别出的标题，按标题相似度、年份和集数为候选条目打分
+#[tauri::command]
+pub async fn rank_metadata_candidates(
+    provider: Option<MetadataProviderKind>,
+    title: String,
+    filenames: Vec<String>,
+    limit: Option<usize>,
+) -> Result<CandidateRanking, String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Ok(CandidateRanking {
+            candidates: Vec::new(),
+            selected: None,
+        });
+    }
+    let provider = resolve_provider(provider);
+    let subjects = provider.search(title, limit.unwrap_or(5)).await?;
+    let details = futures::future::join_all(subjects.into_iter().map(|subject| {
+        let provider = &provider;
+        async move {
+            match provider.detail(subject.id).await {
+                Ok(detail) => detail,
+                Err(_) => detail_from_subject(subject),
+            }
+        }
+    }))
+    .await;
+    Ok(rank_candidates(details, title, &filenames))
+}
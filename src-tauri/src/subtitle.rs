@@ -0,0 +1,951 @@
+use std::{collections::HashMap, fs, path::Path, sync::OnceLock};
+
+use regex::Regex;
+
+use crate::{
+    subtitle_encoding::decode_subtitle,
+    types::{SubtitleConversion, SubtitleFormat},
+    utils::get_extension,
+};
+
+// ASS 与 SSA 样式行的字段，SSA 的 TertiaryColour 对应 ASS 的 OutlineColour
+const ASS_STYLE_FIELDS: [&str; 23] = [
+    "Name",
+    "Fontname",
+    "Fontsize",
+    "PrimaryColour",
+    "SecondaryColour",
+    "OutlineColour",
+    "BackColour",
+    "Bold",
+    "Italic",
+    "Underline",
+    "StrikeOut",
+    "ScaleX",
+    "ScaleY",
+    "Spacing",
+    "Angle",
+    "BorderStyle",
+    "Outline",
+    "Shadow",
+    "Alignment",
+    "MarginL",
+    "MarginR",
+    "MarginV",
+    "Encoding",
+];
+const SSA_STYLE_FIELDS: [&str; 18] = [
+    "Name",
+    "Fontname",
+    "Fontsize",
+    "PrimaryColour",
+    "SecondaryColour",
+    "TertiaryColour",
+    "BackColour",
+    "Bold",
+    "Italic",
+    "BorderStyle",
+    "Outline",
+    "Shadow",
+    "Alignment",
+    "MarginL",
+    "MarginR",
+    "MarginV",
+    "AlphaLevel",
+    "Encoding",
+];
+const ASS_EVENT_FORMAT: &str =
+    "Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text";
+const SSA_EVENT_FORMAT: &str =
+    "Marked, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text";
+// 由 SRT/WebVTT 生成 ASS 时使用的脚本信息与默认样式，字体选择常见的中文字体
+const DEFAULT_SCRIPT_INFO: [&str; 4] = [
+    "PlayResX: 1920",
+    "PlayResY: 1080",
+    "WrapStyle: 0",
+    "ScaledBorderAndShadow: yes",
+];
+const DEFAULT_STYLE: &str = "Default,Microsoft YaHei,72,&H00FFFFFF,&H000000FF,&H00000000,&H80000000,0,0,0,0,100,100,0,0,1,3,1,2,30,30,45,1";
+
+// 键为小写的 ASS 样式字段名
+pub type AssStyle = HashMap<String, String>;
+
+// 来自 ASS/SSA 的脚本信息、样式和其他段落，写回 ASS/SSA 时保留
+#[derive(Clone, Debug, Default)]
+pub struct AssScript {
+    // [Script Info] 中除 ScriptType 外的行
+    pub script_info: Vec<String>,
+    pub styles: Vec<AssStyle>,
+    // [Fonts]、[Graphics] 等段落，含段落标题原样保留
+    pub extra_sections: Vec<String>,
+}
+
+// 一条字幕，时间单位为毫秒；text 统一使用 ASS 写法（{\i1} 覆盖标签，\N 换行）
+#[derive(Clone, Debug, Default)]
+pub struct Cue {
+    pub start: i64,
+    pub end: i64,
+    pub text: String,
+    pub style: String,
+    pub actor: String,
+    pub layer: i32,
+    pub margin_l: i32,
+    pub margin_r: i32,
+    pub margin_v: i32,
+    pub effect: String,
+    // ASS 的 Comment 行，不输出到 SRT/WebVTT
+    pub comment: bool,
+    // WebVTT 的 cue 设置，如 line:0 align:start
+    pub vtt_settings: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Subtitle {
+    pub cues: Vec<Cue>,
+    pub ass: Option<AssScript>,
+}
+
+impl SubtitleFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::Ass => "ass",
+            SubtitleFormat::Ssa => "ssa",
+            SubtitleFormat::Vtt => "vtt",
+        }
+    }
+
+    pub fn from_path(path: &str) -> Option<Self> {
+        match get_extension(path).as_str() {
+            "srt" => Some(SubtitleFormat::Srt),
+            "ass" => Some(SubtitleFormat::Ass),
+            "ssa" => Some(SubtitleFormat::Ssa),
+            "vtt" => Some(SubtitleFormat::Vtt),
+            _ => None,
+        }
+    }
+}
+
+// 解析 HH:MM:SS,mmm、HH:MM:SS.mmm、H:MM:SS.cc 及 WebVTT 省略小时的 MM:SS.mmm
+pub fn parse_timestamp(raw: &str) -> Option<i64> {
+    let raw = raw.trim();
+    let (clock, frac) = match raw.rfind([',', '.']) {
+        Some(i) => (&raw[..i], &raw[i + 1..]),
+        None => (raw, "0"),
+    };
+    if frac.is_empty() || !frac.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // 小数部分按位数换算，.5 / .50 / .500 都是 500 毫秒
+    let millis: i64 = format!("{:0<3}", &frac[..frac.len().min(3)]).parse().ok()?;
+    let parts: Vec<i64> = clock
+        .split(':')
+        .map(|p| p.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let seconds = match parts.as_slice() {
+        [h, m, s] => h * 3600 + m * 60 + s,
+        [m, s] => m * 60 + s,
+        _ => return None,
+    };
+    Some(seconds * 1000 + millis)
+}
+
+fn split_clock(ms: i64) -> (i64, i64, i64, i64) {
+    let ms = ms.max(0);
+    (ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000)
+}
+
+pub fn format_timestamp(ms: i64, format: SubtitleFormat) -> String {
+    match format {
+        SubtitleFormat::Srt | SubtitleFormat::Vtt => {
+            let (h, m, s, ms) = split_clock(ms);
+            let sep = if format == SubtitleFormat::Srt {
+                ','
+            } else {
+                '.'
+            };
+            format!("{:02}:{:02}:{:02}{}{:03}", h, m, s, sep, ms)
+        }
+        // ASS 精确到百分之一秒，四舍五入
+        SubtitleFormat::Ass | SubtitleFormat::Ssa => {
+            let (h, m, s, cs) = split_clock((ms.max(0) + 5) / 10 * 10);
+            format!("{}:{:02}:{:02}.{:02}", h, m, s, cs / 10)
+        }
+    }
+}
+
+// 解析 "开始 --> 结束 [设置]" 时间行
+pub(crate) fn parse_timing(line: &str) -> Option<(i64, i64, String)> {
+    let (start, rest) = line.split_once("-->")?;
+    let rest = rest.trim();
+    let (end, settings) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    Some((
+        parse_timestamp(start)?,
+        parse_timestamp(end)?,
+        settings.trim().to_string(),
+    ))
+}
+
+fn html_tag_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    CELL.get_or_init(|| {
+        Regex::new(r"<(/?)([A-Za-z]+|\d[\d:.]*)((?:\.[^\s>]*)?(?:\s[^>]*)?)>").unwrap()
+    })
+}
+
+fn font_color_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    CELL.get_or_init(|| Regex::new(r#"(?i)color\s*=\s*["']?#([0-9a-f]{6})"#).unwrap())
+}
+
+fn ass_tag_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    CELL.get_or_init(|| {
+        Regex::new(r"\\(?:([ibus])(\d*)|1?c(&H[0-9A-Fa-f]+&?)?|p(\d+)|(r)[^\\]*)").unwrap()
+    })
+}
+
+// SRT/WebVTT 的 HTML 标签转为 ASS 覆盖标签；返回文本和 <v 说话人> 中的说话人
+fn html_to_ass(text: &str, vtt: bool) -> (String, String) {
+    let mut actor = String::new();
+    let converted = html_tag_re().replace_all(text, |caps: &regex::Captures| {
+        let closing = !caps[1].is_empty();
+        let name = caps[2].to_lowercase();
+        match name.as_str() {
+            "i" | "b" | "u" | "s" => format!("{{\\{}{}}}", name, if closing { 0 } else { 1 }),
+            "font" if closing => "{\\c}".to_string(),
+            "font" => font_color_re()
+                .captures(&caps[3])
+                .map(|c| {
+                    let rgb = c[1].to_uppercase();
+                    format!("{{\\c&H{}{}{}&}}", &rgb[4..6], &rgb[2..4], &rgb[0..2])
+                })
+                .unwrap_or_default(),
+            "v" if !closing => {
+                actor = caps[3].trim().to_string();
+                String::new()
+            }
+            _ => String::new(),
+        }
+    });
+    let mut text = converted.into_owned();
+    if vtt {
+        text = text
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&nbsp;", "\u{a0}")
+            .replace("&lrm;", "\u{200e}")
+            .replace("&rlm;", "\u{200f}")
+            .replace("&amp;", "&");
+    }
+    (text.replace("\r\n", "\n").replace('\n', "\\N"), actor)
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+struct Formatting {
+    italic: bool,
+    bold: bool,
+    underline: bool,
+    strike: bool,
+    // #RRGGBB
+    color: Option<[u8; 3]>,
+}
+
+fn style_flag(style: Option<&AssStyle>, key: &str) -> bool {
+    style
+        .and_then(|s| s.get(key))
+        .is_some_and(|v| v.trim() != "0" && !v.trim().is_empty())
+}
+
+fn style_formatting(style: Option<&AssStyle>) -> Formatting {
+    Formatting {
+        italic: style_flag(style, "italic"),
+        bold: style_flag(style, "bold"),
+        underline: style_flag(style, "underline"),
+        strike: style_flag(style, "strikeout"),
+        color: None,
+    }
+}
+
+// &HBBGGRR& 或 &HAABBGGRR& 转为 RGB
+fn ass_color(raw: &str) -> Option<[u8; 3]> {
+    let hex = raw.trim_start_matches("&H").trim_end_matches('&');
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some([value as u8, (value >> 8) as u8, (value >> 16) as u8])
+}
+
+fn open_tags(out: &mut String, f: &Formatting, srt: bool) {
+    if f.bold {
+        out.push_str("<b>");
+    }
+    if f.italic {
+        out.push_str("<i>");
+    }
+    if f.underline {
+        out.push_str("<u>");
+    }
+    if f.strike && srt {
+        out.push_str("<s>");
+    }
+    if let (Some([r, g, b]), true) = (f.color, srt) {
+        out.push_str(&format!("<font color=\"#{:02x}{:02x}{:02x}\">", r, g, b));
+    }
+}
+
+fn close_tags(out: &mut String, f: &Formatting, srt: bool) {
+    if f.color.is_some() && srt {
+        out.push_str("</font>");
+    }
+    if f.strike && srt {
+        out.push_str("</s>");
+    }
+    if f.underline {
+        out.push_str("</u>");
+    }
+    if f.italic {
+        out.push_str("</i>");
+    }
+    if f.bold {
+        out.push_str("</b>");
+    }
+}
+
+// 去掉覆盖标签后的纯文本，用于预览
+pub fn plain_text(text: &str) -> String {
+    ass_to_html(text, None, true, false)
+}
+
+// ASS 文本转为 SRT/WebVTT：粗斜体等转为 HTML 标签（SRT 还保留颜色），其余覆盖标签与绘图丢弃；
+// keep_styles 为 false 时只输出纯文本
+fn ass_to_html(text: &str, style: Option<&AssStyle>, srt: bool, keep_styles: bool) -> String {
+    let base = style_formatting(style);
+    let mut state = base;
+    let mut out = String::new();
+    let mut drawing = false;
+    if keep_styles {
+        open_tags(&mut out, &state, srt);
+    }
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '{' {
+            if let Some(end) = rest.find('}') {
+                let mut next = state;
+                let block = &rest[1..end];
+                for caps in ass_tag_re().captures_iter(block) {
+                    // \bord、\shad、\clip 等只是前缀相同的其他标签
+                    let whole = caps.get(0).unwrap();
+                    if block[whole.end()..]
+                        .chars()
+                        .next()
+                        .is_some_and(|c| c.is_ascii_alphabetic())
+                    {
+                        continue;
+                    }
+                    if let Some(tag) = caps.get(1) {
+                        let value = &caps[2];
+                        let on = match (tag.as_str(), value.parse::<u32>()) {
+                            (_, Err(_)) => None,
+                            ("b", Ok(n)) => Some(n == 1 || n >= 600),
+                            (_, Ok(n)) => Some(n != 0),
+                        };
+                        let default = |f: &Formatting| match tag.as_str() {
+                            "i" => f.italic,
+                            "b" => f.bold,
+                            "u" => f.underline,
+                            _ => f.strike,
+                        };
+                        let on = on.unwrap_or_else(|| default(&base));
+                        match tag.as_str() {
+                            "i" => next.italic = on,
+                            "b" => next.bold = on,
+                            "u" => next.underline = on,
+                            _ => next.strike = on,
+                        }
+                    } else if let Some(n) = caps.get(4) {
+                        drawing = n.as_str() != "0";
+                    } else if caps.get(5).is_some() {
+                        next = base;
+                    } else {
+                        next.color = caps.get(3).and_then(|c| ass_color(c.as_str()));
+                    }
+                }
+                if keep_styles && next != state {
+                    close_tags(&mut out, &state, srt);
+                    open_tags(&mut out, &next, srt);
+                }
+                state = next;
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        if c == '\\' {
+            match rest.get(1..2).unwrap_or("") {
+                "N" | "n" => {
+                    out.push('\n');
+                    rest = &rest[2..];
+                    continue;
+                }
+                "h" => {
+                    out.push('\u{a0}');
+                    rest = &rest[2..];
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        if !drawing {
+            match c {
+                '&' if !srt => out.push_str("&amp;"),
+                '<' if !srt => out.push_str("&lt;"),
+                '>' if !srt => out.push_str("&gt;"),
+                c => out.push(c),
+            }
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    if keep_styles {
+        close_tags(&mut out, &state, srt);
+    }
+    // 去掉只剩标签的空行，行中的标签并入相邻行，保证标签成对
+    let mut lines: Vec<String> = Vec::new();
+    let mut pending = String::new();
+    for line in out.split('\n').map(str::trim_end) {
+        if strip_html(line).trim().is_empty() {
+            pending.push_str(line.trim());
+            continue;
+        }
+        lines.push(format!("{}{}", std::mem::take(&mut pending), line));
+    }
+    if let Some(last) = lines.last_mut() {
+        last.push_str(&pending);
+    }
+    lines.join("\n")
+}
+
+fn strip_html(text: &str) -> std::borrow::Cow<'_, str> {
+    html_tag_re().replace_all(text, "")
+}
+
+fn parse_srt(text: &str) -> Subtitle {
+    let lines: Vec<&str> = text.lines().collect();
+    let timings: Vec<(usize, i64, i64)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, l)| parse_timing(l).map(|(start, end, _)| (i, start, end)))
+        .collect();
+    let mut cues = Vec::new();
+    for (n, &(i, start, end)) in timings.iter().enumerate() {
+        let mut stop = timings.get(n + 1).map_or(lines.len(), |t| t.0);
+        // 下一条的序号行不属于本条
+        if n + 1 < timings.len() && stop > i + 1 {
+            let prev = lines[stop - 1].trim();
+            if !prev.is_empty() && prev.chars().all(|c| c.is_ascii_digit()) {
+                stop -= 1;
+            }
+        }
+        let body = lines[i + 1..stop]
+            .iter()
+            .map(|l| l.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let (text, actor) = html_to_ass(body.trim_matches('\n'), false);
+        cues.push(Cue {
+            start,
+            end,
+            text,
+            actor,
+            ..Default::default()
+        });
+    }
+    Subtitle { cues, ass: None }
+}
+
+fn parse_vtt(text: &str) -> Subtitle {
+    let mut cues = Vec::new();
+    let normalized = text.replace("\r\n", "\n");
+    for block in normalized.split("\n\n") {
+        let lines: Vec<&str> = block.lines().filter(|l| !l.trim().is_empty()).collect();
+        let Some(timing_index) = lines.iter().take(2).position(|l| l.contains("-->")) else {
+            // 文件头及 NOTE、STYLE、REGION 块
+            continue;
+        };
+        let Some((start, end, settings)) = parse_timing(lines[timing_index]) else {
+            continue;
+        };
+        let (text, actor) = html_to_ass(&lines[timing_index + 1..].join("\n"), true);
+        cues.push(Cue {
+            start,
+            end,
+            text,
+            actor,
+            vtt_settings: settings,
+            ..Default::default()
+        });
+    }
+    Subtitle { cues, ass: None }
+}
+
+fn split_fields(line: &str) -> Vec<String> {
+    line.split(',').map(|f| f.trim().to_lowercase()).collect()
+}
+
+// SSA 的对齐方式为 1-3 底部、5-7 顶部、9-11 中间，ASS 为小键盘布局
+fn ssa_to_ass_alignment(value: &str) -> String {
+    match value.trim().parse::<u32>() {
+        Ok(n @ 5..=7) => (n + 2).to_string(),
+        Ok(n @ 9..=11) => (n - 5).to_string(),
+        _ => value.trim().to_string(),
+    }
+}
+
+fn ass_to_ssa_alignment(value: &str) -> String {
+    match value.trim().parse::<u32>() {
+        Ok(n @ 7..=9) => (n - 2).to_string(),
+        Ok(n @ 4..=6) => (n + 5).to_string(),
+        _ => value.trim().to_string(),
+    }
+}
+
+fn parse_style(values: &str, format: &[String], ssa: bool) -> AssStyle {
+    let mut style: AssStyle = format
+        .iter()
+        .zip(values.splitn(format.len(), ','))
+        .map(|(key, value)| (key.clone(), value.trim().to_string()))
+        .collect();
+    if ssa {
+        if let Some(color) = style.remove("tertiarycolour") {
+            style.insert("outlinecolour".to_string(), color);
+        }
+        if let Some(alignment) = style.get_mut("alignment") {
+            *alignment = ssa_to_ass_alignment(alignment);
+        }
+    }
+    style
+}
+
+fn parse_event(values: &str, format: &[String], comment: bool) -> Option<Cue> {
+    let fields: HashMap<&str, &str> = format
+        .iter()
+        .map(String::as_str)
+        .zip(values.splitn(format.len(), ','))
+        .collect();
+    let number = |key: &str| {
+        fields
+            .get(key)
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(0)
+    };
+    let text = |key: &str| fields.get(key).map_or("", |v| v.trim()).to_string();
+    Some(Cue {
+        start: parse_timestamp(fields.get("start")?)?,
+        end: parse_timestamp(fields.get("end")?)?,
+        text: fields.get("text").copied().unwrap_or_default().to_string(),
+        style: text("style").trim_start_matches('*').to_string(),
+        actor: text("name"),
+        layer: number("layer"),
+        margin_l: number("marginl"),
+        margin_r: number("marginr"),
+        margin_v: number("marginv"),
+        effect: text("effect"),
+        comment,
+        vtt_settings: String::new(),
+    })
+}
+
+fn parse_ass(text: &str) -> Subtitle {
+    let mut script = AssScript::default();
+    let mut cues = Vec::new();
+    let mut section = String::new();
+    let mut ssa_styles = false;
+    let mut style_format: Vec<String> = split_fields(&ASS_STYLE_FIELDS.join(","));
+    let mut event_format: Vec<String> = split_fields(ASS_EVENT_FORMAT);
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            section = trimmed.to_lowercase();
+            match section.as_str() {
+                "[script info]" | "[events]" => {}
+                "[v4+ styles]" | "[v4 styles]" => {
+                    ssa_styles = section == "[v4 styles]";
+                    if ssa_styles {
+                        style_format = split_fields(&SSA_STYLE_FIELDS.join(","));
+                    }
+                }
+                _ => script.extra_sections.push(format!("{}\n", trimmed)),
+            }
+            continue;
+        }
+        let key_value = trimmed
+            .split_once(':')
+            .map(|(k, v)| (k.trim().to_lowercase(), v.trim_start()));
+        match section.as_str() {
+            "[script info]" => {
+                let is_type = key_value.as_ref().is_some_and(|(k, _)| k == "scripttype");
+                if !trimmed.is_empty() && !is_type {
+                    script.script_info.push(trimmed.to_string());
+                }
+            }
+            "[v4+ styles]" | "[v4 styles]" => match key_value {
+                Some((k, v)) if k == "format" => style_format = split_fields(v),
+                Some((k, v)) if k == "style" => {
+                    script
+                        .styles
+                        .push(parse_style(v, &style_format, ssa_styles))
+                }
+                _ => {}
+            },
+            "[events]" => match key_value {
+                Some((k, v)) if k == "format" => event_format = split_fields(v),
+                Some((k, v)) if k == "dialogue" || k == "comment" => {
+                    cues.extend(parse_event(v, &event_format, k == "comment"))
+                }
+                _ => {}
+            },
+            _ => {
+                if let Some(extra) = script.extra_sections.last_mut() {
+                    if !trimmed.is_empty() {
+                        extra.push_str(line.trim_end());
+                        extra.push('\n');
+                    }
+                }
+            }
+        }
+    }
+    Subtitle {
+        cues,
+        ass: Some(script),
+    }
+}
+
+// 拆出行尾换行符，改写时保持原来的换行风格
+pub(crate) fn split_eol(line: &str) -> (&str, &str) {
+    let body = line.trim_end_matches(['\r', '\n']);
+    (body, &line[body.len()..])
+}
+
+// 替换字段的值，保留字段前后原有的空白
+pub(crate) fn replace_field(field: &mut String, value: &str) {
+    let trimmed = field.trim();
+    let start = field.len() - field.trim_start().len();
+    *field = format!(
+        "{}{}{}",
+        &field[..start],
+        value,
+        &field[start + trimmed.len()..]
+    );
+}
+
+// 逐行改写 [Events] 中的 Dialogue/Comment 行，其余内容原样保留。
+// edit 收到小写的 Format 字段名和按 Format 拆分的原始字段（含空白），返回 true 时写回修改后的字段
+pub(crate) fn rewrite_ass_events(
+    text: &str,
+    mut edit: impl FnMut(&[String], &mut Vec<String>) -> bool,
+) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_events = false;
+    let mut format = split_fields(ASS_EVENT_FORMAT);
+    for line in text.split_inclusive('\n') {
+        let (body, eol) = split_eol(line);
+        // 未经解码直接读入的文本第一行可能带有 BOM
+        let trimmed = body.trim().trim_start_matches('\u{feff}');
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            in_events = trimmed.eq_ignore_ascii_case("[events]");
+        }
+        let mut rewritten = None;
+        if let Some((key, values)) = body.split_once(':').filter(|_| in_events) {
+            match key.trim().to_lowercase().as_str() {
+                "format" => format = split_fields(values),
+                "dialogue" | "comment" => {
+                    let mut fields: Vec<String> = values
+                        .splitn(format.len(), ',')
+                        .map(str::to_string)
+                        .collect();
+                    if edit(&format, &mut fields) {
+                        rewritten = Some(format!("{}:{}", key, fields.join(",")));
+                    }
+                }
+                _ => {}
+            }
+        }
+        out.push_str(rewritten.as_deref().unwrap_or(body));
+        out.push_str(eol);
+    }
+    out
+}
+
+pub fn parse_subtitle(text: &str, format: SubtitleFormat) -> Subtitle {
+    let text = text.trim_start_matches('\u{feff}');
+    match format {
+        SubtitleFormat::Srt => parse_srt(text),
+        SubtitleFormat::Vtt => parse_vtt(text),
+        SubtitleFormat::Ass | SubtitleFormat::Ssa => parse_ass(text),
+    }
+}
+
+fn find_style<'a>(subtitle: &'a Subtitle, name: &str) -> Option<&'a AssStyle> {
+    let name = if name.is_empty() { "Default" } else { name };
+    subtitle
+        .ass
+        .as_ref()?
+        .styles
+        .iter()
+        .find(|s| s.get("name").is_some_and(|n| n.eq_ignore_ascii_case(name)))
+}
+
+// 输出 SRT/WebVTT 的字幕：按时间排序，去掉注释和空行，开始结束时间相同的（如双语字幕的两行）合并为一条
+fn flat_cues(subtitle: &Subtitle, srt: bool, keep_styles: bool) -> Vec<(i64, i64, String, String)> {
+    let mut cues: Vec<&Cue> = subtitle.cues.iter().filter(|c| !c.comment).collect();
+    cues.sort_by_key(|c| c.start);
+    let mut out: Vec<(i64, i64, String, String)> = Vec::new();
+    for cue in cues {
+        let text = ass_to_html(
+            &cue.text,
+            find_style(subtitle, &cue.style),
+            srt,
+            keep_styles,
+        );
+        if text.is_empty() {
+            continue;
+        }
+        match out.last_mut() {
+            Some(last) if last.0 == cue.start && last.1 == cue.end => {
+                last.2.push('\n');
+                last.2.push_str(&text);
+            }
+            _ => out.push((cue.start, cue.end, text, cue.vtt_settings.clone())),
+        }
+    }
+    out
+}
+
+fn write_srt(subtitle: &Subtitle, keep_styles: bool) -> String {
+    let mut out = String::new();
+    for (i, (start, end, text, _)) in flat_cues(subtitle, true, keep_styles).iter().enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_timestamp(*start, SubtitleFormat::Srt),
+            format_timestamp(*end, SubtitleFormat::Srt),
+            text
+        ));
+    }
+    out
+}
+
+fn write_vtt(subtitle: &Subtitle, keep_styles: bool) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for (start, end, text, settings) in flat_cues(subtitle, false, keep_styles) {
+        let settings = if settings.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", settings)
+        };
+        out.push_str(&format!(
+            "{} --> {}{}\n{}\n\n",
+            format_timestamp(start, SubtitleFormat::Vtt),
+            format_timestamp(end, SubtitleFormat::Vtt),
+            settings,
+            text
+        ));
+    }
+    out
+}
+
+fn default_style_value(field: &str) -> &'static str {
+    match field {
+        "scalex" | "scaley" => "100",
+        "encoding" => "1",
+        _ => "0",
+    }
+}
+
+fn style_line(style: &AssStyle, ssa: bool) -> String {
+    let fields: &[&str] = if ssa {
+        &SSA_STYLE_FIELDS
+    } else {
+        &ASS_STYLE_FIELDS
+    };
+    let values: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let key = match field.to_lowercase().as_str() {
+                "tertiarycolour" => "outlinecolour".to_string(),
+                key => key.to_string(),
+            };
+            let value = style
+                .get(&key)
+                .cloned()
+                .unwrap_or_else(|| default_style_value(&key).to_string());
+            if ssa && key == "alignment" {
+                ass_to_ssa_alignment(&value)
+            } else {
+                value
+            }
+        })
+        .collect();
+    format!("Style: {}", values.join(","))
+}
+
+fn write_ass(subtitle: &Subtitle, ssa: bool) -> String {
+    let default_script;
+    let script = match &subtitle.ass {
+        Some(script) => script,
+        None => {
+            let format = split_fields(&ASS_STYLE_FIELDS.join(","));
+            default_script = AssScript {
+                script_info: DEFAULT_SCRIPT_INFO.iter().map(|l| l.to_string()).collect(),
+                styles: vec![parse_style(DEFAULT_STYLE, &format, false)],
+                extra_sections: Vec::new(),
+            };
+            &default_script
+        }
+    };
+    let mut out = String::from("[Script Info]\n");
+    out.push_str(if ssa {
+        "ScriptType: v4.00\n"
+    } else {
+        "ScriptType: v4.00+\n"
+    });
+    for line in &script.script_info {
+        out.push_str(line);
+        out.push('\n');
+    }
+
+    let (section, fields): (&str, &[&str]) = if ssa {
+        ("[V4 Styles]", &SSA_STYLE_FIELDS)
+    } else {
+        ("[V4+ Styles]", &ASS_STYLE_FIELDS)
+    };
+    out.push_str(&format!("\n{}\nFormat: {}\n", section, fields.join(", ")));
+    for style in &script.styles {
+        out.push_str(&style_line(style, ssa));
+        out.push('\n');
+    }
+
+    for extra in &script.extra_sections {
+        out.push('\n');
+        out.push_str(extra);
+    }
+
+    out.push_str(&format!(
+        "\n[Events]\nFormat: {}\n",
+        if ssa {
+            SSA_EVENT_FORMAT
+        } else {
+            ASS_EVENT_FORMAT
+        }
+    ));
+    for cue in &subtitle.cues {
+        let kind = if cue.comment { "Comment" } else { "Dialogue" };
+        let first = if ssa {
+            "Marked=0".to_string()
+        } else {
+            cue.layer.to_string()
+        };
+        let style = if cue.style.is_empty() {
+            "Default"
+        } else {
+            &cue.style
+        };
+        out.push_str(&format!(
+            "{}: {},{},{},{},{},{},{},{},{},{}\n",
+            kind,
+            first,
+            format_timestamp(cue.start, SubtitleFormat::Ass),
+            format_timestamp(cue.end, SubtitleFormat::Ass),
+            style,
+            cue.actor,
+            cue.margin_l,
+            cue.margin_r,
+            cue.margin_v,
+            cue.effect,
+            cue.text
+        ));
+    }
+    out
+}
+
+// keep_styles 只影响 SRT/WebVTT：为 true 时保留粗体、斜体、下划线（SRT 还保留颜色），否则输出纯文本
+pub fn write_subtitle(subtitle: &Subtitle, format: SubtitleFormat, keep_styles: bool) -> String {
+    match format {
+        SubtitleFormat::Srt => write_srt(subtitle, keep_styles),
+        SubtitleFormat::Vtt => write_vtt(subtitle, keep_styles),
+        SubtitleFormat::Ass => write_ass(subtitle, false),
+        SubtitleFormat::Ssa => write_ass(subtitle, true),
+    }
+}
+
+// 读取字幕文件，自动检测编码
+pub fn read_subtitle(path: &str) -> Result<(Subtitle, SubtitleFormat), String> {
+    let format = SubtitleFormat::from_path(path).ok_or(format!("不支持的字幕格式: {}", path))?;
+    let bytes = fs::read(path).map_err(|e| format!("读取字幕失败: {}", e))?;
+    let (text, detected) = decode_subtitle(&bytes);
+    // 按猜测的编码解码会把乱码写进转换出的字幕
+    if !detected.is_reliable() {
+        return Err("无法识别字幕编码，未转换".to_string());
+    }
+    Ok((parse_subtitle(&text, format), format))
+}
+
+// 在原字幕旁生成目标格式的文件（UTF-8），原文件保留
+pub fn convert_subtitle_file(
+    path: &str,
+    target: SubtitleFormat,
+    keep_styles: bool,
+    overwrite: bool,
+) -> SubtitleConversion {
+    let mut report = SubtitleConversion {
+        source: path.to_string(),
+        target: None,
+        cues: 0,
+        error: None,
+    };
+    let result = (|| {
+        let (subtitle, format) = read_subtitle(path)?;
+        if format == target {
+            return Err("已是目标格式".to_string());
+        }
+        if subtitle.cues.is_empty() {
+            return Err("没有解析到字幕内容".to_string());
+        }
+        let target_path = Path::new(path).with_extension(target.extension());
+        if target_path.exists() && !overwrite {
+            return Err(format!("目标文件已存在: {}", target_path.display()));
+        }
+        fs::write(&target_path, write_subtitle(&subtitle, target, keep_styles))
+            .map_err(|e| format!("写入字幕失败: {}", e))?;
+        Ok((
+            target_path.to_string_lossy().to_string(),
+            subtitle.cues.len(),
+        ))
+    })();
+    match result {
+        Ok((target, cues)) => {
+            report.target = Some(target);
+            report.cues = cues;
+        }
+        Err(e) => report.error = Some(e),
+    }
+    report
+}
+
+// 转换字幕格式，keep_styles 默认为 true，overwrite 默认为 false
+#[tauri::command]
+pub async fn convert_subtitles(
+    paths: Vec<String>,
+    target_format: SubtitleFormat,
+    keep_styles: Option<bool>,
+    overwrite: Option<bool>,
+) -> Result<Vec<SubtitleConversion>, String> {
+    Ok(paths
+        .iter()
+        .map(|path| {
+            convert_subtitle_file(
+                path,
+                target_format,
+                keep_styles.unwrap_or(true),
+                overwrite.unwrap_or(false),
+            )
+        })
+        .collect())
+}
@@ -0,0 +1,222 @@
+use std::{fs, io::Write, path::Path};
+
+use encoding_rs::{Encoding, BIG5, GB18030, SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8};
+
+use crate::{
+    types::{EncodingConversion, SubtitleEncodingTarget},
+    utils::is_subtitle_file,
+};
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+// 简繁体中文里最常用的字，用于判断按哪种编码解码出的文字更像正常文本
+const COMMON_CHARS: &str = "的一是不了在人有我他这這个個们們中来來上大为為和国國地到以说說时時要就出会會可也你对對生能而子那得于於着著下自之年过過发發后後作里裡用道行所然家种種事成方多经經么麼去法学學如都同现現当當没沒动動面起看定天分还還进進好小部其些主样樣理心她本前开開但因只从從想实實吗嗎啊吧呢什她们";
+
+// 正常的中日文字幕中常用字和假名的比例远高于此；误判的编码（如 EUC-KR 按 Big5 解码）
+// 偶尔也会碰巧解出几个常用字，因此不能只看是否大于 0
+const MIN_CONFIDENCE: f32 = 0.2;
+
+// 检测到的编码与 BOM 长度；confidence 为常用字比例（BOM 或合法 UTF-8 时为 1），
+// lossy 表示没有任何候选编码能完整解码，解码结果含有替换字符
+pub struct DetectedEncoding {
+    pub encoding: &'static Encoding,
+    pub bom_len: usize,
+    pub confidence: f32,
+    pub lossy: bool,
+}
+
+impl DetectedEncoding {
+    pub fn name(&self) -> String {
+        match (self.encoding.name(), self.bom_len) {
+            ("UTF-8", 0) => "UTF-8".to_string(),
+            ("UTF-8", _) => "UTF-8 BOM".to_string(),
+            (name, _) => name.to_string(),
+        }
+    }
+
+    // 只是猜测的编码不能用来改写原文件，否则会把内容变成乱码
+    pub fn is_reliable(&self) -> bool {
+        !self.lossy && self.confidence >= MIN_CONFIDENCE
+    }
+}
+
+// 按解码结果中常用汉字和假名的比例打分，解码出错的编码直接排除
+fn decode_score(bytes: &[u8], encoding: &'static Encoding) -> Option<f32> {
+    let (text, had_errors) = encoding.decode_without_bom_handling(bytes);
+    if had_errors {
+        return None;
+    }
+    let mut wide = 0usize;
+    let mut common = 0usize;
+    for c in text.chars().filter(|c| !c.is_ascii()) {
+        wide += 1;
+        if COMMON_CHARS.contains(c) || ('\u{3041}'..='\u{30ff}').contains(&c) {
+            common += 1;
+        }
+    }
+    Some(if wide == 0 {
+        0.0
+    } else {
+        common as f32 / wide as f32
+    })
+}
+
+// 先看 BOM，再检查是否为合法 UTF-8，最后在 GB18030、Big5、Shift_JIS 中选择得分最高的
+pub fn detect_encoding(bytes: &[u8]) -> DetectedEncoding {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        return DetectedEncoding {
+            encoding,
+            bom_len,
+            confidence: 1.0,
+            lossy: false,
+        };
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return DetectedEncoding {
+            encoding: UTF_8,
+            bom_len: 0,
+            confidence: 1.0,
+            lossy: false,
+        };
+    }
+    let best = [GB18030, BIG5, SHIFT_JIS]
+        .into_iter()
+        .filter_map(|e| decode_score(bytes, e).map(|score| (e, score)))
+        .fold(
+            None,
+            |best: Option<(&'static Encoding, f32)>, (e, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((e, score)),
+            },
+        );
+    match best {
+        Some((encoding, confidence)) => DetectedEncoding {
+            encoding,
+            bom_len: 0,
+            confidence,
+            lossy: false,
+        },
+        None => DetectedEncoding {
+            encoding: GB18030,
+            bom_len: 0,
+            confidence: 0.0,
+            lossy: true,
+        },
+    }
+}
+
+// 解码字幕文本，去掉 BOM；无法确定编码时按 GB18030 解码，非法字节替换为 U+FFFD
+pub fn decode_subtitle(bytes: &[u8]) -> (String, DetectedEncoding) {
+    let mut detected = detect_encoding(bytes);
+    let (text, had_errors) = detected
+        .encoding
+        .decode_without_bom_handling(&bytes[detected.bom_len..]);
+    detected.lossy |= had_errors;
+    (text.into_owned(), detected)
+}
+
+fn is_target(detected: &DetectedEncoding, target: SubtitleEncodingTarget) -> bool {
+    detected.encoding == UTF_8
+        && (detected.bom_len > 0) == (target == SubtitleEncodingTarget::Utf8Bom)
+}
+
+// 转换为目标编码，已是目标编码时返回 None
+pub fn convert_bytes(bytes: &[u8], target: SubtitleEncodingTarget) -> Option<Vec<u8>> {
+    let (text, detected) = decode_subtitle(bytes);
+    if is_target(&detected, target) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(text.len() + UTF8_BOM.len());
+    if target == SubtitleEncodingTarget::Utf8Bom {
+        out.extend_from_slice(UTF8_BOM);
+    }
+    out.extend_from_slice(text.as_bytes());
+    Some(out)
+}
+
+// 按检测到的编码重新编码，保留原有的 BOM
+pub fn encode_subtitle(text: &str, detected: &DetectedEncoding) -> Vec<u8> {
+    let bom: &[u8] = match (detected.bom_len, detected.encoding) {
+        (0, _) => b"",
+        (_, e) if e == UTF_16LE => b"\xFF\xFE",
+        (_, e) if e == UTF_16BE => b"\xFE\xFF",
+        _ => UTF8_BOM,
+    };
+    let mut out = bom.to_vec();
+    // encoding_rs 不支持编码为 UTF-16，需要手动处理
+    if detected.encoding == UTF_16LE {
+        out.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    } else if detected.encoding == UTF_16BE {
+        out.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+    } else {
+        out.extend_from_slice(&detected.encoding.encode(text).0);
+    }
+    out
+}
+
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let tmp = path.with_extension("encoding.tmp");
+    let mut f = fs::File::create(&tmp).map_err(|e| format!("写入字幕失败: {}", e))?;
+    f.write_all(bytes)
+        .map_err(|e| format!("写入字幕失败: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("写入字幕失败: {}", e))
+}
+
+// 转换单个字幕文件，失败时记录在结果中而不是中断整批
+pub fn convert_file(path: &str, target: SubtitleEncodingTarget) -> EncodingConversion {
+    let mut report = EncodingConversion {
+        path: path.to_string(),
+        detected: None,
+        converted: false,
+        error: None,
+    };
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            report.error = Some(format!("读取字幕失败: {}", e));
+            return report;
+        }
+    };
+    let detected = detect_encoding(&bytes);
+    report.detected = Some(detected.name());
+    // 没有 UTF-16 BOM 却含有 NUL 的多半是 VobSub 等二进制字幕
+    if detected.encoding != UTF_16LE && detected.encoding != UTF_16BE && bytes.contains(&0) {
+        report.detected = None;
+        report.error = Some("不是文本字幕".to_string());
+        return report;
+    }
+    // 猜测的编码可能把 cp1252、EUC-KR 等文件转成乱码，撤销重命名也无法恢复，因此不改写
+    if !detected.is_reliable() {
+        report.detected = None;
+        report.error = Some("无法识别字幕编码，未转换".to_string());
+        return report;
+    }
+    if let Some(converted) = convert_bytes(&bytes, target) {
+        match write_atomic(Path::new(path), &converted) {
+            Ok(()) => report.converted = true,
+            Err(e) => report.error = Some(e),
+        }
+    }
+    report
+}
+
+// 批量转换，只处理字幕文件；返回每个文件的检测结果与是否改写
+pub fn convert_files(paths: &[String], target: SubtitleEncodingTarget) -> Vec<EncodingConversion> {
+    paths
+        .iter()
+        .filter(|p| is_subtitle_file(p))
+        .map(|p| convert_file(p, target))
+        .collect()
+}
+
+// 检测字幕编码并转为 UTF-8（target 为空时不带 BOM）
+#[tauri::command]
+pub async fn convert_subtitle_encoding(
+    paths: Vec<String>,
+    target: Option<SubtitleEncodingTarget>,
+) -> Result<Vec<EncodingConversion>, String> {
+    Ok(convert_files(
+        &paths,
+        target.unwrap_or(SubtitleEncodingTarget::Utf8),
+    ))
+}
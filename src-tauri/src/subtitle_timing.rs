@@ -0,0 +1,244 @@
+use std::{fs, path::Path, sync::OnceLock};
+
+use regex::Regex;
+
+use crate::{
+    rename::resolve_tracks,
+    subtitle::{
+        format_timestamp, parse_subtitle, parse_timestamp, parse_timing, plain_text, replace_field,
+        rewrite_ass_events, split_eol,
+    },
+    subtitle_encoding::{decode_subtitle, encode_subtitle, write_atomic},
+    types::{CueTimingChange, RenameRequest, RetimeOptions, RetimeReport, SubtitleFormat},
+};
+
+// 预览显示的字幕条数
+pub const PREVIEW_CUES: usize = 5;
+
+// 检查帧率，返回换算比例；字幕按 from_fps 的片源制作，用在 to_fps 的视频上
+pub fn retime_ratio(options: &RetimeOptions) -> Result<f64, String> {
+    match (options.from_fps, options.to_fps) {
+        (Some(from), Some(to)) => {
+            if !(from.is_finite() && to.is_finite() && from > 0.0 && to > 0.0) {
+                return Err(format!("帧率无效: {} → {}", from, to));
+            }
+            Ok(from / to)
+        }
+        _ => Ok(1.0),
+    }
+}
+
+// 换算单个时间点，结果可能为负数
+pub fn retime_ms(ms: i64, ratio: f64, offset_ms: i64) -> i64 {
+    (ms as f64 * ratio).round() as i64 + offset_ms
+}
+
+// 调整一条字幕的开始和结束时间，提前到 0 之前的部分截断
+struct Retimer {
+    ratio: f64,
+    offset_ms: i64,
+    cues: usize,
+    clamped: usize,
+}
+
+impl Retimer {
+    fn new(options: &RetimeOptions) -> Result<Self, String> {
+        Ok(Retimer {
+            ratio: retime_ratio(options)?,
+            offset_ms: options.offset_ms,
+            cues: 0,
+            clamped: 0,
+        })
+    }
+
+    fn point(&self, ms: i64) -> i64 {
+        retime_ms(ms, self.ratio, self.offset_ms).max(0)
+    }
+
+    fn cue(&mut self, start: i64, end: i64) -> (i64, i64) {
+        self.cues += 1;
+        if retime_ms(start, self.ratio, self.offset_ms) < 0 {
+            self.clamped += 1;
+        }
+        (self.point(start), self.point(end))
+    }
+}
+
+fn vtt_inline_time_re() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    CELL.get_or_init(|| Regex::new(r"<((?:\d+:)?\d{2}:\d{2}\.\d{3})>").unwrap())
+}
+
+fn retime_cue_lines(text: &str, format: SubtitleFormat, retimer: &mut Retimer) -> String {
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let (body, eol) = split_eol(line);
+        match parse_timing(body) {
+            Some((start, end, settings)) => {
+                let (start, end) = retimer.cue(start, end);
+                out.push_str(&format_timestamp(start, format));
+                out.push_str(" --> ");
+                out.push_str(&format_timestamp(end, format));
+                if !settings.is_empty() {
+                    out.push(' ');
+                    out.push_str(&settings);
+                }
+            }
+            // WebVTT 卡拉 OK 的行内时间戳同样调整
+            None if format == SubtitleFormat::Vtt => {
+                let replaced = vtt_inline_time_re().replace_all(body, |caps: &regex::Captures| {
+                    match parse_timestamp(&caps[1]) {
+                        Some(ms) => format!(
+                            "<{}>",
+                            format_timestamp(retimer.point(ms), SubtitleFormat::Vtt)
+                        ),
+                        None => caps[0].to_string(),
+                    }
+                });
+                out.push_str(&replaced);
+            }
+            None => out.push_str(body),
+        }
+        out.push_str(eol);
+    }
+    out
+}
+
+// 只改写 [Events] 中 Dialogue/Comment 行的 Start、End 字段，其余内容原样保留；
+// \t、\move 等标签中的时间相对于行开始，不受偏移影响
+fn retime_ass_lines(text: &str, retimer: &mut Retimer) -> String {
+    rewrite_ass_events(text, |format, fields| {
+        let index = |name: &str| format.iter().position(|f| f == name);
+        let (Some(start_index), Some(end_index)) = (index("start"), index("end")) else {
+            return false;
+        };
+        let times = fields
+            .get(start_index)
+            .and_then(|f| parse_timestamp(f))
+            .zip(fields.get(end_index).and_then(|f| parse_timestamp(f)));
+        let Some((start, end)) = times else {
+            return false;
+        };
+        let (start, end) = retimer.cue(start, end);
+        replace_field(
+            &mut fields[start_index],
+            &format_timestamp(start, SubtitleFormat::Ass),
+        );
+        replace_field(
+            &mut fields[end_index],
+            &format_timestamp(end, SubtitleFormat::Ass),
+        );
+        true
+    })
+}
+
+// 调整字幕文本中的所有时间，返回改写后的文本、字幕条数和被截断的条数
+pub fn retime_text(
+    text: &str,
+    format: SubtitleFormat,
+    options: &RetimeOptions,
+) -> Result<(String, usize, usize), String> {
+    let mut retimer = Retimer::new(options)?;
+    let out = match format {
+        SubtitleFormat::Srt | SubtitleFormat::Vtt => retime_cue_lines(text, format, &mut retimer),
+        SubtitleFormat::Ass | SubtitleFormat::Ssa => retime_ass_lines(text, &mut retimer),
+    };
+    Ok((out, retimer.cues, retimer.clamped))
+}
+
+// 前几条字幕调整前后的时间，注释行不显示
+pub fn preview_changes(
+    text: &str,
+    format: SubtitleFormat,
+    options: &RetimeOptions,
+) -> Result<Vec<CueTimingChange>, String> {
+    let retimer = Retimer::new(options)?;
+    Ok(parse_subtitle(text, format)
+        .cues
+        .into_iter()
+        .filter(|cue| !cue.comment)
+        .take(PREVIEW_CUES)
+        .map(|cue| CueTimingChange {
+            text: plain_text(&cue.text),
+            start: cue.start,
+            end: cue.end,
+            new_start: retimer.point(cue.start),
+            new_end: retimer.point(cue.end),
+        })
+        .collect())
+}
+
+// 调整单个字幕文件，按原编码写回；dry_run 时只返回预览
+pub fn retime_file(path: &str, options: &RetimeOptions, dry_run: bool) -> RetimeReport {
+    let mut report = RetimeReport {
+        path: path.to_string(),
+        ..Default::default()
+    };
+    let result = (|| {
+        let format =
+            SubtitleFormat::from_path(path).ok_or(format!("不支持的字幕格式: {}", path))?;
+        let bytes = fs::read(path).map_err(|e| format!("读取字幕失败: {}", e))?;
+        let (text, detected) = decode_subtitle(&bytes);
+        // 按原编码写回前必须能完整解码，否则时间以外的文字也会被改坏
+        if !detected.is_reliable() {
+            return Err("无法识别字幕编码，未调整".to_string());
+        }
+        let preview = preview_changes(&text, format, options)?;
+        let (retimed, cues, clamped) = retime_text(&text, format, options)?;
+        if cues == 0 {
+            return Err("没有解析到字幕内容".to_string());
+        }
+        let applied = !dry_run && retimed != text;
+        if applied {
+            write_atomic(Path::new(path), &encode_subtitle(&retimed, &detected))?;
+        }
+        Ok((preview, cues, clamped, applied))
+    })();
+    match result {
+        Ok((preview, cues, clamped, applied)) => {
+            report.preview = preview;
+            report.cues = cues;
+            report.clamped = clamped;
+            report.applied = applied;
+        }
+        Err(e) => report.error = Some(e),
+    }
+    report
+}
+
+// 调整字幕时间轴，dry_run 默认为 false
+#[tauri::command]
+pub async fn retime_subtitles(
+    paths: Vec<String>,
+    options: RetimeOptions,
+    dry_run: Option<bool>,
+) -> Result<Vec<RetimeReport>, String> {
+    retime_ratio(&options)?;
+    let dry_run = dry_run.unwrap_or(false);
+    Ok(paths
+        .iter()
+        .map(|path| retime_file(path, &options, dry_run))
+        .collect())
+}
+
+// 对已配对的视频和字幕批量调整时间轴，配对方式与重命名相同
+#[tauri::command]
+pub async fn retime_paired_subtitles(
+    request: RenameRequest,
+    options: RetimeOptions,
+    dry_run: Option<bool>,
+) -> Result<Vec<RetimeReport>, String> {
+    retime_ratio(&options)?;
+    let dry_run = dry_run.unwrap_or(false);
+    let tracks = resolve_tracks(&request)?;
+    Ok(tracks
+        .iter()
+        .map(|track| RetimeReport {
+            video: request
+                .video_files
+                .get(track.video_index)
+                .map(|video| video.name.clone()),
+            ..retime_file(&track.subtitle.path, &options, dry_run)
+        })
+        .collect())
+}
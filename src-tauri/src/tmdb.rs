@@ -0,0 +1,249 @@
+use async_trait::async_trait;
+use reqwest::RequestBuilder;
+use serde::Deserialize;
+
+use crate::{
+    http::{fetch_json, http_client},
+    metadata_provider::{
+        non_empty, parse_year, MetadataProvider, EPISODE_TYPE_MAIN, EPISODE_TYPE_SPECIAL,
+    },
+    types::{MetadataEpisode, MetadataSubject, MetadataSubjectDetail, MetadataTag},
+};
+
+pub(crate) const CACHE_NAMESPACE: &str = "tmdb";
+const IMAGE_BASE: &str = "https://image.tmdb.org/t/p/w500";
+// 条目标题与分集标题分别按中文和日文各请求一次
+const LANGUAGE_CN: &str = "zh-CN";
+const LANGUAGE_JP: &str = "ja-JP";
+
+#[derive(Deserialize)]
+struct SearchPage {
+    #[serde(default)]
+    results: Vec<TvResult>,
+}
+
+#[derive(Deserialize)]
+struct TvResult {
+    id: i64,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    original_name: String,
+    first_air_date: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NamedItem {
+    #[serde(default, alias = "title")]
+    name: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct NamedList {
+    results: Vec<NamedItem>,
+}
+
+#[derive(Deserialize)]
+struct SeasonSummary {
+    season_number: u32,
+}
+
+#[derive(Deserialize)]
+struct TvDetail {
+    id: i64,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    original_name: String,
+    first_air_date: Option<String>,
+    number_of_episodes: Option<i32>,
+    poster_path: Option<String>,
+    #[serde(default)]
+    genres: Vec<NamedItem>,
+    #[serde(default)]
+    keywords: NamedList,
+    #[serde(default)]
+    alternative_titles: NamedList,
+    #[serde(default)]
+    seasons: Vec<SeasonSummary>,
+}
+
+#[derive(Deserialize)]
+struct SeasonDetail {
+    #[serde(default)]
+    episodes: Vec<TvEpisode>,
+}
+
+#[derive(Deserialize)]
+struct TvEpisode {
+    id: i64,
+    episode_number: u32,
+    #[serde(default)]
+    name: String,
+    air_date: Option<String>,
+}
+
+// 原名与中文名相同时不再重复显示
+fn localized_name(name: String, original_name: &str) -> Option<String> {
+    non_empty(name).filter(|n| n != original_name)
+}
+
+// TMDB v3 API，剧集按季组织，第 0 季为特别篇；base_url 默认为 https://api.themoviedb.org/3
+pub struct TmdbProvider {
+    base_url: String,
+    api_key: String,
+}
+
+impl TmdbProvider {
+    pub fn new(base_url: &str, api_key: &str) -> Self {
+        TmdbProvider {
+            base_url: base_url.trim().trim_end_matches('/').to_string(),
+            api_key: api_key.trim().to_string(),
+        }
+    }
+
+    // v4 读取令牌（JWT）放在认证头中，v3 API Key 作为查询参数
+    fn get(&self, path: &str, language: &str) -> RequestBuilder {
+        let builder = http_client()
+            .get(format!("{}{}", self.base_url, path))
+            .query(&[("language", language)]);
+        if self.api_key.starts_with("eyJ") {
+            builder.bearer_auth(&self.api_key)
+        } else if self.api_key.is_empty() {
+            builder
+        } else {
+            builder.query(&[("api_key", &self.api_key)])
+        }
+    }
+
+    async fn tv_detail(&self, id: i64) -> Result<TvDetail, String> {
+        let builder = self
+            .get(&format!("/tv/{}", id), LANGUAGE_CN)
+            .query(&[("append_to_response", "alternative_titles,keywords")]);
+        fetch_json(builder, "TMDB").await
+    }
+
+    async fn season(&self, id: i64, season: u32, language: &str) -> Result<SeasonDetail, String> {
+        fetch_json(
+            self.get(&format!("/tv/{}/season/{}", id, season), language),
+            "TMDB",
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for TmdbProvider {
+    async fn search(&self, keyword: &str, limit: usize) -> Result<Vec<MetadataSubject>, String> {
+        let builder = self
+            .get("/search/tv", LANGUAGE_CN)
+            .query(&[("query", keyword)]);
+        let page: SearchPage = fetch_json(builder, "TMDB").await?;
+        Ok(page
+            .results
+            .into_iter()
+            .take(limit)
+            .map(|r| MetadataSubject {
+                id: r.id,
+                name_cn: localized_name(r.name, &r.original_name),
+                name: r.original_name,
+                subject_type: None,
+                date: r.first_air_date.and_then(non_empty),
+                platform: Some("TV".to_string()),
+            })
+            .collect())
+    }
+
+    async fn detail(&self, id: i64) -> Result<MetadataSubjectDetail, String> {
+        let tv = self.tv_detail(id).await?;
+        let name_cn = localized_name(tv.name, &tv.original_name);
+        let mut aliases: Vec<String> = Vec::new();
+        for title in tv.alternative_titles.results {
+            let title = title.name.trim().to_string();
+            if !title.is_empty()
+                && title != tv.original_name
+                && Some(&title) != name_cn.as_ref()
+                && !aliases.contains(&title)
+            {
+                aliases.push(title);
+            }
+        }
+        let air_date = tv.first_air_date.and_then(non_empty);
+        Ok(MetadataSubjectDetail {
+            id: tv.id,
+            name: tv.original_name,
+            name_cn,
+            cover_url: tv.poster_path.map(|p| format!("{}{}", IMAGE_BASE, p)),
+            episodes: tv.number_of_episodes,
+            year: parse_year(air_date.as_deref()),
+            aliases,
+            tags: tv
+                .genres
+                .into_iter()
+                .chain(tv.keywords.results)
+                .map(|t| MetadataTag {
+                    name: t.name,
+                    count: 0,
+                })
+                .collect(),
+            platform: Some("TV".to_string()),
+            air_date,
+            episode_list: Vec::new(),
+        })
+    }
+
+    // 逐季获取中文与日文分集标题；本篇的 sort 为跨季累计的绝对集数
+    async fn episodes(&self, id: i64) -> Result<Vec<MetadataEpisode>, String> {
+        let mut seasons: Vec<u32> = self
+            .tv_detail(id)
+            .await?
+            .seasons
+            .iter()
+            .map(|s| s.season_number)
+            .collect();
+        seasons.sort();
+
+        let mut episodes = Vec::new();
+        let mut absolute = 0;
+        for season in seasons {
+            let (cn, jp) = futures::try_join!(
+                self.season(id, season, LANGUAGE_CN),
+                self.season(id, season, LANGUAGE_JP)
+            )?;
+            for e in cn.episodes {
+                let name = jp
+                    .episodes
+                    .iter()
+                    .find(|j| j.id == e.id)
+                    .and_then(|j| non_empty(j.name.clone()));
+                let sort = if season == 0 {
+                    e.episode_number as f64
+                } else {
+                    absolute += 1;
+                    absolute as f64
+                };
+                episodes.push(MetadataEpisode {
+                    id: e.id,
+                    episode_type: if season == 0 {
+                        EPISODE_TYPE_SPECIAL
+                    } else {
+                        EPISODE_TYPE_MAIN
+                    },
+                    sort,
+                    ep: Some(e.episode_number as f64),
+                    season: Some(season),
+                    name,
+                    name_cn: non_empty(e.name),
+                    airdate: e.air_date.and_then(non_empty),
+                });
+            }
+        }
+        episodes.sort_by(|a, b| {
+            a.episode_type
+                .cmp(&b.episode_type)
+                .then(a.sort.total_cmp(&b.sort))
+        });
+        Ok(episodes)
+    }
+}
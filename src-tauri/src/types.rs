@@ -1,8 +1,21 @@
-#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct FileInfo {
     pub name: String,
     pub path: String,
     pub is_video: bool,
+    // zip/7z/rar 字幕包，需要先解压再参与配对
+    #[serde(default)]
+    pub is_archive: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct SubtitleTrack {
+    // 对应 RenameRequest.video_files 中的下标
+    pub video_index: usize,
+    pub subtitle: FileInfo,
+    // 该轨道的语言后缀，为空时使用检测到的语言标记或请求的 suffix
+    #[serde(default)]
+    pub suffix: Option<String>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
@@ -10,13 +23,272 @@ pub struct RenameRequest {
     pub video_files: Vec<FileInfo>,
     pub subtitle_files: Vec<FileInfo>,
     pub suffix: String,
+    // 一个视频对应多个字幕时使用，非空时忽略 subtitle_files
+    #[serde(default)]
+    pub tracks: Vec<SubtitleTrack>,
+    // 保留字幕文件名中已有的语言标记，如 video.CHT.srt 中的 CHT
+    #[serde(default)]
+    pub keep_language_tag: bool,
+    // 重命名后把字幕转为 UTF-8，为空时不转换
+    #[serde(default)]
+    pub subtitle_encoding: Option<SubtitleEncodingTarget>,
+    // 重命名后另存为指定格式的字幕，为空时不转换
+    #[serde(default)]
+    pub subtitle_format: Option<SubtitleFormat>,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    Srt,
+    Ass,
+    Ssa,
+    Vtt,
+}
+
+// 单个字幕的格式转换结果，target 为生成的文件路径
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct SubtitleConversion {
+    pub source: String,
+    pub target: Option<String>,
+    pub cues: usize,
+    pub error: Option<String>,
+}
+
+// 简繁转换方向，名称与 OpenCC 的配置一致
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ChineseConversion {
+    S2t,
+    T2s,
+}
+
+// 单个字幕的简繁转换结果，target 为生成的文件路径，changed 为改动的字数
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct ScriptConversion {
+    pub source: String,
+    pub target: Option<String>,
+    pub changed: usize,
+    pub error: Option<String>,
+}
+
+// 压缩包中的文件，name 为包内以 / 分隔的完整路径
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub size: u64,
+    pub is_subtitle: bool,
+}
+
+// 单个文件的解压结果，target 为解压到的路径
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct ArchiveExtraction {
+    pub entry: String,
+    pub target: Option<String>,
+    pub error: Option<String>,
+}
+
+// 时间轴调整：先按帧率换算，再加上偏移；帧率只填一个或不填时不换算
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default)]
+pub struct RetimeOptions {
+    #[serde(default)]
+    pub offset_ms: i64,
+    // 字幕原本对应的视频帧率
+    #[serde(default)]
+    pub from_fps: Option<f64>,
+    // 当前视频的帧率
+    #[serde(default)]
+    pub to_fps: Option<f64>,
+}
+
+// 单条字幕调整前后的时间，单位毫秒
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct CueTimingChange {
+    pub text: String,
+    pub start: i64,
+    pub end: i64,
+    pub new_start: i64,
+    pub new_end: i64,
+}
+
+// 单个字幕的时间轴调整结果；preview 为前几条字幕的变化，clamped 为提前到 0 之前被截断的条数
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct RetimeReport {
+    pub path: String,
+    pub video: Option<String>,
+    pub cues: usize,
+    pub clamped: usize,
+    pub preview: Vec<CueTimingChange>,
+    pub applied: bool,
+    pub error: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SubtitleEncodingTarget {
+    Utf8,
+    Utf8Bom,
+}
+
+// 单个字幕文件的编码转换结果，detected 为检测到的编码名称，如 GB18030、Big5、UTF-8 BOM
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct EncodingConversion {
+    pub path: String,
+    pub detected: Option<String>,
+    // 已是目标编码时为 false，文件内容不变
+    pub converted: bool,
+    pub error: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct RenameOperation {
+    pub from_path: String,
+    pub to_path: String,
+    // 执行时新建的目录，由浅到深；撤销或回滚时只清理这些目录
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub created_dirs: Vec<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RenameBatchStatus {
+    Applied,
+    Undone,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct RenameBatch {
+    pub id: u64,
+    // Unix 时间戳（秒）
+    pub created_at: u64,
+    pub description: String,
+    pub operations: Vec<RenameOperation>,
+    // 本批次额外生成的文件（如转换格式后的字幕），撤销时删除，重做时不会重新生成
+    #[serde(default)]
+    pub created_files: Vec<String>,
+    pub status: RenameBatchStatus,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default)]
 pub struct RenameResponse {
     pub success: bool,
     pub message: String,
     pub renamed_files: Vec<String>,
+    // 批次中途失败时已恢复原名的操作
+    #[serde(default)]
+    pub rolled_back: Vec<RenameOperation>,
+    // 回滚时未能恢复的文件
+    #[serde(default)]
+    pub rollback_errors: Vec<String>,
+    // 写入重命名历史后的批次 ID，可用于撤销
+    #[serde(default)]
+    pub batch_id: Option<u64>,
+    // 重命名后转换字幕编码的结果
+    #[serde(default)]
+    pub encoding_report: Vec<EncodingConversion>,
+    // 重命名后转换字幕格式的结果
+    #[serde(default)]
+    pub subtitle_conversions: Vec<SubtitleConversion>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RenameProblem {
+    MissingSource,
+    TargetExists,
+    DuplicateTarget,
+    InvalidFilename,
+    // 目标目录不存在且未开启自动创建
+    MissingTargetDir,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct RenamePlanItem {
+    pub source_name: String,
+    pub source_path: String,
+    pub target_name: String,
+    pub target_path: String,
+    #[serde(default)]
+    pub problems: Vec<RenameProblem>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct RenamePlan {
+    pub items: Vec<RenamePlanItem>,
+    pub valid: bool,
+    // 执行时自动创建缺失的目标目录
+    #[serde(default)]
+    pub create_dirs: bool,
+    // 执行后转换字幕文件的编码；撤销重命名时不会还原编码
+    #[serde(default)]
+    pub subtitle_encoding: Option<SubtitleEncodingTarget>,
+    // 执行后在字幕旁另存为该格式，原文件保留
+    #[serde(default)]
+    pub subtitle_format: Option<SubtitleFormat>,
+}
+
+// 媒体服务器命名预设
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaServerPreset {
+    Jellyfin,
+    Plex,
+    Kodi,
+    Emby,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct VideoRenameItem {
+    pub source_path: String,
+    pub info: NamingInfo,
+    // 随视频一起改名/移动的字幕等附属文件
+    #[serde(default)]
+    pub companions: Vec<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct VideoRenameRequest {
+    pub items: Vec<VideoRenameItem>,
+    // 优先级：preset > template > 设置中的默认模板
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub preset: Option<MediaServerPreset>,
+    // 媒体库根目录，未指定时相对于视频所在目录
+    #[serde(default)]
+    pub target_root: Option<String>,
+    #[serde(default)]
+    pub create_dirs: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct EpisodePair {
+    pub episode: String,
+    pub video: FileInfo,
+    // 同一集的多个字幕轨道（如 chs / cht / jp）
+    pub subtitles: Vec<FileInfo>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct AmbiguousEpisode {
+    pub episode: String,
+    pub videos: Vec<FileInfo>,
+    pub subtitles: Vec<FileInfo>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct UnmatchedFile {
+    // 无法识别集数时为空
+    pub episode: Option<String>,
+    pub file: FileInfo,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+pub struct EpisodeMatchResult {
+    pub pairs: Vec<EpisodePair>,
+    pub unmatched_videos: Vec<UnmatchedFile>,
+    pub unmatched_subtitles: Vec<UnmatchedFile>,
+    pub ambiguous: Vec<AmbiguousEpisode>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -24,9 +296,15 @@ pub struct LLMRequest {
     pub filename: String,
     pub model_url: String,
     pub model_name: String,
+    // 跳过规则解析，直接调用 LLM
+    #[serde(default)]
+    pub force_llm: bool,
+    // 不读取识别缓存，结果仍会写回缓存
+    #[serde(default)]
+    pub bypass_cache: bool,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default, Debug)]
 pub struct AnimeInfo {
     pub title: String,
     #[serde(default)]
@@ -35,13 +313,54 @@ pub struct AnimeInfo {
     pub codec: Option<String>,
     #[serde(default)]
     pub group: Option<String>,
+    #[serde(default)]
+    pub year: Option<i32>,
+    // 0 表示特别篇（SP/OVA）
+    #[serde(default)]
+    pub season: Option<u32>,
+    #[serde(default)]
+    pub resolution: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub crc32: Option<String>,
+    #[serde(default)]
+    pub version: Option<u32>,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+// 命名模板可用的变量
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct NamingInfo {
+    pub title: String,
+    pub year: Option<i32>,
+    pub season: Option<u32>,
+    pub episode: Option<String>,
+    pub ep_title: Option<String>,
+    pub group: Option<String>,
+    pub codec: Option<String>,
+    pub resolution: Option<String>,
+    pub source: Option<String>,
+    pub crc32: Option<String>,
+    pub version: Option<u32>,
+    // 不带点的扩展名，如 mkv
+    pub ext: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ParsedFilename {
+    pub info: AnimeInfo,
+    pub confidence: f32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct LLMResponse {
     pub success: bool,
     pub data: Option<AnimeInfo>,
     pub error: Option<String>,
+    // 识别来源：parser、llm 或 cache
+    #[serde(default)]
+    pub engine: Option<String>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -49,6 +368,8 @@ pub struct BatchLLMRequest {
     pub filenames: Vec<String>,
     pub model_url: String,
     pub model_name: String,
+    #[serde(default)]
+    pub bypass_cache: bool,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -59,30 +380,241 @@ pub struct BatchLLMResponse {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
-pub struct BangumiSubject {
+pub struct MetadataSubject {
     pub id: i64,
     pub name: String,
     pub name_cn: Option<String>,
     #[serde(rename = "type")]
     pub subject_type: Option<i32>,
     pub date: Option<String>,
+    // TV、OVA、剧场版、WEB 等
+    #[serde(default)]
+    pub platform: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct MetadataTag {
+    pub name: String,
+    pub count: u32,
+}
+
+// 章节类型：0 本篇，1 SP，2 OP，3 ED，4 预告/宣传/广告，5 MAD，6 其他
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct MetadataEpisode {
+    pub id: i64,
+    #[serde(rename = "type")]
+    pub episode_type: i32,
+    // 在条目内的排序，可能是 6.5 这类小数
+    pub sort: f64,
+    // 在所属季度内的集数，特别篇通常没有
+    pub ep: Option<f64>,
+    // 条目本身包含多季时（如 TMDB 剧集）的季号
+    #[serde(default)]
+    pub season: Option<u32>,
+    pub name: Option<String>,
+    pub name_cn: Option<String>,
+    pub airdate: Option<String>,
+}
+
+// 按识别出的集数查询分集标题；special 为 true 时在 SP 中查找
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct EpisodeTitleQuery {
+    pub episode: String,
+    #[serde(default)]
+    pub special: bool,
+    // 条目包含多季时只在该季中查找
+    #[serde(default)]
+    pub season: Option<u32>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct EpisodeTitle {
+    pub episode: String,
+    pub special: bool,
+    // 匹配到的章节排序，未找到时为空
+    pub sort: Option<f64>,
+    pub name: Option<String>,
+    pub name_cn: Option<String>,
+}
+
+// 系列中的一季，first_episode 为该季第一集的绝对集数；episodes 为 0 表示集数未知
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct SeasonBoundary {
+    pub subject_id: i64,
+    pub season: u32,
+    pub name: String,
+    pub name_cn: Option<String>,
+    pub first_episode: u32,
+    pub episodes: u32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct EpisodeMapping {
+    pub episode: String,
+    // 映射到的条目与季内集数，无法映射时为空
+    pub subject_id: Option<i64>,
+    pub season: Option<u32>,
+    pub seasonal_episode: Option<String>,
+    pub absolute: Option<u32>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
-pub struct BangumiSubjectDetail {
+pub struct MetadataSubjectDetail {
     pub id: i64,
     pub name: String,
     pub name_cn: Option<String>,
     pub cover_url: Option<String>,
     pub episodes: Option<i32>,
     pub year: Option<i32>,
+    // 信息框中的别名、英文名等
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<MetadataTag>,
+    #[serde(default)]
+    pub platform: Option<String>,
+    #[serde(default)]
+    pub air_date: Option<String>,
+    // 仅在请求时附带章节列表
+    #[serde(default)]
+    pub episode_list: Vec<MetadataEpisode>,
 }
 
+// 候选条目的匹配得分（0-1），year_score / episode_score 为空表示缺少信息未参与比较
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct ScoredCandidate {
+    pub detail: MetadataSubjectDetail,
+    pub score: f32,
+    pub title_score: f32,
+    // 与识别标题最接近的名称（原名、中文名或别名）
+    pub matched_title: String,
+    pub year_score: Option<f32>,
+    pub episode_score: Option<f32>,
+}
+
+// 按得分从高到低排列；selected 为可自动选择的条目，存在歧义时为空
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct CandidateRanking {
+    pub candidates: Vec<ScoredCandidate>,
+    pub selected: Option<i64>,
+}
+
+// 缺失的字段使用默认值，兼容旧版本的 settings.json
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(default)]
 pub struct Settings {
     pub episode_regex: String,
     pub model_url: String,
     pub model_name: String,
+    // 规则解析置信度达到该值时不再调用 LLM
+    pub parser_confidence_threshold: f32,
+    // 视频重命名使用的默认命名模板
+    pub naming_template: String,
+    // 选择预设时忽略 naming_template
+    pub naming_preset: Option<MediaServerPreset>,
+    pub media_library_root: Option<String>,
+    // LLM 识别缓存的有效期（天，0 表示不过期）与最大条目数
+    pub llm_cache_ttl_days: u64,
+    pub llm_cache_max_entries: usize,
+    // 批量识别时同时请求 LLM 的数量
+    pub llm_concurrency: usize,
+    pub llm_provider: LlmProviderKind,
+    // 为空时不发送认证头
+    pub llm_api_key: String,
+    // 请求时附带输出 JSON Schema（response_format / format / json_schema）
+    pub llm_structured_output: bool,
+    // LLM 与 Bangumi 等接口的请求超时（秒）
+    pub llm_timeout_secs: u64,
+    pub api_timeout_secs: u64,
+    // 连接失败、429、5xx 时的最大重试次数与首次重试等待（毫秒，之后逐次翻倍）
+    pub http_max_retries: u32,
+    pub http_retry_base_ms: u64,
+    // 搜索条目和分集标题使用的元数据来源
+    pub metadata_provider: MetadataProviderKind,
+    // 各来源的接口地址，可改为本地模拟服务或反向代理
+    pub bangumi_api_url: String,
+    pub anilist_api_url: String,
+    pub tmdb_api_url: String,
+    // TMDB v3 API Key 或 v4 读取令牌
+    pub tmdb_api_key: String,
+    pub anidb_api_url: String,
+    // AniDB HTTP API 需要注册的客户端名称与版本
+    pub anidb_client: String,
+    pub anidb_client_version: u32,
+    // 元数据缓存的有效期（天，0 表示不过期）；过期后仍会在请求失败时使用
+    pub metadata_cache_ttl_days: u64,
+    // 离线模式下只读取缓存，不请求元数据接口
+    pub offline_mode: bool,
+    // 选择或拖入文件夹时的扫描选项
+    pub scan_max_depth: u32,
+    pub scan_exclude: Vec<String>,
+    pub scan_follow_symlinks: bool,
+    pub scan_include_subtitles: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataProviderKind {
+    #[default]
+    Bangumi,
+    AniList,
+    Tmdb,
+    AniDb,
+}
+
+// LLM 接口格式，model_url 需填写对应的完整接口地址
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmProviderKind {
+    // /v1/chat/completions
+    #[default]
+    OpenAi,
+    // Ollama 原生 /api/chat
+    Ollama,
+    // llama.cpp server /completion
+    LlamaCpp,
+    // Messages 格式 /v1/messages
+    Messages,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct AnalyzeFilesRequest {
+    pub job_id: String,
+    pub files: Vec<FileInfo>,
+    pub model_url: String,
+    pub model_name: String,
+    // 同时进行的识别数量，未指定时使用设置中的值
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+    #[serde(default)]
+    pub force_llm: bool,
+    #[serde(default)]
+    pub bypass_cache: bool,
+}
+
+// 每完成一个文件发送一次的进度事件
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct AnalyzeProgress {
+    pub job_id: String,
+    pub done: usize,
+    pub total: usize,
+    pub file: FileInfo,
+    pub result: LLMResponse,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct AnalyzeFilesSummary {
+    pub job_id: String,
+    pub done: usize,
+    pub total: usize,
+    pub succeeded: usize,
+    pub canceled: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct LlmCacheEntry {
+    pub created_at: u64,
+    pub data: AnimeInfo,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -90,3 +622,15 @@ pub struct DirectoryPickResult {
     pub files: Vec<FileInfo>,
     pub canceled: bool,
 }
+
+// 扫描文件夹的选项，未传入时使用设置中的值
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ScanOptions {
+    // 向下进入子文件夹的层数，0 表示只扫描所选文件夹
+    pub max_depth: u32,
+    // gitignore 风格的排除规则：以 / 结尾只匹配文件夹，含 / 时相对所选文件夹匹配，! 开头表示重新包含
+    pub exclude: Vec<String>,
+    pub follow_symlinks: bool,
+    pub include_subtitles: bool,
+}
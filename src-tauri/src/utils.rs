@@ -3,11 +3,28 @@ use std::{
     path::{Path, PathBuf},
 };
 
-pub fn settings_path() -> Result<PathBuf, String> {
+pub fn app_config_dir() -> Result<PathBuf, String> {
     let base = dirs::config_dir().ok_or("获取配置目录失败")?;
     let app_dir = base.join("anime-renamer-tauri");
     fs::create_dir_all(&app_dir).map_err(|e| format!("创建配置目录失败: {}", e))?;
-    Ok(app_dir.join("settings.json"))
+    Ok(app_dir)
+}
+
+pub fn settings_path() -> Result<PathBuf, String> {
+    Ok(app_config_dir()?.join("settings.json"))
+}
+
+pub fn rename_journal_path() -> Result<PathBuf, String> {
+    Ok(app_config_dir()?.join("rename_history.json"))
+}
+
+pub fn llm_cache_path() -> Result<PathBuf, String> {
+    Ok(app_config_dir()?.join("llm_cache.json"))
+}
+
+// 元数据缓存按来源和类别分目录，每个条目一个 JSON 文件
+pub fn metadata_cache_dir() -> Result<PathBuf, String> {
+    Ok(app_config_dir()?.join("metadata_cache"))
 }
 
 // 获取文件扩展名
@@ -34,3 +51,58 @@ pub fn is_subtitle_file(filename: &str) -> bool {
     let subtitle_extensions = ["srt", "ass", "ssa", "sub", "idx", "vtt", "txt"];
     subtitle_extensions.contains(&ext.as_str())
 }
+
+// 判断是否为字幕组常用的压缩包
+pub fn is_archive_file(filename: &str) -> bool {
+    let ext = get_extension(filename);
+    ["zip", "7z", "rar"].contains(&ext.as_str())
+}
+
+// 检测字幕文件名中已有的语言标记，如 video.CHT.srt 返回 CHT（保留原大小写）
+pub fn detect_language_tag(filename: &str) -> Option<String> {
+    let stem = Path::new(filename).file_stem()?.to_str()?;
+    let (_, tag) = stem.rsplit_once('.')?;
+    let known = [
+        "chs", "cht", "sc", "tc", "gb", "big5", "zh", "zho", "chi", "zh-cn", "zh-tw", "zh-hk",
+        "zh-hans", "zh-hant", "jp", "jpn", "ja", "jap", "en", "eng", "ko", "kor", "chs&jpn",
+        "cht&jpn", "chs_jpn", "cht_jpn", "jpsc", "jptc", "sc_jp", "tc_jp",
+    ];
+    if known.contains(&tag.to_lowercase().as_str()) {
+        Some(tag.to_string())
+    } else {
+        None
+    }
+}
+
+// 判断路径是否包含目录（仅给出文件名时不操作磁盘）
+pub fn has_full_path(path: &str) -> bool {
+    path.contains('/') || path.contains('\\')
+}
+
+// 判断文件名在主流文件系统上是否合法
+pub fn is_valid_filename(name: &str) -> bool {
+    if name.is_empty() || name == "." || name == ".." || name.len() > 255 {
+        return false;
+    }
+    if name.chars().any(|c| {
+        c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*')
+    }) {
+        return false;
+    }
+    if name.ends_with('.') || name.ends_with(' ') {
+        return false;
+    }
+    // Windows 保留设备名，不论扩展名
+    let stem = name.split('.').next().unwrap_or("").to_uppercase();
+    let reserved = ["CON", "PRN", "AUX", "NUL"];
+    if reserved.contains(&stem.as_str()) {
+        return false;
+    }
+    if (stem.starts_with("COM") || stem.starts_with("LPT"))
+        && stem.len() == 4
+        && stem[3..].chars().all(|c| ('1'..='9').contains(&c))
+    {
+        return false;
+    }
+    true
+}
@@ -1 +1,22 @@
+mod common;
+mod test_analyze_jobs;
+mod test_archive;
+mod test_chinese_convert;
+mod test_http;
+mod test_llm_cache;
+mod test_llm_output;
+mod test_llm_provider;
+mod test_matching;
+mod test_metadata_cache;
+mod test_metadata_provider;
+mod test_naming;
+mod test_parser;
 mod test_regex;
+mod test_rename;
+mod test_rename_journal;
+mod test_scanner;
+mod test_season_mapping;
+mod test_subject_ranking;
+mod test_subtitle;
+mod test_subtitle_encoding;
+mod test_subtitle_timing;
@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use app_lib::analyze_jobs::{cancel_job, run_job};
+
+#[tokio::test]
+async fn test_run_job_completes() {
+    let mut seen = Vec::new();
+    let tasks = (0..5).map(|i| async move { i * 2 });
+    let (done, canceled) = run_job("test-complete", tasks, 2, |done, value| {
+        seen.push((done, value))
+    })
+    .await
+    .unwrap();
+    assert_eq!(done, 5);
+    assert!(!canceled);
+    assert_eq!(
+        seen.iter().map(|(done, _)| *done).collect::<Vec<_>>(),
+        [1, 2, 3, 4, 5]
+    );
+    // 结束后不再登记
+    assert!(!cancel_job("test-complete").unwrap());
+}
+
+#[tokio::test]
+async fn test_cancel_job() {
+    let job = tokio::spawn(async {
+        let tasks = (0..20).map(|_| tokio::time::sleep(Duration::from_millis(50)));
+        run_job("test-cancel", tasks, 1, |_, _| {}).await
+    });
+    tokio::time::sleep(Duration::from_millis(120)).await;
+
+    // 同名任务不能重复启动
+    let duplicate = run_job(
+        "test-cancel",
+        Vec::<std::future::Ready<()>>::new(),
+        1,
+        |_, _| {},
+    )
+    .await;
+    assert!(duplicate.unwrap_err().contains("test-cancel"));
+
+    assert!(cancel_job("test-cancel").unwrap());
+    let (done, canceled) = job.await.unwrap().unwrap();
+    assert!(canceled);
+    assert!(done > 0 && done < 20, "done = {}", done);
+    assert!(!cancel_job("test-cancel").unwrap());
+    assert!(!cancel_job("no-such-job").unwrap());
+}
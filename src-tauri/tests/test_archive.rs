@@ -0,0 +1,120 @@
+use std::{fs, io::Write};
+
+use app_lib::archive::{
+    decode_entry_names, extract_entries, extraction_names, list_entries, MAX_ENTRY_SIZE,
+};
+use encoding_rs::GBK;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use crate::common::TempDir;
+
+#[test]
+fn test_decode_entry_names() {
+    let raw = vec![
+        GBK.encode("[字幕组] 葬送的芙莉莲/简体/01.ass")
+            .0
+            .into_owned(),
+        "Show/01.sc.ass".as_bytes().to_vec(),
+        b"..\\..\\evil.srt".to_vec(),
+    ];
+    assert_eq!(
+        decode_entry_names(&raw),
+        [
+            "[字幕组] 葬送的芙莉莲/简体/01.ass",
+            "Show/01.sc.ass",
+            "evil.srt"
+        ]
+    );
+}
+
+#[test]
+fn test_extraction_names() {
+    let entries = [
+        "Show/CHS/01.ass".to_string(),
+        "Show/CHT/01.ass".to_string(),
+        "Show/02.ass".to_string(),
+        "03:v2.srt".to_string(),
+    ];
+    assert_eq!(
+        extraction_names(&entries),
+        ["01.CHS.ass", "01.CHT.ass", "02.ass", "03_v2.srt"]
+    );
+}
+
+#[test]
+fn test_extract_zip() {
+    let root = TempDir::new("archive");
+    let archive = root.join("[Group] Show [01-02][CHS].zip");
+
+    let mut writer = ZipWriter::new(fs::File::create(&archive).unwrap());
+    for (name, content) in [
+        ("Show/CHS/01.ass", "简体"),
+        ("Show/CHT/01.ass", "繁體"),
+        ("Show/02.srt", "1"),
+        ("Show/cover.jpg", ""),
+        ("Show/setup.exe", "MZ"),
+    ] {
+        writer
+            .start_file(name, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(content.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let subtitles: Vec<String> = list_entries(&archive)
+        .unwrap()
+        .into_iter()
+        .filter(|e| e.is_subtitle)
+        .map(|e| e.name)
+        .collect();
+    assert_eq!(
+        subtitles,
+        ["Show/CHS/01.ass", "Show/CHT/01.ass", "Show/02.srt"]
+    );
+
+    let dest = root.join("videos");
+    let report = extract_entries(&archive, &subtitles, &dest, false).unwrap();
+    assert!(report.iter().all(|r| r.error.is_none()));
+    assert_eq!(fs::read_to_string(dest.join("01.CHT.ass")).unwrap(), "繁體");
+    assert!(dest.join("02.srt").exists());
+
+    // 已存在的文件不覆盖
+    let report = extract_entries(&archive, &subtitles[2..], &dest, false).unwrap();
+    assert!(report[0].error.is_some());
+
+    // 明确指定的文件也只解压字幕
+    let requested = ["Show/setup.exe".to_string(), "Show/cover.jpg".to_string()];
+    let report = extract_entries(&archive, &requested, &dest, false).unwrap();
+    assert!(report
+        .iter()
+        .all(|r| r.target.is_none() && r.error.as_deref() == Some("不是字幕文件")));
+    assert!(!dest.join("setup.exe").exists());
+    assert!(!dest.join("cover.jpg").exists());
+}
+
+#[test]
+fn test_extract_rejects_oversized_entry() {
+    let root = TempDir::new("bomb");
+    let archive = root.join("bomb.zip");
+
+    // 全零数据压缩后只有几十 KB，解压后超过上限
+    let mut writer = ZipWriter::new(fs::File::create(&archive).unwrap());
+    writer
+        .start_file("big.ass", SimpleFileOptions::default())
+        .unwrap();
+    let chunk = vec![0u8; 1024 * 1024];
+    for _ in 0..=MAX_ENTRY_SIZE / chunk.len() as u64 {
+        writer.write_all(&chunk).unwrap();
+    }
+    writer
+        .start_file("01.ass", SimpleFileOptions::default())
+        .unwrap();
+    writer.write_all(b"ok").unwrap();
+    writer.finish().unwrap();
+
+    let entries = ["big.ass".to_string(), "01.ass".to_string()];
+    let report = extract_entries(&archive, &entries, &root, false).unwrap();
+    assert!(report[0].error.is_some());
+    assert!(!root.join("big.ass").exists());
+    assert_eq!(fs::read(root.join("01.ass")).unwrap(), b"ok");
+}
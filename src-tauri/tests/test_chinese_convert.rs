@@ -0,0 +1,94 @@
+use app_lib::{
+    chinese_convert::{convert_file, convert_subtitle_text, converted_path, converter},
+    types::{ChineseConversion, SubtitleFormat},
+};
+use encoding_rs::BIG5;
+use std::{fs, path::PathBuf};
+
+use crate::common::TempDir;
+
+#[test]
+fn test_convert_phrases() {
+    let s2t = converter(ChineseConversion::S2t);
+    assert_eq!(
+        s2t.convert("头发晒干了，后来去吃面条").0,
+        "頭髮曬乾了，後來去吃麵條"
+    );
+    assert_eq!(s2t.convert("皇后的关系很复杂").0, "皇后的關係很複雜");
+
+    let t2s = converter(ChineseConversion::T2s);
+    assert_eq!(
+        t2s.convert("頭髮曬乾了，後來去吃麵條").0,
+        "头发晒干了，后来去吃面条"
+    );
+    assert_eq!(
+        t2s.convert("這本名著很著名，他說著").0,
+        "这本名著很著名，他说着"
+    );
+    assert_eq!(t2s.convert("abc").1, 0);
+}
+
+#[test]
+fn test_convert_ass_keeps_tags() {
+    let ass = "[V4+ Styles]\nStyle: 简体,微软雅黑,48\n\n[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\nDialogue: 0,0:00:01.00,0:00:02.00,简体,,0,0,0,,{\\fn微软雅黑\\i1}这里{\\i0}还有\\N头发\n";
+    let (out, changed) = convert_subtitle_text(ass, SubtitleFormat::Ass, ChineseConversion::S2t);
+    assert_eq!(changed, 5);
+    assert!(out.contains("Style: 简体,微软雅黑,48\n"));
+    assert!(out.ends_with(
+        "Dialogue: 0,0:00:01.00,0:00:02.00,简体,,0,0,0,,{\\fn微软雅黑\\i1}這裏{\\i0}還有\\N頭髮\n"
+    ));
+}
+
+#[test]
+fn test_converted_path() {
+    let cases = [
+        (
+            "/anime/ep01.chs.ass",
+            ChineseConversion::S2t,
+            "/anime/ep01.cht.ass",
+        ),
+        (
+            "/anime/ep01.SC.srt",
+            ChineseConversion::S2t,
+            "/anime/ep01.TC.srt",
+        ),
+        (
+            "/anime/ep01.cht&jpn.ass",
+            ChineseConversion::T2s,
+            "/anime/ep01.chs&jpn.ass",
+        ),
+        (
+            "/anime/ep01.ass",
+            ChineseConversion::S2t,
+            "/anime/ep01.cht.ass",
+        ),
+        (
+            "/anime/ep01.jpn.srt",
+            ChineseConversion::T2s,
+            "/anime/ep01.jpn.chs.srt",
+        ),
+    ];
+    for (path, direction, expected) in cases {
+        assert_eq!(converted_path(path, direction), PathBuf::from(expected));
+    }
+}
+
+#[test]
+fn test_convert_file_skips_unrecognized_encoding() {
+    // 混入无法解码的字节，只能猜测编码
+    let bytes = [
+        BIG5.encode("1\n00:00:01,000 --> 00:00:03,000\n這是繁體中文字幕\n")
+            .0
+            .as_ref(),
+        b"\xFF\xFF",
+    ]
+    .concat();
+    let root = TempDir::new("big5");
+    let path = root.join("01.cht.srt");
+    fs::write(&path, &bytes).unwrap();
+
+    let report = convert_file(&path.to_string_lossy(), ChineseConversion::T2s, true);
+    assert!(report.target.is_none());
+    assert_eq!(report.error.as_deref(), Some("无法识别字幕编码，未转换"));
+    assert!(!converted_path(&path.to_string_lossy(), ChineseConversion::T2s).exists());
+}
@@ -0,0 +1,103 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use app_lib::http::{http_client, send_with_policy, RetryPolicy};
+
+// 本地模拟接口：按顺序对每次请求返回 responses 中的一项（状态行与额外的响应头），
+// 返回服务地址和每次收到请求的时间
+fn serve_sequence(responses: Vec<&'static str>) -> (String, Arc<Mutex<Vec<Instant>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let log = received.clone();
+    thread::spawn(move || {
+        for head in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+            }
+            log.lock().unwrap().push(Instant::now());
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                head
+            );
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+        }
+    });
+    (format!("http://{}", addr), received)
+}
+
+fn policy(max_retries: u32, base_delay_ms: u64) -> RetryPolicy {
+    RetryPolicy {
+        timeout: Duration::from_secs(10),
+        max_retries,
+        base_delay_ms,
+    }
+}
+
+fn gaps(received: &Mutex<Vec<Instant>>) -> Vec<Duration> {
+    let received = received.lock().unwrap();
+    received.windows(2).map(|w| w[1] - w[0]).collect()
+}
+
+#[tokio::test]
+async fn test_retry_with_backoff() {
+    let (url, received) = serve_sequence(vec![
+        "500 Internal Server Error",
+        "503 Service Unavailable",
+        "200 OK",
+    ]);
+    let response = send_with_policy(http_client().get(&url), policy(3, 100))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    // 第 n 次重试前等待 100ms * 2^n
+    let gaps = gaps(&received);
+    assert_eq!(gaps.len(), 2);
+    assert!(gaps[0] >= Duration::from_millis(100), "{:?}", gaps);
+    assert!(gaps[1] >= Duration::from_millis(200), "{:?}", gaps);
+}
+
+#[tokio::test]
+async fn test_retry_after_header() {
+    let (url, received) = serve_sequence(vec!["429 Too Many Requests\r\nRetry-After: 1", "200 OK"]);
+    let response = send_with_policy(http_client().get(&url), policy(3, 10))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    // Retry-After 优先于指数退避
+    let gaps = gaps(&received);
+    assert_eq!(gaps.len(), 1);
+    assert!(gaps[0] >= Duration::from_secs(1), "{:?}", gaps);
+}
+
+#[tokio::test]
+async fn test_retry_exhausted_and_client_errors() {
+    // 重试用尽后返回最后一次的响应
+    let (url, received) = serve_sequence(vec!["503 Service Unavailable", "502 Bad Gateway"]);
+    let response = send_with_policy(http_client().get(&url), policy(1, 10))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 502);
+    assert_eq!(received.lock().unwrap().len(), 2);
+
+    // 4xx（429 除外）不重试
+    let (url, received) = serve_sequence(vec!["404 Not Found", "200 OK"]);
+    let response = send_with_policy(http_client().get(&url), policy(3, 10))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    assert_eq!(received.lock().unwrap().len(), 1);
+}
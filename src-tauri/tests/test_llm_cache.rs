@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use app_lib::{
+    llm_cache::{cache_key, insert_entry, lookup_entry},
+    types::{AnimeInfo, LlmCacheEntry},
+};
+
+const DAY: u64 = 24 * 60 * 60;
+
+fn info(title: &str) -> AnimeInfo {
+    AnimeInfo {
+        title: title.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_cache_key_includes_model() {
+    let a = cache_key(
+        "v1",
+        "http://localhost:11434 ",
+        "qwen",
+        "[Sub] Show - 01.mkv",
+    );
+    assert_eq!(
+        a,
+        cache_key(
+            "v1",
+            "http://localhost:11434",
+            " qwen",
+            "[Sub] Show - 01.mkv"
+        )
+    );
+    assert_ne!(
+        a,
+        cache_key(
+            "v2",
+            "http://localhost:11434",
+            "qwen",
+            "[Sub] Show - 01.mkv"
+        )
+    );
+    assert_ne!(
+        a,
+        cache_key(
+            "v1",
+            "http://localhost:11434",
+            "llama",
+            "[Sub] Show - 01.mkv"
+        )
+    );
+}
+
+#[test]
+fn test_cache_ttl_expiry() {
+    let mut cache: HashMap<String, LlmCacheEntry> = HashMap::new();
+    let now = 100 * DAY;
+    insert_entry(&mut cache, "a".to_string(), info("A"), 30, 10, now);
+
+    let hit = |at: u64, ttl: u64| lookup_entry(&cache, "a", ttl, at).map(|i| i.title);
+    assert_eq!(hit(now + 30 * DAY, 30).as_deref(), Some("A"));
+    assert_eq!(hit(now + 30 * DAY + 1, 30), None);
+    // ttl 为 0 时永不过期
+    assert_eq!(hit(now + 1000 * DAY, 0).as_deref(), Some("A"));
+    assert!(lookup_entry(&cache, "b", 30, now).is_none());
+
+    // 写入新条目时顺带清除过期的记录
+    insert_entry(
+        &mut cache,
+        "b".to_string(),
+        info("B"),
+        30,
+        10,
+        now + 31 * DAY,
+    );
+    assert!(!cache.contains_key("a"));
+    assert!(cache.contains_key("b"));
+}
+
+#[test]
+fn test_cache_evicts_oldest() {
+    let mut cache: HashMap<String, LlmCacheEntry> = HashMap::new();
+    for (i, key) in ["a", "b", "c", "d"].iter().enumerate() {
+        insert_entry(&mut cache, key.to_string(), info(key), 0, 3, i as u64);
+    }
+    assert_eq!(cache.len(), 3);
+    assert!(!cache.contains_key("a"));
+
+    // 覆盖已有的键会刷新写入时间
+    insert_entry(&mut cache, "b".to_string(), info("B2"), 0, 3, 10);
+    insert_entry(&mut cache, "e".to_string(), info("E"), 0, 3, 11);
+    let mut keys: Vec<&str> = cache.keys().map(String::as_str).collect();
+    keys.sort();
+    assert_eq!(keys, ["b", "d", "e"]);
+    assert_eq!(lookup_entry(&cache, "b", 0, 12).unwrap().title, "B2");
+}
@@ -0,0 +1,30 @@
+use app_lib::llm_output::{first_json_object, parse_anime_info, strip_think};
+
+#[test]
+fn test_parse_output_with_think_and_trailing_text() {
+    let content = r#"<think>文件名里有 {大括号} 和 "引号"</think>
+好的，结果如下：
+```json
+{"title": "葬送的芙莉莲", "episode": "7", "codec": "x265", "group": "VCB-Studio"}
+```
+以上是提取结果 {"title": "ignored"}"#;
+    let info = parse_anime_info(content).unwrap();
+    assert_eq!(info.title, "葬送的芙莉莲");
+    assert_eq!(info.episode.as_deref(), Some("07"));
+    assert_eq!(info.codec.as_deref(), Some("HEVC"));
+    assert_eq!(info.group.as_deref(), Some("VCB-Studio"));
+}
+
+#[test]
+fn test_first_balanced_json_object() {
+    // 缺少开头 <think> 的模板输出
+    assert_eq!(strip_think("推理过程 {x}</think>{\"a\":1}"), "{\"a\":1}");
+
+    let map = first_json_object(r#"说明 {不是JSON} 然后 {"title": "a}b", "episode": 12}"#).unwrap();
+    assert_eq!(map["title"], "a}b");
+
+    let info = parse_anime_info(r#"{"title": "Frieren", "episode": 12, "codec": "AVC"}"#).unwrap();
+    assert_eq!(info.episode.as_deref(), Some("12"));
+    assert!(parse_anime_info(r#"{"episode": "01"}"#).is_err());
+    assert!(parse_anime_info("没有结果").is_err());
+}
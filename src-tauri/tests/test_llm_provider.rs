@@ -0,0 +1,59 @@
+use app_lib::{
+    llm_provider::{provider_for, ChatRequest},
+    types::LlmProviderKind,
+};
+use serde_json::json;
+
+fn chat<'a>(schema: Option<&'a serde_json::Value>, max_tokens: u32) -> ChatRequest<'a> {
+    ChatRequest {
+        model_url: "http://localhost/v1",
+        model_name: "qwen",
+        system: "只返回 JSON",
+        user: "[Group] Show - 01.mkv",
+        temperature: 0.1,
+        max_tokens,
+        schema,
+    }
+}
+
+#[test]
+fn test_request_bodies() {
+    let schema = json!({ "type": "object" });
+    let chat = chat(Some(&schema), 512);
+
+    let body = provider_for(LlmProviderKind::OpenAi).body(&chat);
+    assert_eq!(body["max_tokens"], 512);
+    assert_eq!(body["messages"][0]["role"], "system");
+    assert_eq!(body["messages"][1]["content"], "[Group] Show - 01.mkv");
+    assert_eq!(body["response_format"]["json_schema"]["schema"], schema);
+
+    let body = provider_for(LlmProviderKind::Ollama).body(&chat);
+    assert_eq!(body["options"]["num_predict"], 512);
+    assert_eq!(body["stream"], false);
+    assert_eq!(body["format"], schema);
+
+    let body = provider_for(LlmProviderKind::LlamaCpp).body(&chat);
+    assert_eq!(body["n_predict"], 512);
+    assert_eq!(body["prompt"], "只返回 JSON\n\n[Group] Show - 01.mkv\n");
+    assert_eq!(body["json_schema"], schema);
+
+    let body = provider_for(LlmProviderKind::Messages).body(&chat);
+    assert_eq!(body["max_tokens"], 512);
+    assert_eq!(body["system"], "只返回 JSON");
+    assert!(body.get("response_format").is_none());
+}
+
+#[test]
+fn test_request_limits_and_headers() {
+    // Messages 接口的 max_tokens 有上限
+    let body = provider_for(LlmProviderKind::Messages).body(&chat(None, 200_000));
+    assert_eq!(body["max_tokens"], 8192);
+
+    // 不需要结构化输出时不附带 Schema
+    let body = provider_for(LlmProviderKind::Ollama).body(&chat(None, 512));
+    assert!(body.get("format").is_none());
+
+    assert!(provider_for(LlmProviderKind::OpenAi).headers("").is_empty());
+    let headers = provider_for(LlmProviderKind::Messages).headers("key");
+    assert!(headers.contains(&("x-api-key", "key".to_string())));
+}
@@ -0,0 +1,113 @@
+use app_lib::{
+    matching::{extract_episode, pair_episodes},
+    types::FileInfo,
+};
+use regex::Regex;
+
+fn file(name: &str, is_video: bool) -> FileInfo {
+    FileInfo {
+        name: name.to_string(),
+        path: format!("/anime/{}", name),
+        is_video,
+        is_archive: false,
+    }
+}
+
+#[test]
+fn test_extract_episode_fallbacks() {
+    let cases = [
+        ("[Nekomoe kissaten] Frieren - 01 [1080p].mkv", "01"),
+        ("Frieren.S01E07.1080p.WEB-DL.mkv", "07"),
+        ("Show.E12.mkv", "12"),
+        ("葬送的芙莉莲 第03话.mp4", "03"),
+        (
+            "[VCB-Studio] Kono Healer, Mendokusai [05v2][Ma10p_1080p][x265_flac].mkv",
+            "05",
+        ),
+        ("[Group] Title EP09 [CHS].ass", "09"),
+        ("Title - 3v2 (BD 1080p).mkv", "03"),
+    ];
+    for (name, expected) in cases {
+        assert_eq!(
+            extract_episode(name, None).as_deref(),
+            Some(expected),
+            "{}",
+            name
+        );
+    }
+    assert_eq!(
+        extract_episode("[Group] Movie [1080p][x265].mkv", None),
+        None
+    );
+}
+
+#[test]
+fn test_custom_regex_takes_priority() {
+    let re = Regex::new(r"#(\d+)").unwrap();
+    assert_eq!(
+        extract_episode("[Group] Show #4 [12].mkv", Some(&re)).as_deref(),
+        Some("04")
+    );
+}
+
+#[test]
+fn test_pair_episodes() {
+    let videos = vec![
+        file("[VCB-Studio] Show [01][1080p].mkv", true),
+        file("[VCB-Studio] Show [02][1080p].mkv", true),
+        file("[VCB-Studio] Show [03][1080p].mkv", true),
+        file("[VCB-Studio] Show [04][1080p].mkv", true),
+        file("[VCB-Studio] Show [04v2][1080p].mkv", true),
+        file("[VCB-Studio] Show NCOP.mkv", true),
+    ];
+    let subtitles = vec![
+        file("Show - 01.ass", false),
+        file("Show - 2.ass", false),
+        file("Show - 04.ass", false),
+        file("Show - 05.ass", false),
+    ];
+
+    let result = pair_episodes(&videos, &subtitles, None);
+
+    let paired: Vec<&str> = result.pairs.iter().map(|p| p.episode.as_str()).collect();
+    assert_eq!(paired, vec!["01", "02"]);
+    assert_eq!(result.pairs[1].subtitles[0].name, "Show - 2.ass");
+
+    assert_eq!(result.ambiguous.len(), 1);
+    assert_eq!(result.ambiguous[0].episode, "04");
+    assert_eq!(result.ambiguous[0].videos.len(), 2);
+
+    let unmatched_videos: Vec<Option<&str>> = result
+        .unmatched_videos
+        .iter()
+        .map(|u| u.episode.as_deref())
+        .collect();
+    assert!(unmatched_videos.contains(&Some("03")));
+    assert!(unmatched_videos.contains(&None));
+
+    assert_eq!(result.unmatched_subtitles.len(), 1);
+    assert_eq!(result.unmatched_subtitles[0].episode.as_deref(), Some("05"));
+}
+
+#[test]
+fn test_pair_multiple_subtitle_tracks() {
+    let videos = vec![file("[Group] Show [01][1080p].mkv", true)];
+    let subtitles = vec![
+        file("[Group] Show [01][1080p].chs.ass", false),
+        file("[Group] Show [01][1080p].CHT.ass", false),
+        file("[Group] Show [01][1080p].jp.srt", false),
+    ];
+    let result = pair_episodes(&videos, &subtitles, None);
+    assert_eq!(result.pairs.len(), 1);
+    assert_eq!(result.pairs[0].subtitles.len(), 3);
+    assert!(result.ambiguous.is_empty());
+
+    // 语言标记重复时无法确定使用哪个字幕
+    let subtitles = vec![
+        file("[Group] Show [01].chs.ass", false),
+        file("[Other] Show - 01.chs.ass", false),
+    ];
+    let result = pair_episodes(&videos, &subtitles, None);
+    assert!(result.pairs.is_empty());
+    assert_eq!(result.ambiguous.len(), 1);
+}
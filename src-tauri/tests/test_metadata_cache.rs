@@ -0,0 +1,121 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use app_lib::metadata_cache::{cached_at, CachePolicy};
+
+use crate::common::TempDir;
+
+const DAY: u64 = 24 * 60 * 60;
+const NOW: u64 = 1000 * DAY;
+
+fn policy(now: u64, offline_mode: bool) -> CachePolicy {
+    CachePolicy {
+        ttl_days: 7,
+        offline_mode,
+        now,
+    }
+}
+
+// 带计数的请求，用于判断是否访问了来源
+async fn fetch(calls: &AtomicUsize, result: Result<&str, &str>) -> Result<String, String> {
+    calls.fetch_add(1, Ordering::SeqCst);
+    result.map(str::to_string).map_err(str::to_string)
+}
+
+#[tokio::test]
+async fn test_cache_hit_and_expiry() {
+    let root = TempDir::new("metadata-cache-expiry");
+    let path = root.join("bangumi/subject/1.json");
+    let calls = AtomicUsize::new(0);
+    let get = |now: u64, result| {
+        cached_at(
+            Some(&path),
+            "1",
+            policy(now, false),
+            |_| true,
+            fetch(&calls, result),
+        )
+    };
+
+    assert_eq!(get(NOW, Ok("v1")).await.unwrap(), "v1");
+    assert!(path.exists());
+    // 有效期内直接使用缓存
+    assert_eq!(get(NOW + 7 * DAY, Ok("v2")).await.unwrap(), "v1");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // 过期后重新请求并写入
+    assert_eq!(get(NOW + 8 * DAY, Ok("v2")).await.unwrap(), "v2");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(get(NOW + 9 * DAY, Ok("v3")).await.unwrap(), "v2");
+
+    // 缓存不满足本次请求时按过期处理
+    let refetched = cached_at(
+        Some(&path),
+        "1",
+        policy(NOW + 9 * DAY, false),
+        |data: &String| data != "v2",
+        fetch(&calls, Ok("v4")),
+    )
+    .await;
+    assert_eq!(refetched.unwrap(), "v4");
+
+    // 键不一致（哈希冲突）时视为未命中
+    let other = cached_at(
+        Some(&path),
+        "2",
+        policy(NOW + 9 * DAY, false),
+        |_| true,
+        fetch(&calls, Ok("other")),
+    )
+    .await;
+    assert_eq!(other.unwrap(), "other");
+}
+
+#[tokio::test]
+async fn test_stale_fallback_on_fetch_error() {
+    let root = TempDir::new("metadata-cache-stale");
+    let path = root.join("bangumi/subject/1.json");
+    let calls = AtomicUsize::new(0);
+    let get = |now: u64, result| {
+        cached_at(
+            Some(&path),
+            "1",
+            policy(now, false),
+            |_| true,
+            fetch(&calls, result),
+        )
+    };
+
+    // 没有缓存时返回请求的错误
+    assert_eq!(get(NOW, Err("请求失败")).await.unwrap_err(), "请求失败");
+    assert!(!path.exists());
+
+    assert_eq!(get(NOW, Ok("v1")).await.unwrap(), "v1");
+    // 过期后请求失败，退回过期的缓存
+    assert_eq!(get(NOW + 30 * DAY, Err("请求失败")).await.unwrap(), "v1");
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_offline_mode_reads_cache_only() {
+    let root = TempDir::new("metadata-cache-offline");
+    let path = root.join("bangumi/subject/1.json");
+    let calls = AtomicUsize::new(0);
+    let get = |now: u64, offline: bool, result| {
+        cached_at(
+            Some(&path),
+            "1",
+            policy(now, offline),
+            |_| true,
+            fetch(&calls, result),
+        )
+    };
+
+    let missing = get(NOW, true, Ok("v1")).await.unwrap_err();
+    assert!(missing.contains("离线模式"), "{}", missing);
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    assert_eq!(get(NOW, false, Ok("v1")).await.unwrap(), "v1");
+    // 离线时即使过期也使用缓存，不访问来源
+    assert_eq!(get(NOW + 30 * DAY, true, Ok("v2")).await.unwrap(), "v1");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,169 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    thread,
+};
+
+use app_lib::{
+    anidb::AniDbProvider,
+    anilist::AniListProvider,
+    metadata_provider::{match_episode_titles, MetadataProvider},
+    types::{EpisodeTitleQuery, MetadataEpisode},
+};
+
+const ANIDB_ANIME: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<anime id="17617" restricted="false">
+  <type>TV Series</type>
+  <episodecount>28</episodecount>
+  <startdate>2023-09-29</startdate>
+  <titles>
+    <title xml:lang="x-jat" type="main">Sousou no Frieren</title>
+    <title xml:lang="ja" type="official">葬送のフリーレン</title>
+  </titles>
+  <episodes>
+    <episode id="271001"><epno type="1">1</epno><airdate>2023-09-29</airdate>
+      <title xml:lang="ja">冒険の終わり</title><title xml:lang="en">The Journey's End</title></episode>
+    <episode id="271100"><epno type="2">S1</epno><title xml:lang="en">Special</title></episode>
+    <episode id="271200"><epno type="3">C1</epno><title xml:lang="en">Opening</title></episode>
+  </episodes>
+</anime>"#;
+
+// 本地模拟接口：接受一次请求并返回固定响应，返回服务地址
+fn serve_once(content_type: &'static str, body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut request_body = vec![0; content_length];
+        reader.read_exact(&mut request_body).unwrap();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            content_type,
+            body.len(),
+            body
+        );
+        reader.get_mut().write_all(response.as_bytes()).unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+fn episode(episode_type: i32, sort: f64, ep: Option<f64>, name_cn: &str) -> MetadataEpisode {
+    MetadataEpisode {
+        id: (episode_type * 1000) as i64 + sort as i64,
+        episode_type,
+        sort,
+        ep,
+        season: None,
+        name: None,
+        name_cn: Some(name_cn.to_string()),
+        airdate: None,
+    }
+}
+
+fn query(episode: &str, special: bool) -> EpisodeTitleQuery {
+    EpisodeTitleQuery {
+        episode: episode.to_string(),
+        special,
+        season: None,
+    }
+}
+
+#[test]
+fn test_match_episode_titles() {
+    // 第二季：季内集数 1-2，sort 延续第一季为 13-14
+    let episodes = vec![
+        episode(0, 13.0, Some(1.0), "新的旅程"),
+        episode(0, 14.0, Some(2.0), "魔法使的考试"),
+        episode(1, 1.0, None, "特别篇"),
+    ];
+    let titles = match_episode_titles(
+        &episodes,
+        &[
+            query("01", false),
+            query("14", false),
+            query("SP01", true),
+            query("03", false),
+        ],
+    );
+    let names: Vec<Option<&str>> = titles.iter().map(|t| t.name_cn.as_deref()).collect();
+    assert_eq!(
+        names,
+        vec![Some("新的旅程"), Some("魔法使的考试"), Some("特别篇"), None]
+    );
+    assert_eq!(titles[1].sort, Some(14.0));
+    assert!(titles[2].special);
+}
+
+#[tokio::test]
+async fn test_anilist_detail_from_mock_server() {
+    let url = serve_once(
+        "application/json",
+        r#"{"data":{"Media":{"id":154587,"format":"TV","episodes":28,
+            "synonyms":["Frieren at the Funeral"],
+            "title":{"romaji":"Sousou no Frieren","english":"Frieren: Beyond Journey's End","native":"葬送のフリーレン"},
+            "startDate":{"year":2023,"month":9,"day":29},
+            "coverImage":{"large":"https://example.com/cover.jpg"},
+            "tags":[{"name":"Elf","rank":95}],
+            "streamingEpisodes":[]}}}"#,
+    );
+    let detail = AniListProvider::new(&url).detail(154587).await.unwrap();
+    assert_eq!(detail.name, "Sousou no Frieren");
+    assert_eq!(
+        detail.aliases,
+        vec![
+            "Frieren: Beyond Journey's End",
+            "葬送のフリーレン",
+            "Frieren at the Funeral"
+        ]
+    );
+    assert_eq!(detail.platform.as_deref(), Some("TV"));
+    assert_eq!(detail.air_date.as_deref(), Some("2023-09-29"));
+    assert_eq!((detail.year, detail.episodes), (Some(2023), Some(28)));
+}
+
+#[tokio::test]
+async fn test_anidb_episodes_from_mock_server() {
+    let url = serve_once("text/xml", ANIDB_ANIME);
+    let episodes = AniDbProvider::new(&url, "test", 1)
+        .episodes(17617)
+        .await
+        .unwrap();
+    let summary: Vec<(i32, f64, Option<&str>)> = episodes
+        .iter()
+        .map(|e| (e.episode_type, e.sort, e.name.as_deref()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (0, 1.0, Some("冒険の終わり")),
+            (1, 1.0, Some("Special")),
+            (2, 1.0, Some("Opening"))
+        ]
+    );
+}
+
+// 模拟接口只接受一次连接：详情与章节必须来自同一个请求
+#[tokio::test]
+async fn test_anidb_detail_with_episodes_single_request() {
+    let url = serve_once("text/xml", ANIDB_ANIME);
+    let detail = AniDbProvider::new(&url, "test", 1)
+        .detail_with_episodes(17617)
+        .await
+        .unwrap();
+    assert_eq!(detail.name, "Sousou no Frieren");
+    assert_eq!(detail.episode_list.len(), 3);
+}
@@ -0,0 +1,110 @@
+use app_lib::{
+    naming::{preset_template, render_template, DEFAULT_NAMING_TEMPLATE},
+    types::{MediaServerPreset, NamingInfo},
+};
+
+fn info() -> NamingInfo {
+    NamingInfo {
+        title: "Sousou no Frieren".to_string(),
+        year: Some(2023),
+        season: Some(1),
+        episode: Some("7".to_string()),
+        group: Some("VCB-Studio".to_string()),
+        ext: Some("mkv".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_render_padding_and_folders() {
+    let template =
+        "{title} ({year})/Season {season:02}/{title} - S{season:02}E{episode:02}{? - {ep_title}}.{ext}";
+    assert_eq!(
+        render_template(template, &info()).unwrap(),
+        "Sousou no Frieren (2023)/Season 01/Sousou no Frieren - S01E07.mkv"
+    );
+
+    let mut with_title = info();
+    with_title.ep_title = Some("魔法: 使い".to_string());
+    assert_eq!(
+        render_template(template, &with_title).unwrap(),
+        "Sousou no Frieren (2023)/Season 01/Sousou no Frieren - S01E07 - 魔法： 使い.mkv"
+    );
+
+    assert_eq!(
+        render_template(DEFAULT_NAMING_TEMPLATE, &info()).unwrap(),
+        "Sousou no Frieren.2023.S01E07.VCB-Studio.mkv"
+    );
+    with_title.season = Some(0);
+    assert_eq!(
+        render_template(DEFAULT_NAMING_TEMPLATE, &with_title).unwrap(),
+        "Sousou no Frieren.2023.S00E07 - SP07 魔法： 使い.VCB-Studio.mkv"
+    );
+}
+
+#[test]
+fn test_render_filters_and_errors() {
+    assert_eq!(
+        render_template("[{group|upper}] {title|lower}{?[{codec}]}.{ext}", &info()).unwrap(),
+        "[VCB-STUDIO] sousou no frieren.mkv"
+    );
+    assert_eq!(
+        render_template("{{{title|title}}}", &info()).unwrap(),
+        "{Sousou No Frieren}"
+    );
+    assert!(render_template("{unknown}", &info()).is_err());
+    assert!(render_template("{? {title}", &info()).is_err());
+    assert!(render_template("{title|reverse}", &info()).is_err());
+}
+
+#[test]
+fn test_media_server_presets() {
+    let jellyfin = preset_template(MediaServerPreset::Jellyfin, false);
+    assert_eq!(
+        render_template(jellyfin, &info()).unwrap(),
+        "Sousou no Frieren (2023)/Season 01/Sousou no Frieren - S01E07.mkv"
+    );
+
+    let mut special = info();
+    special.season = Some(0);
+    special.episode = Some("1".to_string());
+    special.year = None;
+    let specials = preset_template(MediaServerPreset::Jellyfin, true);
+    assert_eq!(
+        render_template(specials, &special).unwrap(),
+        "Sousou no Frieren/Specials/Sousou no Frieren - S00E01.mkv"
+    );
+    let plex = preset_template(MediaServerPreset::Plex, false);
+    assert_eq!(
+        render_template(plex, &info()).unwrap(),
+        "Sousou no Frieren (2023)/Season 01/Sousou no Frieren (2023) - s01e07.mkv"
+    );
+}
+
+#[test]
+fn test_missing_variable_is_error() {
+    // 单季作品通常识别不出季数
+    let mut no_season = info();
+    no_season.season = None;
+    let jellyfin = preset_template(MediaServerPreset::Jellyfin, false);
+    assert_eq!(
+        render_template(jellyfin, &no_season).unwrap_err(),
+        "缺少变量 season"
+    );
+    assert_eq!(
+        render_template(DEFAULT_NAMING_TEMPLATE, &no_season).unwrap_err(),
+        "缺少变量 season"
+    );
+
+    let mut no_episode = info();
+    no_episode.episode = None;
+    assert_eq!(
+        render_template(jellyfin, &no_episode).unwrap_err(),
+        "缺少变量 episode"
+    );
+    // 条件段内的变量为空时只省略该段
+    assert_eq!(
+        render_template("{title}{? - {episode}}.{ext}", &no_episode).unwrap(),
+        "Sousou no Frieren.mkv"
+    );
+}
@@ -0,0 +1,41 @@
+use app_lib::parser::parse_filename;
+
+#[test]
+fn test_parse_fansub_bracket_style() {
+    let parsed =
+        parse_filename("[VCB-Studio] Kono Healer, Mendokusai [05v2][Ma10p_1080p][x265_flac].mkv");
+    let info = parsed.info;
+    assert_eq!(info.group.as_deref(), Some("VCB-Studio"));
+    assert_eq!(info.title, "Kono Healer, Mendokusai");
+    assert_eq!(info.episode.as_deref(), Some("05"));
+    assert_eq!(info.version, Some(2));
+    assert_eq!(info.resolution.as_deref(), Some("1080p"));
+    assert_eq!(info.codec.as_deref(), Some("HEVC"));
+    assert!(parsed.confidence >= 0.8);
+}
+
+#[test]
+fn test_parse_dash_episode_with_crc() {
+    let parsed = parse_filename("[SubsPlease] Sousou no Frieren - 12v2 (1080p) [A1B2C3D4].mkv");
+    let info = parsed.info;
+    assert_eq!(info.group.as_deref(), Some("SubsPlease"));
+    assert_eq!(info.title, "Sousou no Frieren");
+    assert_eq!(info.episode.as_deref(), Some("12"));
+    assert_eq!(info.version, Some(2));
+    assert_eq!(info.crc32.as_deref(), Some("A1B2C3D4"));
+}
+
+#[test]
+fn test_parse_scene_style_and_season() {
+    let info = parse_filename("Frieren.S01E07.1080p.WEB-DL.x264-GROUP.mkv").info;
+    assert_eq!(info.title, "Frieren");
+    assert_eq!(info.season, Some(1));
+    assert_eq!(info.episode.as_deref(), Some("07"));
+    assert_eq!(info.source.as_deref(), Some("WEB-DL"));
+    assert_eq!(info.codec.as_deref(), Some("AVC"));
+
+    let info = parse_filename("[Group] Mushoku Tensei Season 2 - 03 [1080p].mkv").info;
+    assert_eq!(info.title, "Mushoku Tensei");
+    assert_eq!(info.season, Some(2));
+    assert_eq!(info.episode.as_deref(), Some("03"));
+}
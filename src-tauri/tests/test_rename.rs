@@ -0,0 +1,236 @@
+use std::{fs, path::Path};
+
+use app_lib::{
+    rename::{build_plan, build_video_plan, rename_transaction, rollback_response, validate_plan},
+    types::{
+        FileInfo, MediaServerPreset, NamingInfo, RenameOperation, RenamePlanItem, RenameProblem,
+        RenameRequest, SubtitleTrack, VideoRenameItem, VideoRenameRequest,
+    },
+};
+
+use crate::common::TempDir;
+
+fn touch(root: &Path, name: &str) -> FileInfo {
+    let path = root.join(name);
+    fs::write(&path, b"").unwrap();
+    FileInfo {
+        name: name.to_string(),
+        path: path.to_string_lossy().to_string(),
+        is_video: name.ends_with(".mkv"),
+        is_archive: false,
+    }
+}
+
+fn request(videos: Vec<FileInfo>, subtitles: Vec<FileInfo>) -> RenameRequest {
+    RenameRequest {
+        video_files: videos,
+        subtitle_files: subtitles,
+        suffix: "chs".to_string(),
+        tracks: Vec::new(),
+        keep_language_tag: false,
+        subtitle_encoding: None,
+        subtitle_format: None,
+    }
+}
+
+fn item(root: &Path, source: &str, target: &str) -> RenamePlanItem {
+    RenamePlanItem {
+        source_name: source.to_string(),
+        source_path: root.join(source).to_string_lossy().to_string(),
+        target_name: target.to_string(),
+        target_path: root.join(target).to_string_lossy().to_string(),
+        problems: Vec::new(),
+    }
+}
+
+fn op(root: &Path, from: &str, to: &str) -> RenameOperation {
+    RenameOperation {
+        from_path: root.join(from).to_string_lossy().to_string(),
+        to_path: root.join(to).to_string_lossy().to_string(),
+        created_dirs: Vec::new(),
+    }
+}
+
+#[test]
+fn test_build_plan_is_dry_run() {
+    let root = TempDir::new("plan-preview");
+    let videos = vec![touch(&root, "Show 01.mkv"), touch(&root, "Show 02.mkv")];
+    let subtitles = vec![touch(&root, "sub01.ass"), touch(&root, "sub02.ass")];
+
+    let plan = build_plan(&request(videos, subtitles)).unwrap();
+    assert!(plan.valid);
+    let targets: Vec<&str> = plan.items.iter().map(|i| i.target_name.as_str()).collect();
+    assert_eq!(targets, ["Show 01.chs.ass", "Show 02.chs.ass"]);
+    assert_eq!(
+        plan.items[0].target_path,
+        root.join("Show 01.chs.ass").to_string_lossy()
+    );
+    // 预览不修改磁盘
+    assert!(root.join("sub01.ass").exists());
+    assert!(!root.join("Show 01.chs.ass").exists());
+}
+
+#[test]
+fn test_build_plan_detects_conflicts() {
+    let root = TempDir::new("plan-conflicts");
+    let videos = vec![touch(&root, "Show 01.mkv"), touch(&root, "Show 02.mkv")];
+    let subtitles = vec![touch(&root, "sub01.ass"), touch(&root, "sub02.ass")];
+    touch(&root, "Show 02.chs.ass");
+
+    let plan = build_plan(&request(videos.clone(), subtitles.clone())).unwrap();
+    assert!(!plan.valid);
+    assert!(plan.items[0].problems.is_empty());
+    assert_eq!(plan.items[1].problems, [RenameProblem::TargetExists]);
+
+    // 两条字幕轨道指向同一个视频且后缀相同
+    let mut duplicate = request(videos, Vec::new());
+    duplicate.tracks = subtitles
+        .into_iter()
+        .map(|subtitle| SubtitleTrack {
+            video_index: 0,
+            subtitle,
+            suffix: None,
+        })
+        .collect();
+    let plan = build_plan(&duplicate).unwrap();
+    assert!(!plan.valid);
+    assert!(plan
+        .items
+        .iter()
+        .all(|i| i.problems == [RenameProblem::DuplicateTarget]));
+
+    // 数量不一致时直接报错
+    let mismatched = request(vec![touch(&root, "Show 01.mkv")], Vec::new());
+    assert!(build_plan(&mismatched).is_err());
+}
+
+#[test]
+fn test_validate_plan_rechecks_disk() {
+    let root = TempDir::new("plan-validate");
+    touch(&root, "a.ass");
+    let mut items = vec![
+        item(&root, "a.ass", "b.ass"),
+        item(&root, "missing.ass", "c.ass"),
+        item(&root, "a.ass", "bad?.ass"),
+        item(&root, "a.ass", "Season 01/a.ass"),
+    ];
+    items[3].target_name = "a.ass".to_string();
+
+    validate_plan(&mut items, false);
+    assert!(items[0].problems.is_empty());
+    assert_eq!(items[1].problems, [RenameProblem::MissingSource]);
+    assert!(items[2].problems.contains(&RenameProblem::InvalidFilename));
+    assert_eq!(items[3].problems, [RenameProblem::MissingTargetDir]);
+
+    // 允许创建目录时不再报告缺少目标目录
+    validate_plan(&mut items, true);
+    assert!(items[3].problems.is_empty());
+
+    // 预览之后目标被占用，执行前重新检查能发现
+    touch(&root, "b.ass");
+    validate_plan(&mut items, true);
+    assert_eq!(items[0].problems, [RenameProblem::TargetExists]);
+}
+
+#[test]
+fn test_rename_transaction_rolls_back() {
+    let root = TempDir::new("rename-rollback");
+    fs::write(root.join("a.ass"), b"a").unwrap();
+    fs::write(root.join("b.ass"), b"b").unwrap();
+    let mut ops = vec![
+        op(&root, "a.ass", "A.ass"),
+        op(&root, "b.ass", "B.ass"),
+        op(&root, "missing.ass", "C.ass"),
+    ];
+
+    let report = rename_transaction(&mut ops).unwrap_err();
+    assert_eq!(report.failed_index, 2);
+    assert!(report.rollback_errors.is_empty());
+    // 按相反顺序恢复
+    let rolled_back: Vec<&str> = report
+        .rolled_back
+        .iter()
+        .map(|op| op.from_path.as_str())
+        .collect();
+    assert_eq!(rolled_back, [&ops[1].from_path, &ops[0].from_path]);
+    assert_eq!(fs::read(root.join("a.ass")).unwrap(), b"a");
+    assert_eq!(fs::read(root.join("b.ass")).unwrap(), b"b");
+    assert!(!root.join("A.ass").exists());
+    assert!(!root.join("B.ass").exists());
+
+    let response = rollback_response(&ops, report);
+    assert!(!response.success);
+    assert!(response.message.contains("missing.ass"));
+    assert!(response.message.contains("已回滚2个文件"));
+    assert!(response.renamed_files.is_empty());
+}
+
+#[test]
+fn test_rename_transaction_applies_all() {
+    let root = TempDir::new("rename-apply");
+    fs::write(root.join("a.ass"), b"a").unwrap();
+    fs::write(root.join("b.ass"), b"b").unwrap();
+    // 交换文件名需要经过临时名
+    let mut ops = vec![
+        op(&root, "a.ass", "tmp.ass"),
+        op(&root, "b.ass", "a.ass"),
+        op(&root, "tmp.ass", "b.ass"),
+    ];
+
+    assert!(rename_transaction(&mut ops).is_ok());
+    assert_eq!(fs::read(root.join("a.ass")).unwrap(), b"b");
+    assert_eq!(fs::read(root.join("b.ass")).unwrap(), b"a");
+    assert!(!root.join("tmp.ass").exists());
+}
+
+#[test]
+fn test_rollback_prunes_created_dirs() {
+    let root = TempDir::new("rename-rollback-dirs");
+    fs::write(root.join("01.mkv"), b"1").unwrap();
+    let mut ops = vec![
+        op(&root, "01.mkv", "Show/Season 01/Show S01E01.mkv"),
+        // 失败的操作自己新建的目录同样清理
+        op(&root, "missing.mkv", "Show/Season 02/Show S02E01.mkv"),
+    ];
+
+    let report = rename_transaction(&mut ops).unwrap_err();
+    assert_eq!(report.failed_index, 1);
+    assert!(root.join("01.mkv").exists());
+    assert!(!root.join("Show").exists());
+}
+
+#[test]
+fn test_video_plan_defaults_season() {
+    let root = TempDir::new("video-plan-season");
+    let video = touch(&root, "[Sub] Frieren - 07.mkv");
+    let item = |episode: &str| VideoRenameItem {
+        source_path: video.path.clone(),
+        info: NamingInfo {
+            title: "Sousou no Frieren".to_string(),
+            episode: Some(episode.to_string()),
+            ext: Some("mkv".to_string()),
+            ..Default::default()
+        },
+        companions: Vec::new(),
+    };
+    let request = |items| VideoRenameRequest {
+        items,
+        template: None,
+        preset: Some(MediaServerPreset::Jellyfin),
+        target_root: Some(root.join("TV").to_string_lossy().to_string()),
+        create_dirs: true,
+    };
+
+    // 未识别出季数时按第 1 季处理
+    let plan = build_video_plan(&request(vec![item("7")])).unwrap();
+    assert_eq!(
+        plan.items[0].target_path,
+        root.join("TV")
+            .join("Sousou no Frieren/Season 01/Sousou no Frieren - S01E07.mkv")
+            .to_string_lossy()
+    );
+
+    // 分集不是数字时无法判断是否为特别篇
+    let error = build_video_plan(&request(vec![item("OVA")])).unwrap_err();
+    assert_eq!(error, "缺少变量 season");
+}
@@ -0,0 +1,203 @@
+use std::{fs, path::Path};
+
+use app_lib::{
+    rename::rename_transaction,
+    rename_journal::{record_batch, switch_batch},
+    types::{RenameBatchStatus, RenameOperation},
+};
+
+use crate::common::TempDir;
+
+fn op(root: &Path, from: &str, to: &str) -> RenameOperation {
+    RenameOperation {
+        from_path: root.join(from).to_string_lossy().to_string(),
+        to_path: root.join(to).to_string_lossy().to_string(),
+        created_dirs: Vec::new(),
+    }
+}
+
+// 执行一组重命名并写入历史，返回批次 ID
+fn apply(journal: &Path, mut ops: Vec<RenameOperation>, created_files: Vec<String>) -> u64 {
+    assert!(rename_transaction(&mut ops).is_ok());
+    record_batch(journal, "重命名".to_string(), ops, created_files).unwrap()
+}
+
+#[test]
+fn test_undo_redo_round_trip() {
+    let root = TempDir::new("journal-round-trip");
+    let journal = root.join("rename_history.json");
+    fs::write(root.join("a.ass"), b"a").unwrap();
+    fs::write(root.join("b.ass"), b"b").unwrap();
+    let id = apply(
+        &journal,
+        vec![op(&root, "a.ass", "A.ass"), op(&root, "b.ass", "B.ass")],
+        Vec::new(),
+    );
+    assert!(journal.exists());
+
+    // 原位置被占用时拒绝撤销，不移动任何文件
+    fs::write(root.join("a.ass"), b"new").unwrap();
+    let response = switch_batch(&journal, id, RenameBatchStatus::Undone).unwrap();
+    assert!(!response.success);
+    assert!(response.message.contains("目标路径已被占用"));
+    assert!(root.join("A.ass").exists());
+    assert!(root.join("B.ass").exists());
+
+    fs::remove_file(root.join("a.ass")).unwrap();
+    let response = switch_batch(&journal, id, RenameBatchStatus::Undone).unwrap();
+    assert!(response.success, "{}", response.message);
+    assert_eq!(response.batch_id, Some(id));
+    assert_eq!(fs::read(root.join("a.ass")).unwrap(), b"a");
+    assert_eq!(fs::read(root.join("b.ass")).unwrap(), b"b");
+
+    let response = switch_batch(&journal, id, RenameBatchStatus::Undone).unwrap();
+    assert!(!response.success);
+    assert_eq!(response.message, "该批次已撤销");
+
+    // 文件被移走后拒绝重做
+    fs::rename(root.join("b.ass"), root.join("moved.ass")).unwrap();
+    let response = switch_batch(&journal, id, RenameBatchStatus::Applied).unwrap();
+    assert!(!response.success);
+    assert!(response.message.contains("文件不在预期位置"));
+    assert!(root.join("a.ass").exists());
+
+    fs::rename(root.join("moved.ass"), root.join("b.ass")).unwrap();
+    let response = switch_batch(&journal, id, RenameBatchStatus::Applied).unwrap();
+    assert!(response.success, "{}", response.message);
+    assert_eq!(fs::read(root.join("A.ass")).unwrap(), b"a");
+    assert_eq!(fs::read(root.join("B.ass")).unwrap(), b"b");
+
+    // 历史保存在文件中，新批次的 ID 接着递增
+    fs::write(root.join("c.ass"), b"c").unwrap();
+    let next = apply(&journal, vec![op(&root, "c.ass", "C.ass")], Vec::new());
+    assert_eq!(next, id + 1);
+    assert!(switch_batch(&journal, 99, RenameBatchStatus::Undone).is_err());
+}
+
+#[test]
+fn test_undo_removes_created_files() {
+    let root = TempDir::new("journal-created");
+    let journal = root.join("rename_history.json");
+    fs::write(root.join("a.ass"), b"a").unwrap();
+    let id = apply(
+        &journal,
+        vec![op(&root, "a.ass", "Show 01.ass")],
+        vec![root.join("Show 01.srt").to_string_lossy().to_string()],
+    );
+    fs::write(root.join("Show 01.srt"), b"srt").unwrap();
+
+    let response = switch_batch(&journal, id, RenameBatchStatus::Undone).unwrap();
+    assert!(response.success, "{}", response.message);
+    assert!(response.message.contains("删除1个生成的文件"));
+    assert!(root.join("a.ass").exists());
+    assert!(!root.join("Show 01.srt").exists());
+
+    // 重做时不会重新生成
+    let response = switch_batch(&journal, id, RenameBatchStatus::Applied).unwrap();
+    assert!(response.success, "{}", response.message);
+    assert!(root.join("Show 01.ass").exists());
+    assert!(!root.join("Show 01.srt").exists());
+}
+
+#[test]
+fn test_undo_prunes_created_dirs() {
+    let root = TempDir::new("journal-prune");
+    let journal = root.join("rename_history.json");
+    fs::create_dir_all(root.join("downloads")).unwrap();
+    fs::create_dir_all(root.join("library/Other")).unwrap();
+    fs::write(root.join("downloads/01.mkv"), b"1").unwrap();
+    fs::write(root.join("downloads/01.ass"), b"s").unwrap();
+    fs::write(root.join("downloads/SP.mkv"), b"sp").unwrap();
+    fs::write(root.join("library/Other/keep.mkv"), b"k").unwrap();
+
+    // 目标目录不存在时按需逐级创建
+    let id = apply(
+        &journal,
+        vec![
+            op(
+                &root,
+                "downloads/01.mkv",
+                "library/Show/Season 01/Show S01E01.mkv",
+            ),
+            op(
+                &root,
+                "downloads/01.ass",
+                "library/Show/Season 01/Show S01E01.ass",
+            ),
+            op(
+                &root,
+                "downloads/SP.mkv",
+                "library/Show/Specials/Show S00E01.mkv",
+            ),
+        ],
+        Vec::new(),
+    );
+    assert!(root.join("library/Show/Season 01/Show S01E01.mkv").exists());
+    assert!(root.join("library/Show/Specials/Show S00E01.mkv").exists());
+
+    let response = switch_batch(&journal, id, RenameBatchStatus::Undone).unwrap();
+    assert!(response.success, "{}", response.message);
+    assert!(root.join("downloads/01.mkv").exists());
+    assert!(root.join("downloads/SP.mkv").exists());
+    // 新建的目录变空后被删除，原有的目录保留
+    assert!(!root.join("library/Show").exists());
+    assert!(root.join("library/Other/keep.mkv").exists());
+    assert!(root.join("downloads").exists());
+
+    // 重做时重新创建目录
+    let response = switch_batch(&journal, id, RenameBatchStatus::Applied).unwrap();
+    assert!(response.success, "{}", response.message);
+    assert!(root.join("library/Show/Season 01/Show S01E01.ass").exists());
+}
+
+#[test]
+fn test_undo_keeps_existing_empty_dirs() {
+    let root = TempDir::new("journal-keep-dirs");
+    let journal = root.join("rename_history.json");
+    fs::write(root.join("01.mkv"), b"1").unwrap();
+    fs::write(root.join("02.mkv"), b"2").unwrap();
+    // 媒体库中原本就有的空目录
+    fs::create_dir_all(root.join("TV/Show/Season 01")).unwrap();
+
+    let id = apply(
+        &journal,
+        vec![
+            op(&root, "01.mkv", "TV/Show/Season 01/Show S01E01.mkv"),
+            op(&root, "02.mkv", "TV/Show/Season 02/Show S02E01.mkv"),
+        ],
+        Vec::new(),
+    );
+    let response = switch_batch(&journal, id, RenameBatchStatus::Undone).unwrap();
+    assert!(response.success, "{}", response.message);
+    assert!(root.join("01.mkv").exists());
+    assert!(root.join("TV/Show/Season 01").is_dir());
+    assert!(!root.join("TV/Show/Season 02").exists());
+
+    // 重做后再撤销，只清理重做时新建的目录
+    let response = switch_batch(&journal, id, RenameBatchStatus::Applied).unwrap();
+    assert!(response.success, "{}", response.message);
+    let response = switch_batch(&journal, id, RenameBatchStatus::Undone).unwrap();
+    assert!(response.success, "{}", response.message);
+    assert!(root.join("TV/Show/Season 01").is_dir());
+    assert!(!root.join("TV/Show/Season 02").exists());
+}
+
+// 用指向新文件名的符号链接模拟大小写不敏感的文件系统：原文件名与新文件名指向同一个文件
+#[cfg(unix)]
+#[test]
+fn test_undo_case_only_rename() {
+    let root = TempDir::new("journal-case-only");
+    let journal = root.join("rename_history.json");
+    fs::write(root.join("ep01.ass"), b"a").unwrap();
+    let id = apply(
+        &journal,
+        vec![op(&root, "ep01.ass", "EP01.ass")],
+        Vec::new(),
+    );
+    std::os::unix::fs::symlink(root.join("EP01.ass"), root.join("ep01.ass")).unwrap();
+
+    let response = switch_batch(&journal, id, RenameBatchStatus::Undone).unwrap();
+    assert!(response.success, "{}", response.message);
+    assert_eq!(fs::read(root.join("ep01.ass")).unwrap(), b"a");
+    assert!(!root.join("EP01.ass").exists());
+}
@@ -0,0 +1,97 @@
+use std::{fs, path::Path};
+
+use app_lib::{
+    scanner::{scan_dir, ExcludeSet},
+    types::ScanOptions,
+};
+
+use crate::common::TempDir;
+
+// 在临时目录中创建 BD 常见的多层结构
+fn make_tree(name: &str) -> TempDir {
+    let root = TempDir::new(name);
+    for file in [
+        "Vol.1/BDMV/[BD] Show - 01.mkv",
+        "Vol.1/BDMV/[BD] Show - 01.sc.ass",
+        "Vol.1/SPs/[BD] Show - NCOP.mkv",
+        "Vol.1/[BD] Show - 02.mkv",
+        "Vol.1/sample/clip.mkv",
+        "Vol.1/Show Sample.mkv",
+        "CDs/track01.flac",
+        "Scans/cover.jpg",
+        "[BD] Show - 03.mp4",
+        "a/b/c/d/[BD] Show - 04.mkv",
+    ] {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, b"").unwrap();
+    }
+    root
+}
+
+fn names(root: &Path, options: &ScanOptions) -> Vec<String> {
+    scan_dir(root, options)
+        .unwrap()
+        .into_iter()
+        .map(|f| f.name)
+        .collect()
+}
+
+#[test]
+fn test_exclude_patterns() {
+    let set = ExcludeSet::new(&[
+        "SPs/".to_string(),
+        "*sample*".to_string(),
+        "/extras/**".to_string(),
+        "!keep.sample.mkv".to_string(),
+    ])
+    .unwrap();
+    assert!(set.is_excluded("Vol.1/SPs", true));
+    assert!(!set.is_excluded("Vol.1/SPs", false));
+    assert!(set.is_excluded("Vol.1/Show Sample.mkv", false));
+    assert!(set.is_excluded("extras/a/b.mkv", false));
+    assert!(!set.is_excluded("Vol.1/extras/b.mkv", false));
+    assert!(!set.is_excluded("keep.sample.mkv", false));
+}
+
+#[test]
+fn test_scan_dir_depth_and_subtitles() {
+    let root = make_tree("scan");
+    let options = ScanOptions {
+        max_depth: 3,
+        exclude: ScanOptions::default().exclude,
+        follow_symlinks: false,
+        include_subtitles: true,
+    };
+    assert_eq!(
+        names(&root, &options),
+        [
+            "[BD] Show - 01.mkv",
+            "[BD] Show - 01.sc.ass",
+            "[BD] Show - 02.mkv",
+            "[BD] Show - 03.mp4",
+        ]
+    );
+
+    let shallow = ScanOptions {
+        max_depth: 0,
+        include_subtitles: false,
+        ..options.clone()
+    };
+    assert_eq!(names(&root, &shallow), ["[BD] Show - 03.mp4"]);
+
+    let deep = ScanOptions {
+        max_depth: 4,
+        include_subtitles: false,
+        ..options
+    };
+    assert_eq!(
+        names(&root, &deep),
+        [
+            "[BD] Show - 01.mkv",
+            "[BD] Show - 02.mkv",
+            "[BD] Show - 03.mp4",
+            "[BD] Show - 04.mkv",
+        ]
+    );
+}
@@ -0,0 +1,59 @@
+use app_lib::{
+    season_mapping::{absolute_to_seasonal, map_episode, seasonal_to_absolute},
+    types::SeasonBoundary,
+};
+
+fn boundaries() -> Vec<SeasonBoundary> {
+    [(101, 26), (102, 24), (103, 0)]
+        .iter()
+        .enumerate()
+        .scan(1, |first, (i, &(id, episodes))| {
+            let boundary = SeasonBoundary {
+                subject_id: id,
+                season: i as u32 + 1,
+                name: format!("Season {}", i + 1),
+                name_cn: None,
+                first_episode: *first,
+                episodes,
+            };
+            *first += episodes;
+            Some(boundary)
+        })
+        .collect()
+}
+
+#[test]
+fn test_absolute_and_seasonal_conversion() {
+    let b = boundaries();
+    assert_eq!(absolute_to_seasonal(&b, 26), Some((1, 26)));
+    assert_eq!(absolute_to_seasonal(&b, 27), Some((2, 1)));
+    // 最后一季集数未知，不限上限
+    assert_eq!(absolute_to_seasonal(&b, 62), Some((3, 12)));
+    assert_eq!(absolute_to_seasonal(&b, 0), None);
+    assert_eq!(seasonal_to_absolute(&b, 2, 24), Some(50));
+    assert_eq!(seasonal_to_absolute(&b, 2, 25), None);
+    assert_eq!(seasonal_to_absolute(&b, 3, 12), Some(62));
+}
+
+#[test]
+fn test_map_episode_from_selected_subject() {
+    let b = boundaries();
+    // 不超过所选条目集数时按季内集数处理
+    let m = map_episode(&b, 102, "05");
+    assert_eq!(
+        (m.season, m.seasonal_episode.as_deref()),
+        (Some(2), Some("05"))
+    );
+    assert_eq!(m.absolute, Some(31));
+
+    // 超过时按绝对集数处理，可以落在其他季
+    let m = map_episode(&b, 102, "30.5");
+    assert_eq!(
+        (m.season, m.seasonal_episode.as_deref()),
+        (Some(2), Some("04.5"))
+    );
+    assert_eq!(m.subject_id, Some(102));
+
+    let m = map_episode(&b, 101, "SP");
+    assert_eq!(m.season, None);
+}
@@ -0,0 +1,79 @@
+use app_lib::{
+    subject_ranking::{rank_candidates, title_similarity},
+    types::MetadataSubjectDetail,
+};
+
+fn detail(
+    id: i64,
+    name: &str,
+    name_cn: Option<&str>,
+    year: i32,
+    episodes: i32,
+) -> MetadataSubjectDetail {
+    MetadataSubjectDetail {
+        id,
+        name: name.to_string(),
+        name_cn: name_cn.map(str::to_string),
+        cover_url: None,
+        episodes: Some(episodes),
+        year: Some(year),
+        aliases: Vec::new(),
+        tags: Vec::new(),
+        platform: Some("TV".to_string()),
+        air_date: None,
+        episode_list: Vec::new(),
+    }
+}
+
+fn filenames(year: i32, count: u32) -> Vec<String> {
+    (1..=count)
+        .map(|n| {
+            format!(
+                "[Group] Sousou no Frieren ({}) - {:02} [1080p].mkv",
+                year, n
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_title_similarity() {
+    assert_eq!(
+        title_similarity("Sousou no Frieren", "sousou-no-frieren"),
+        1.0
+    );
+    assert_eq!(title_similarity("ＳＰＹ×ＦＡＭＩＬＹ", "SPY×FAMILY"), 1.0);
+    assert!(title_similarity("葬送的芙莉莲", "葬送的芙莉蓮") > 0.8);
+    assert!(title_similarity("Frieren", "Oshi no Ko") < 0.3);
+    assert_eq!(title_similarity("", "Frieren"), 0.0);
+}
+
+#[test]
+fn test_rank_candidates_auto_select() {
+    let mut with_alias = detail(400602, "葬送のフリーレン", Some("葬送的芙莉莲"), 2023, 28);
+    with_alias.aliases.push("Sousou no Frieren".to_string());
+    let details = vec![
+        detail(
+            1,
+            "葬送のフリーレン 第2期",
+            Some("葬送的芙莉莲 第二季"),
+            2026,
+            12,
+        ),
+        with_alias,
+    ];
+    let ranking = rank_candidates(details.clone(), "Sousou no Frieren", &filenames(2023, 28));
+    assert_eq!(ranking.selected, Some(400602));
+    assert_eq!(ranking.candidates[0].matched_title, "Sousou no Frieren");
+    assert_eq!(ranking.candidates[0].year_score, Some(1.0));
+    assert_eq!(ranking.candidates[0].episode_score, Some(1.0));
+
+    // 两个候选标题同样接近且缺少年份、集数信息时不自动选择
+    let ambiguous = vec![
+        detail(1, "Frieren", None, 2023, 28),
+        detail(2, "Frieren", None, 2023, 28),
+    ];
+    let ranking = rank_candidates(ambiguous, "Frieren", &[]);
+    assert_eq!(ranking.selected, None);
+    assert_eq!(ranking.candidates.len(), 2);
+}
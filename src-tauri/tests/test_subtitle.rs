@@ -0,0 +1,124 @@
+use std::fs;
+
+use app_lib::{
+    subtitle::{convert_subtitle_file, parse_subtitle, parse_timestamp, write_subtitle},
+    types::SubtitleFormat,
+};
+use encoding_rs::EUC_KR;
+
+use crate::common::TempDir;
+
+const SRT: &str = "1\n00:00:01,500 --> 00:00:03,000\n<i>你好</i>\n世界\n\n2\n00:01:02,010 --> 00:01:04,000\n<font color=\"#ff8000\">2</font>\n";
+
+const ASS: &str = "[Script Info]
+ScriptType: v4.00+
+PlayResX: 1280
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,48,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,0,2,10,10,10,1
+Style: Sign,Arial,40,&H00FFFFFF,&H000000FF,&H00112233,&H00000000,-1,0,0,0,100,100,0,0,1,2,0,8,10,10,10,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Comment: 0,0:00:00.00,0:00:01.00,Default,,0,0,0,,校对
+Dialogue: 0,0:00:01.00,0:00:02.50,Default,,0,0,0,,{\\bord3\\i1}斜体{\\i0}，正常\\N第二行
+Dialogue: 0,0:00:01.00,0:00:02.50,Sign,,0,0,0,,{\\pos(10,10)}招牌
+Dialogue: 0,0:00:03.00,0:00:04.00,Default,,0,0,0,,{\\p1}m 0 0 l 10 10{\\p0}
+";
+
+#[test]
+fn test_parse_timestamp() {
+    assert_eq!(parse_timestamp("00:01:02,345"), Some(62_345));
+    assert_eq!(parse_timestamp("1:01:02.34"), Some(3_662_340));
+    assert_eq!(parse_timestamp("01:02.5"), Some(62_500));
+    assert_eq!(parse_timestamp("abc"), None);
+}
+
+#[test]
+fn test_srt_ass_round_trip() {
+    let subtitle = parse_subtitle(SRT, SubtitleFormat::Srt);
+    assert_eq!(subtitle.cues.len(), 2);
+    assert_eq!(subtitle.cues[0].start, 1_500);
+    assert_eq!(subtitle.cues[0].text, "{\\i1}你好{\\i0}\\N世界");
+    assert_eq!(subtitle.cues[1].text, "{\\c&H0080FF&}2{\\c}");
+
+    let ass = write_subtitle(&subtitle, SubtitleFormat::Ass, true);
+    assert!(ass.contains("Style: Default,"));
+    assert!(
+        ass.contains("Dialogue: 0,0:00:01.50,0:00:03.00,Default,,0,0,0,,{\\i1}你好{\\i0}\\N世界")
+    );
+
+    let back = parse_subtitle(&ass, SubtitleFormat::Ass);
+    let srt = write_subtitle(&back, SubtitleFormat::Srt, true);
+    assert_eq!(
+        srt,
+        "1\n00:00:01,500 --> 00:00:03,000\n<i>你好</i>\n世界\n\n2\n00:01:02,010 --> 00:01:04,000\n<font color=\"#ff8000\">2</font>\n\n"
+    );
+}
+
+#[test]
+fn test_ass_to_srt_and_vtt() {
+    let subtitle = parse_subtitle(ASS, SubtitleFormat::Ass);
+    assert_eq!(subtitle.cues.len(), 4);
+    assert!(subtitle.cues[0].comment);
+
+    // 注释和绘图不输出，时间相同的两条合并，Sign 样式的粗体保留
+    let srt = write_subtitle(&subtitle, SubtitleFormat::Srt, true);
+    assert_eq!(
+        srt,
+        "1\n00:00:01,000 --> 00:00:02,500\n<i>斜体</i>，正常\n第二行\n<b>招牌</b>\n\n"
+    );
+    let plain = write_subtitle(&subtitle, SubtitleFormat::Srt, false);
+    assert!(plain.contains("斜体，正常\n第二行\n招牌"));
+
+    let vtt = write_subtitle(&subtitle, SubtitleFormat::Vtt, true);
+    assert!(vtt.starts_with("WEBVTT\n\n00:00:01.000 --> 00:00:02.500\n<i>斜体</i>"));
+}
+
+#[test]
+fn test_vtt_voice_and_entities() {
+    let vtt = "WEBVTT\n\nNOTE 注释\n\nintro\n00:05.000 --> 00:06.000 line:0\n<v 阿库娅>Tom &amp; Jerry &lt;3\n";
+    let subtitle = parse_subtitle(vtt, SubtitleFormat::Vtt);
+    assert_eq!(subtitle.cues.len(), 1);
+    assert_eq!(subtitle.cues[0].actor, "阿库娅");
+    assert_eq!(subtitle.cues[0].text, "Tom & Jerry <3");
+    assert_eq!(
+        write_subtitle(&subtitle, SubtitleFormat::Vtt, true),
+        "WEBVTT\n\n00:00:05.000 --> 00:00:06.000 line:0\nTom &amp; Jerry &lt;3\n\n"
+    );
+}
+
+#[test]
+fn test_ssa_styles() {
+    let subtitle = parse_subtitle(ASS, SubtitleFormat::Ass);
+    let ssa = write_subtitle(&subtitle, SubtitleFormat::Ssa, true);
+    assert!(ssa.contains("[V4 Styles]"));
+    // 顶部居中在 SSA 中为 6，OutlineColour 写为 TertiaryColour
+    assert!(ssa.contains(
+        "Style: Sign,Arial,40,&H00FFFFFF,&H000000FF,&H00112233,&H00000000,-1,0,1,2,0,6,10,10,10,0,1"
+    ));
+    assert!(ssa.contains("Dialogue: Marked=0,0:00:01.00,0:00:02.50,Sign,"));
+
+    let back = parse_subtitle(&ssa, SubtitleFormat::Ssa);
+    let ass = write_subtitle(&back, SubtitleFormat::Ass, true);
+    assert!(ass.contains("Style: Sign,Arial,40,&H00FFFFFF,&H000000FF,&H00112233,&H00000000,-1,0,0,0,100,100,0,0,1,2,0,8,10,10,10,1"));
+    assert!(ass.contains("PlayResX: 1280"));
+}
+
+#[test]
+fn test_convert_skips_unrecognized_encoding() {
+    let korean = EUC_KR
+        .encode("1\n00:00:01,000 --> 00:00:03,000\n안녕하세요 반갑습니다\n")
+        .0
+        .into_owned();
+    let root = TempDir::new("korean");
+    let path = root.join("01.srt");
+    fs::write(&path, &korean).unwrap();
+
+    // 编码只是猜测时不生成转换后的字幕
+    let report = convert_subtitle_file(&path.to_string_lossy(), SubtitleFormat::Ass, true, true);
+    assert!(report.target.is_none());
+    assert_eq!(report.error.as_deref(), Some("无法识别字幕编码，未转换"));
+    assert!(!path.with_extension("ass").exists());
+}
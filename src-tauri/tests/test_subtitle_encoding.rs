@@ -0,0 +1,76 @@
+use std::fs;
+
+use app_lib::{
+    subtitle_encoding::{convert_bytes, convert_file, detect_encoding},
+    types::SubtitleEncodingTarget,
+};
+use encoding_rs::{BIG5, EUC_KR, GBK, SHIFT_JIS, WINDOWS_1252};
+
+use crate::common::TempDir;
+
+const DIALOGUE_CN: &str = "1\n00:00:01,000 --> 00:00:03,000\n我们要去哪里？这就是魔法的力量。\n";
+const DIALOGUE_TW: &str = "1\n00:00:01,000 --> 00:00:03,000\n我們要去哪裡？這就是魔法的力量。\n";
+const DIALOGUE_JP: &str = "1\n00:00:01,000 --> 00:00:03,000\nどこへ行くの？これが魔法の力だ。\n";
+
+#[test]
+fn test_detect_encoding() {
+    let cases = [
+        (GBK.encode(DIALOGUE_CN).0.into_owned(), "gb18030"),
+        (BIG5.encode(DIALOGUE_TW).0.into_owned(), "Big5"),
+        (SHIFT_JIS.encode(DIALOGUE_JP).0.into_owned(), "Shift_JIS"),
+        (DIALOGUE_CN.as_bytes().to_vec(), "UTF-8"),
+        (
+            [b"\xEF\xBB\xBF", DIALOGUE_CN.as_bytes()].concat(),
+            "UTF-8 BOM",
+        ),
+        ([b"\xFF\xFE".as_slice(), &[b'1', 0]].concat(), "UTF-16LE"),
+    ];
+    for (bytes, expected) in cases {
+        assert_eq!(detect_encoding(&bytes).name(), expected);
+    }
+}
+
+#[test]
+fn test_convert_bytes() {
+    let gbk = GBK.encode(DIALOGUE_CN).0.into_owned();
+    let utf8 = convert_bytes(&gbk, SubtitleEncodingTarget::Utf8).unwrap();
+    assert_eq!(utf8, DIALOGUE_CN.as_bytes());
+
+    let with_bom = convert_bytes(&utf8, SubtitleEncodingTarget::Utf8Bom).unwrap();
+    assert!(with_bom.starts_with(b"\xEF\xBB\xBF"));
+    assert_eq!(&with_bom[3..], DIALOGUE_CN.as_bytes());
+
+    // 已是目标编码时不改写
+    assert!(convert_bytes(&utf8, SubtitleEncodingTarget::Utf8).is_none());
+    assert!(convert_bytes(&with_bom, SubtitleEncodingTarget::Utf8Bom).is_none());
+    assert_eq!(
+        convert_bytes(&with_bom, SubtitleEncodingTarget::Utf8).unwrap(),
+        DIALOGUE_CN.as_bytes()
+    );
+}
+
+#[test]
+fn test_unrecognized_encoding_not_rewritten() {
+    let latin = WINDOWS_1252
+        .encode("1\n00:00:01,000 --> 00:00:03,000\nPokémon Détective\n")
+        .0
+        .into_owned();
+    let korean = EUC_KR
+        .encode("1\n00:00:01,000 --> 00:00:03,000\n안녕하세요 반갑습니다\n")
+        .0
+        .into_owned();
+    let broken = [GBK.encode(DIALOGUE_CN).0.as_ref(), b"\xFF\xFF"].concat();
+    for bytes in [&latin, &korean] {
+        assert!(!detect_encoding(bytes).is_reliable());
+    }
+    assert!(detect_encoding(&broken).lossy);
+
+    // 猜测的编码不改写原文件
+    let root = TempDir::new("latin");
+    let path = root.join("01.srt");
+    fs::write(&path, &latin).unwrap();
+    let report = convert_file(&path.to_string_lossy(), SubtitleEncodingTarget::Utf8);
+    assert!(!report.converted);
+    assert!(report.error.is_some());
+    assert_eq!(fs::read(&path).unwrap(), latin);
+}
@@ -0,0 +1,110 @@
+use std::fs;
+
+use app_lib::{
+    subtitle_timing::{preview_changes, retime_file, retime_text},
+    types::{RetimeOptions, SubtitleFormat},
+};
+use encoding_rs::WINDOWS_1252;
+
+use crate::common::TempDir;
+
+const SRT: &str = "1\r\n00:00:01,000 --> 00:00:03,000\r\n<i>你好</i>\r\n\r\n2\r\n00:01:00,000 --> 00:01:02,500 X1:10\r\n再见\r\n";
+
+const ASS: &str = "[Script Info]
+ScriptType: v4.00+
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Comment: 0,0:00:00.50,0:00:01.00,Default,,0,0,0,,校对
+Dialogue: 0,0:00:10.00,0:00:12.00,Default,,0,0,0,,{\\move(0,0,10,10,0,500)}A, B
+";
+
+fn offset(offset_ms: i64) -> RetimeOptions {
+    RetimeOptions {
+        offset_ms,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_shift_srt() {
+    let (text, cues, clamped) = retime_text(SRT, SubtitleFormat::Srt, &offset(-1500)).unwrap();
+    assert_eq!((cues, clamped), (2, 1));
+    assert_eq!(
+        text,
+        "1\r\n00:00:00,000 --> 00:00:01,500\r\n<i>你好</i>\r\n\r\n2\r\n00:00:58,500 --> 00:01:01,000 X1:10\r\n再见\r\n"
+    );
+}
+
+#[test]
+fn test_retime_framerate() {
+    // 25fps 的字幕用在 23.976fps 的视频上，时间按 25/23.976 拉长
+    let options = RetimeOptions {
+        offset_ms: 0,
+        from_fps: Some(25.0),
+        to_fps: Some(23.976),
+    };
+    let preview = preview_changes(SRT, SubtitleFormat::Srt, &options).unwrap();
+    assert_eq!(preview.len(), 2);
+    assert_eq!(preview[0].text, "你好");
+    assert_eq!((preview[1].start, preview[1].new_start), (60_000, 62_563));
+
+    let bad = RetimeOptions {
+        from_fps: Some(0.0),
+        to_fps: Some(25.0),
+        ..Default::default()
+    };
+    assert!(retime_text(SRT, SubtitleFormat::Srt, &bad).is_err());
+}
+
+#[test]
+fn test_shift_ass_keeps_other_fields() {
+    let (text, cues, _) = retime_text(ASS, SubtitleFormat::Ass, &offset(2345)).unwrap();
+    assert_eq!(cues, 2);
+    assert!(text.starts_with("[Script Info]\nScriptType: v4.00+\n"));
+    assert!(text.contains("Comment: 0,0:00:02.85,0:00:03.35,Default,,0,0,0,,校对\n"));
+    assert!(text.contains(
+        "Dialogue: 0,0:00:12.35,0:00:14.35,Default,,0,0,0,,{\\move(0,0,10,10,0,500)}A, B\n"
+    ));
+
+    let preview = preview_changes(ASS, SubtitleFormat::Ass, &offset(2345)).unwrap();
+    assert_eq!(preview.len(), 1);
+    assert_eq!((preview[0].new_start, preview[0].new_end), (12_345, 14_345));
+}
+
+#[test]
+fn test_shift_ass_byte_for_byte() {
+    // 冒号后无空格、字段两侧带空格、CRLF 换行都原样保留，只改时间
+    let ass = "\u{feff}[Events]\r\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\r\nDialogue:0, 0:00:01.00 ,0:00:02.00,  Default,,0,0,0,, 你好 \r\n  Comment : 0,0:00:03.00,0:00:04.00,Default,,0,0,0,,x\n";
+    let (text, cues, _) = retime_text(ass, SubtitleFormat::Ass, &offset(500)).unwrap();
+    assert_eq!(cues, 2);
+    assert_eq!(
+        text,
+        "\u{feff}[Events]\r\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\r\nDialogue:0, 0:00:01.50 ,0:00:02.50,  Default,,0,0,0,, 你好 \r\n  Comment : 0,0:00:03.50,0:00:04.50,Default,,0,0,0,,x\n"
+    );
+}
+
+#[test]
+fn test_shift_vtt_inline_timestamps() {
+    let vtt = "WEBVTT\n\n00:01.000 --> 00:04.000 align:start\n<00:01.000>一<00:02.000>二\n";
+    let (text, _, _) = retime_text(vtt, SubtitleFormat::Vtt, &offset(1000)).unwrap();
+    assert_eq!(
+        text,
+        "WEBVTT\n\n00:00:02.000 --> 00:00:05.000 align:start\n<00:00:02.000>一<00:00:03.000>二\n"
+    );
+}
+
+#[test]
+fn test_retime_file_skips_unrecognized_encoding() {
+    let bytes = WINDOWS_1252
+        .encode("1\n00:00:01,000 --> 00:00:03,000\nPokémon Détective\n")
+        .0
+        .into_owned();
+    let root = TempDir::new("retime");
+    let path = root.join("01.srt");
+    fs::write(&path, &bytes).unwrap();
+    let report = retime_file(&path.to_string_lossy(), &offset(1000), false);
+    assert!(report.error.is_some());
+    assert!(!report.applied);
+    assert_eq!(fs::read(&path).unwrap(), bytes);
+}
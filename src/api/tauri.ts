@@ -1,5 +1,6 @@
 import { invoke } from '@tauri-apps/api/core';
-import { FileInfo, LLMRequest, LLMResponse, BatchLLMRequest, BatchLLMResponse } from '../types/llm';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import { FileInfo, LLMRequest, LLMResponse, BatchLLMRequest, BatchLLMResponse, ParsedFilename } from '../types/llm';
 
 // 确保Tauri API可用
 const isTauri = typeof window !== 'undefined' && '__TAURI__' in window;
@@ -9,12 +10,22 @@ export interface DirectoryPickResult {
   canceled: boolean;
 }
 
-// 获取拖放的文件
-export async function getDroppedFiles(paths: string[]): Promise<FileInfo[]> {
+// 扫描文件夹的选项，未传入时使用设置中的值
+export interface ScanOptions {
+  // 向下进入子文件夹的层数，0 表示只扫描所选文件夹
+  max_depth: number;
+  // gitignore 风格的排除规则
+  exclude: string[];
+  follow_symlinks: boolean;
+  include_subtitles: boolean;
+}
+
+// 获取拖放的文件，文件夹按扫描选项展开
+export async function getDroppedFiles(paths: string[], options?: ScanOptions): Promise<FileInfo[]> {
   if (!isTauri) {
     throw new Error('Tauri API 不可用');
   }
-  return invoke('get_dropped_files', { paths });
+  return invoke('get_dropped_files', { paths, options });
 }
 
 // 选择文件
@@ -26,11 +37,11 @@ export async function pickFilesAndGetInfo(): Promise<FileInfo[]> {
 }
 
 // 选择文件夹
-export async function pickDirectoryAndGetInfo(): Promise<DirectoryPickResult> {
+export async function pickDirectoryAndGetInfo(options?: ScanOptions): Promise<DirectoryPickResult> {
   if (!isTauri) {
     throw new Error('Tauri API 不可用');
   }
-  return invoke('pick_directory_and_get_info');
+  return invoke('pick_directory_and_get_info', { options });
 }
 
 // 分析文件名
@@ -41,6 +52,136 @@ export async function analyzeFilename(request: LLMRequest): Promise<LLMResponse>
   return invoke('analyze_filename', { request });
 }
 
+// 命名模板可用的变量
+export interface NamingInfo {
+  title: string;
+  year?: number;
+  season?: number;
+  episode?: string;
+  ep_title?: string;
+  group?: string;
+  codec?: string;
+  resolution?: string;
+  source?: string;
+  crc32?: string;
+  version?: number;
+  // 不带点的扩展名，如 mkv
+  ext?: string;
+}
+
+// 按命名模板生成文件名（可含 / 表示目录），template 为空时使用设置中的默认模板
+export async function renderName(template: string | null, info: NamingInfo): Promise<string> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('render_name', { template, info });
+}
+
+export interface VideoRenameItem {
+  source_path: string;
+  info: NamingInfo;
+  // 随视频一起改名/移动的字幕等附属文件
+  companions?: string[];
+}
+
+export interface VideoRenameRequest {
+  items: VideoRenameItem[];
+  template?: string | null;
+  preset?: MediaServerPreset | null;
+  target_root?: string | null;
+  create_dirs?: boolean;
+}
+
+// 按命名模板或媒体服务器预设预览视频重命名，结果可直接交给 executeRenamePlan
+export async function planVideoRename(request: VideoRenameRequest): Promise<RenamePlan> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('plan_video_rename', { request });
+}
+
+// 清空 LLM 识别缓存，返回清除的条目数
+export async function clearRecognitionCache(): Promise<number> {
+  if (!isTauri) {
+    return 0;
+  }
+  return invoke('clear_recognition_cache');
+}
+
+// 清空元数据与封面缓存，返回删除的文件数
+export async function clearMetadataCache(): Promise<number> {
+  if (!isTauri) {
+    return 0;
+  }
+  return invoke('clear_metadata_cache');
+}
+
+// 返回封面的 data URL，下载后缓存在本地
+export async function getCoverImage(url: string): Promise<string> {
+  if (!isTauri) {
+    return url;
+  }
+  return invoke('get_cover_image', { url });
+}
+
+// 规则解析文件名（不调用 LLM）
+export async function parseAnimeFilename(filename: string): Promise<ParsedFilename> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('parse_anime_filename', { filename });
+}
+
+export interface AnalyzeFilesRequest {
+  job_id: string;
+  files: FileInfo[];
+  model_url: string;
+  model_name: string;
+  // 同时进行的识别数量，未指定时使用设置中的值
+  concurrency?: number;
+  force_llm?: boolean;
+  bypass_cache?: boolean;
+}
+
+export interface AnalyzeProgress {
+  job_id: string;
+  done: number;
+  total: number;
+  file: FileInfo;
+  result: LLMResponse;
+}
+
+export interface AnalyzeFilesSummary {
+  job_id: string;
+  done: number;
+  total: number;
+  succeeded: number;
+  canceled: boolean;
+}
+
+// 并发识别一组文件，进度通过 onAnalyzeProgress 推送
+export async function analyzeFiles(request: AnalyzeFilesRequest): Promise<AnalyzeFilesSummary> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('analyze_files', { request });
+}
+
+// 取消识别任务，返回任务是否存在
+export async function cancelAnalyzeJob(jobId: string): Promise<boolean> {
+  if (!isTauri) {
+    return false;
+  }
+  return invoke('cancel_analyze_job', { jobId });
+}
+
+export async function onAnalyzeProgress(handler: (progress: AnalyzeProgress) => void): Promise<UnlistenFn> {
+  if (!isTauri) {
+    return () => {};
+  }
+  return listen<AnalyzeProgress>('analyze-progress', (event) => handler(event.payload));
+}
+
 // 批量分析文件名
 export async function batchAnalyzeFilenames(request: BatchLLMRequest): Promise<BatchLLMResponse> {
   if (!isTauri) {
@@ -49,10 +190,85 @@ export async function batchAnalyzeFilenames(request: BatchLLMRequest): Promise<B
   return invoke('batch_analyze_filenames', { request });
 }
 
+export const DEFAULT_NAMING_TEMPLATE = '{title}{?.{year}}.S{season:02}E{episode:02}{? - {ep_label} {ep_title}}{?.{group}}{?.{codec}}.{ext}';
+
+export type MediaServerPreset = 'jellyfin' | 'plex' | 'kodi' | 'emby';
+
+export const mediaServerPresetOptions: { label: string; value: MediaServerPreset }[] = [
+  { label: 'Jellyfin', value: 'jellyfin' },
+  { label: 'Plex', value: 'plex' },
+  { label: 'Kodi', value: 'kodi' },
+  { label: 'Emby', value: 'emby' },
+];
+
+export type LlmProviderKind = 'open_ai' | 'ollama' | 'llama_cpp' | 'messages';
+
+// 各接口格式对应的默认地址，模型地址需填写完整的接口路径
+export const llmProviderOptions: { label: string; value: LlmProviderKind; url: string }[] = [
+  { label: 'OpenAI 兼容', value: 'open_ai', url: 'http://localhost:11434/v1/chat/completions' },
+  { label: 'Ollama', value: 'ollama', url: 'http://localhost:11434/api/chat' },
+  { label: 'llama.cpp', value: 'llama_cpp', url: 'http://localhost:8080/completion' },
+  { label: 'Messages', value: 'messages', url: 'https://api.anthropic.com/v1/messages' },
+];
+
+export type MetadataProviderKind = 'bangumi' | 'anilist' | 'tmdb' | 'anidb';
+
+export const metadataProviderOptions: { label: string; value: MetadataProviderKind }[] = [
+  { label: 'Bangumi', value: 'bangumi' },
+  { label: 'AniList', value: 'anilist' },
+  { label: 'TMDB', value: 'tmdb' },
+  { label: 'AniDB', value: 'anidb' },
+];
+
 export interface Settings {
   episode_regex: string;
   model_url: string;
   model_name: string;
+  // 规则解析置信度达到该值时不再调用 LLM
+  parser_confidence_threshold: number;
+  // 视频重命名使用的默认命名模板
+  naming_template: string;
+  // 选择预设时忽略 naming_template
+  naming_preset?: MediaServerPreset | null;
+  // 媒体库根目录，留空则在视频所在目录下整理
+  media_library_root?: string | null;
+  // LLM 识别缓存的有效期（天，0 表示不过期）与最大条目数
+  llm_cache_ttl_days: number;
+  llm_cache_max_entries: number;
+  // 批量识别时同时请求 LLM 的数量
+  llm_concurrency: number;
+  llm_provider: LlmProviderKind;
+  // 为空时不发送认证头
+  llm_api_key: string;
+  // 请求时附带输出 JSON Schema，兼容接口不支持时可关闭
+  llm_structured_output: boolean;
+  // LLM 与 Bangumi 等接口的请求超时（秒）
+  llm_timeout_secs: number;
+  api_timeout_secs: number;
+  // 连接失败、429、5xx 时的最大重试次数与首次重试等待（毫秒，之后逐次翻倍）
+  http_max_retries: number;
+  http_retry_base_ms: number;
+  // 搜索条目和分集标题使用的元数据来源
+  metadata_provider: MetadataProviderKind;
+  // 各来源的接口地址，可改为本地模拟服务或反向代理
+  bangumi_api_url: string;
+  anilist_api_url: string;
+  tmdb_api_url: string;
+  // TMDB v3 API Key 或 v4 读取令牌
+  tmdb_api_key: string;
+  anidb_api_url: string;
+  // AniDB HTTP API 需要注册的客户端名称与版本
+  anidb_client: string;
+  anidb_client_version: number;
+  // 元数据缓存的有效期（天，0 表示不过期）；过期后仍会在请求失败时使用
+  metadata_cache_ttl_days: number;
+  // 离线模式下只读取缓存，不请求元数据接口
+  offline_mode: boolean;
+  // 选择或拖入文件夹时的扫描选项
+  scan_max_depth: number;
+  scan_exclude: string[];
+  scan_follow_symlinks: boolean;
+  scan_include_subtitles: boolean;
 }
 
 export async function loadSettings(): Promise<Settings> {
@@ -61,6 +277,34 @@ export async function loadSettings(): Promise<Settings> {
       episode_regex: '\\[(\\d{2})\\]',
       model_url: 'http://localhost:11434/v1/chat/completions',
       model_name: 'qwen/qwen3-vl-8b',
+      parser_confidence_threshold: 0.8,
+      naming_template: DEFAULT_NAMING_TEMPLATE,
+      naming_preset: null,
+      media_library_root: null,
+      llm_cache_ttl_days: 30,
+      llm_cache_max_entries: 5000,
+      llm_concurrency: 2,
+      llm_provider: 'open_ai',
+      llm_api_key: '',
+      llm_structured_output: true,
+      llm_timeout_secs: 300,
+      api_timeout_secs: 10,
+      http_max_retries: 3,
+      http_retry_base_ms: 1000,
+      metadata_provider: 'bangumi',
+      bangumi_api_url: 'https://api.bgm.tv',
+      anilist_api_url: 'https://graphql.anilist.co',
+      tmdb_api_url: 'https://api.themoviedb.org/3',
+      tmdb_api_key: '',
+      anidb_api_url: 'http://api.anidb.net:9001/httpapi',
+      anidb_client: '',
+      anidb_client_version: 1,
+      metadata_cache_ttl_days: 7,
+      offline_mode: false,
+      scan_max_depth: 3,
+      scan_exclude: ['SPs/', 'CDs/', 'Scans/', '*sample*'],
+      scan_follow_symlinks: false,
+      scan_include_subtitles: true,
     };
   }
   return invoke('load_settings');
@@ -73,33 +317,453 @@ export async function saveSettings(settings: Settings): Promise<boolean> {
   return invoke('save_settings', { settings });
 }
 
-export interface BangumiSubject {
+export interface MetadataSubject {
   id: number;
   name: string;
   name_cn?: string;
-  subject_type?: number;
+  type?: number;
   date?: string;
+  platform?: string;
 }
 
-export async function searchBangumiSubjects(query: string, limit = 10): Promise<BangumiSubject[]> {
+// provider 为空时使用设置中选择的元数据来源
+export async function searchMetadata(query: string, limit = 10, provider?: MetadataProviderKind): Promise<MetadataSubject[]> {
   if (!isTauri) {
     return [];
   }
-  return invoke('search_bangumi_subjects', { query, limit });
+  return invoke('search_metadata', { provider, query, limit });
+}
+
+export interface MetadataTag {
+  name: string;
+  count: number;
+}
+
+// type: 0 本篇，1 SP，2 OP，3 ED，4 预告/宣传/广告，5 MAD，6 其他
+export interface MetadataEpisode {
+  id: number;
+  type: number;
+  sort: number;
+  ep?: number;
+  // 条目包含多季时（如 TMDB 剧集）的季号
+  season?: number;
+  name?: string;
+  name_cn?: string;
+  airdate?: string;
 }
 
-export interface BangumiSubjectDetail {
+export interface MetadataSubjectDetail {
   id: number;
   name: string;
   name_cn?: string;
   cover_url?: string;
   episodes?: number;
   year?: number;
+  aliases: string[];
+  tags: MetadataTag[];
+  platform?: string;
+  air_date?: string;
+  episode_list: MetadataEpisode[];
+}
+
+export async function getMetadataDetail(id: number, includeEpisodes = false, provider?: MetadataProviderKind): Promise<MetadataSubjectDetail> {
+  if (!isTauri) {
+    return { id, name: String(id), aliases: [], tags: [], episode_list: [] };
+  }
+  return invoke('get_metadata_detail', { provider, id, includeEpisodes });
+}
+
+export async function getMetadataEpisodes(id: number, provider?: MetadataProviderKind): Promise<MetadataEpisode[]> {
+  if (!isTauri) {
+    return [];
+  }
+  return invoke('get_metadata_episodes', { provider, id });
+}
+
+// score 为 0-1 的匹配度；year_score / episode_score 为空表示缺少信息未参与比较
+export interface ScoredCandidate {
+  detail: MetadataSubjectDetail;
+  score: number;
+  title_score: number;
+  matched_title: string;
+  year_score?: number;
+  episode_score?: number;
+}
+
+// selected 为可自动选择的条目 ID，存在歧义时为空
+export interface CandidateRanking {
+  candidates: ScoredCandidate[];
+  selected?: number;
+}
+
+export async function rankMetadataCandidates(title: string, filenames: string[], limit = 5, provider?: MetadataProviderKind): Promise<CandidateRanking> {
+  if (!isTauri) {
+    return { candidates: [] };
+  }
+  return invoke('rank_metadata_candidates', { provider, title, filenames, limit });
+}
+
+export interface EpisodeTitleQuery {
+  episode: string;
+  special?: boolean;
+  season?: number;
+}
+
+export interface EpisodeTitle {
+  episode: string;
+  special: boolean;
+  sort?: number;
+  name?: string;
+  name_cn?: string;
+}
+
+export async function lookupEpisodeTitles(subjectId: number, episodes: EpisodeTitleQuery[], provider?: MetadataProviderKind): Promise<EpisodeTitle[]> {
+  if (!isTauri) {
+    return episodes.map(e => ({ episode: e.episode, special: !!e.special }));
+  }
+  return invoke('lookup_episode_titles', { provider, subjectId, episodes });
 }
 
-export async function getBangumiSubjectDetail(id: number): Promise<BangumiSubjectDetail> {
+export interface SeasonBoundary {
+  subject_id: number;
+  season: number;
+  name: string;
+  name_cn?: string;
+  first_episode: number;
+  episodes: number;
+}
+
+export interface EpisodeMapping {
+  episode: string;
+  subject_id?: number;
+  season?: number;
+  seasonal_episode?: string;
+  absolute?: number;
+}
+
+export async function getSeasonMap(subjectId: number): Promise<SeasonBoundary[]> {
+  if (!isTauri) {
+    return [];
+  }
+  return invoke('get_season_map', { subjectId });
+}
+
+export async function mapEpisodeNumbers(subjectId: number, episodes: string[]): Promise<EpisodeMapping[]> {
   if (!isTauri) {
-    return { id, name: String(id) } as BangumiSubjectDetail;
+    return episodes.map(episode => ({ episode }));
+  }
+  return invoke('map_episode_numbers', { subjectId, episodes });
+}
+
+export type RenameProblem = 'missing_source' | 'target_exists' | 'duplicate_target' | 'invalid_filename' | 'missing_target_dir';
+
+export interface RenamePlanItem {
+  source_name: string;
+  source_path: string;
+  target_name: string;
+  target_path: string;
+  problems: RenameProblem[];
+}
+
+export interface RenamePlan {
+  items: RenamePlanItem[];
+  valid: boolean;
+  // 执行时自动创建缺失的目标目录
+  create_dirs?: boolean;
+  // 执行后转换字幕文件的编码；撤销重命名时不会还原编码
+  subtitle_encoding?: SubtitleEncodingTarget | null;
+  // 执行后在字幕旁生成该格式的副本
+  subtitle_format?: SubtitleFormat | null;
+}
+
+export interface SubtitleTrack {
+  video_index: number;
+  subtitle: FileInfo;
+  suffix?: string;
+}
+
+export interface RenameRequest {
+  video_files: FileInfo[];
+  subtitle_files: FileInfo[];
+  suffix: string;
+  tracks?: SubtitleTrack[];
+  keep_language_tag?: boolean;
+  // 重命名后把字幕转为 UTF-8，为空时不转换
+  subtitle_encoding?: SubtitleEncodingTarget | null;
+  // 重命名后另存为该格式，原字幕保留，为空时不转换
+  subtitle_format?: SubtitleFormat | null;
+}
+
+export type SubtitleEncodingTarget = 'utf8' | 'utf8_bom';
+
+export const subtitleEncodingOptions: { value: SubtitleEncodingTarget; label: string }[] = [
+  { value: 'utf8', label: 'UTF-8' },
+  { value: 'utf8_bom', label: 'UTF-8 (BOM)' },
+];
+
+// detected 为检测到的编码名称，如 gb18030、Big5、UTF-8 BOM；已是目标编码时 converted 为 false
+export interface EncodingConversion {
+  path: string;
+  detected?: string;
+  converted: boolean;
+  error?: string;
+}
+
+// 检测字幕编码并转为 UTF-8，target 为空时不带 BOM
+export async function convertSubtitleEncoding(paths: string[], target?: SubtitleEncodingTarget): Promise<EncodingConversion[]> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('convert_subtitle_encoding', { paths, target });
+}
+
+export type SubtitleFormat = 'srt' | 'ass' | 'ssa' | 'vtt';
+
+export const subtitleFormatOptions: { value: SubtitleFormat; label: string }[] = [
+  { value: 'srt', label: 'SRT' },
+  { value: 'ass', label: 'ASS' },
+  { value: 'ssa', label: 'SSA' },
+  { value: 'vtt', label: 'WebVTT' },
+];
+
+// target 为生成的文件路径，cues 为字幕条数
+export interface SubtitleConversion {
+  source: string;
+  target?: string;
+  cues: number;
+  error?: string;
+}
+
+// 在原字幕旁生成目标格式的文件；keepStyles 为 false 时 SRT/WebVTT 只保留纯文本
+export async function convertSubtitles(
+  paths: string[],
+  targetFormat: SubtitleFormat,
+  keepStyles?: boolean,
+  overwrite?: boolean,
+): Promise<SubtitleConversion[]> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('convert_subtitles', { paths, targetFormat, keepStyles, overwrite });
+}
+
+export type ChineseConversion = 's2t' | 't2s';
+
+export const chineseConversionOptions: { value: ChineseConversion; label: string }[] = [
+  { value: 's2t', label: '简体 → 繁体' },
+  { value: 't2s', label: '繁体 → 简体' },
+];
+
+// target 为生成的文件路径，changed 为改动的字数
+export interface ScriptConversion {
+  source: string;
+  target?: string;
+  changed: number;
+  error?: string;
+}
+
+// 离线简繁转换，在原字幕旁生成 .cht / .chs 文件，ASS 只转换对白文本
+export async function convertChineseScript(
+  paths: string[],
+  direction: ChineseConversion,
+  overwrite?: boolean,
+): Promise<ScriptConversion[]> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('convert_chinese_script', { paths, direction, overwrite });
+}
+
+// name 为包内以 / 分隔的完整路径
+export interface ArchiveEntry {
+  name: string;
+  size: number;
+  is_subtitle: boolean;
+}
+
+// target 为解压到的文件路径
+export interface ArchiveExtraction {
+  entry: string;
+  target?: string;
+  error?: string;
+}
+
+// 列出字幕包中的文件，zip 中的 GBK 文件名会自动识别
+export async function listArchiveEntries(path: string): Promise<ArchiveEntry[]> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('list_archive_entries', { path });
+}
+
+// 解压选中的文件，不保留包内的文件夹；entries 为空时解压全部字幕，destDir 默认为压缩包所在文件夹
+export async function extractArchiveSubtitles(
+  path: string,
+  entries?: string[],
+  destDir?: string,
+  overwrite?: boolean,
+): Promise<ArchiveExtraction[]> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('extract_archive_subtitles', { path, entries, destDir, overwrite });
+}
+
+// 先按帧率换算再加偏移；from_fps 为字幕原本对应的帧率，to_fps 为当前视频的帧率
+export interface RetimeOptions {
+  offset_ms: number;
+  from_fps?: number | null;
+  to_fps?: number | null;
+}
+
+export const framerateOptions = [23.976, 24, 25, 29.97, 30].map((fps) => ({ value: fps, label: `${fps} fps` }));
+
+// 单位均为毫秒
+export interface CueTimingChange {
+  text: string;
+  start: number;
+  end: number;
+  new_start: number;
+  new_end: number;
+}
+
+// clamped 为提前到 0 之前被截断的字幕条数
+export interface RetimeReport {
+  path: string;
+  video?: string;
+  cues: number;
+  clamped: number;
+  preview: CueTimingChange[];
+  applied: boolean;
+  error?: string;
+}
+
+// 调整字幕时间轴，dryRun 为 true 时只返回前几条字幕的预览
+export async function retimeSubtitles(paths: string[], options: RetimeOptions, dryRun?: boolean): Promise<RetimeReport[]> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('retime_subtitles', { paths, options, dryRun });
+}
+
+// 按重命名的配对批量调整时间轴
+export async function retimePairedSubtitles(request: RenameRequest, options: RetimeOptions, dryRun?: boolean): Promise<RetimeReport[]> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('retime_paired_subtitles', { request, options, dryRun });
+}
+
+export interface RenameOperation {
+  from_path: string;
+  to_path: string;
+  // 执行时新建的目录
+  created_dirs?: string[];
+}
+
+export interface RenameResponse {
+  success: boolean;
+  message: string;
+  renamed_files: string[];
+  rolled_back: RenameOperation[];
+  rollback_errors: string[];
+  batch_id?: number;
+  // 重命名后转换字幕编码的结果
+  encoding_report: EncodingConversion[];
+  // 重命名后转换字幕格式的结果
+  subtitle_conversions: SubtitleConversion[];
+}
+
+export const renameProblemText: Record<RenameProblem, string> = {
+  missing_source: '源文件不存在',
+  target_exists: '目标已存在',
+  duplicate_target: '目标重复',
+  invalid_filename: '文件名不合法',
+  missing_target_dir: '目标目录不存在',
+};
+
+// 预览重命名计划
+export async function planRename(request: RenameRequest): Promise<RenamePlan> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('plan_rename', { request });
+}
+
+// 执行重命名计划
+export async function executeRenamePlan(plan: RenamePlan): Promise<RenameResponse> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('execute_rename_plan', { plan });
+}
+
+export type RenameBatchStatus = 'applied' | 'undone';
+
+export interface RenameBatch {
+  id: number;
+  created_at: number;
+  description: string;
+  operations: RenameOperation[];
+  // 额外生成的文件，撤销时删除
+  created_files?: string[];
+  status: RenameBatchStatus;
+}
+
+// 重命名历史（最新在前）
+export async function listRenameHistory(): Promise<RenameBatch[]> {
+  if (!isTauri) {
+    return [];
+  }
+  return invoke('list_rename_history');
+}
+
+export async function undoRenameBatch(id: number): Promise<RenameResponse> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('undo_rename_batch', { id });
+}
+
+export async function redoRenameBatch(id: number): Promise<RenameResponse> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
+  }
+  return invoke('redo_rename_batch', { id });
+}
+
+export interface EpisodePair {
+  episode: string;
+  video: FileInfo;
+  subtitles: FileInfo[];
+}
+
+export interface AmbiguousEpisode {
+  episode: string;
+  videos: FileInfo[];
+  subtitles: FileInfo[];
+}
+
+export interface UnmatchedFile {
+  episode?: string;
+  file: FileInfo;
+}
+
+export interface EpisodeMatchResult {
+  pairs: EpisodePair[];
+  unmatched_videos: UnmatchedFile[];
+  unmatched_subtitles: UnmatchedFile[];
+  ambiguous: AmbiguousEpisode[];
+}
+
+// 按集数配对视频与字幕
+export async function matchEpisodes(
+  videoFiles: FileInfo[],
+  subtitleFiles: FileInfo[],
+  episodeRegex?: string
+): Promise<EpisodeMatchResult> {
+  if (!isTauri) {
+    throw new Error('Tauri API 不可用');
   }
-  return invoke('get_bangumi_subject_detail', { id });
+  return invoke('match_episodes', { videoFiles, subtitleFiles, episodeRegex });
 }
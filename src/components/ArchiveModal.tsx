@@ -0,0 +1,169 @@
+import React, { useEffect, useState } from 'react';
+import { Alert, Button, Checkbox, Empty, List, Modal, Space, Spin, Tag, Typography, message } from 'antd';
+import { ArchiveEntry, ArchiveExtraction, extractArchiveSubtitles, listArchiveEntries } from '../api/tauri';
+import { FileInfo } from '../types/llm';
+
+interface ArchiveModalProps {
+  // 待解压的字幕包，为空时关闭
+  archives: FileInfo[];
+  // 解压到的文件夹，为空时解压到字幕包所在文件夹
+  destDir: string | null;
+  onClose: () => void;
+  onExtracted: (files: FileInfo[]) => void;
+}
+
+interface ArchiveListing {
+  archive: FileInfo;
+  entries: ArchiveEntry[];
+  selected: string[];
+  error?: string;
+  results?: ArchiveExtraction[];
+}
+
+const fileName = (path: string) => path.split(/[\\/]/).pop() || path;
+
+const formatSize = (size: number) => (size >= 1024 * 1024 ? `${(size / 1024 / 1024).toFixed(1)} MB` : `${Math.ceil(size / 1024)} KB`);
+
+export default function ArchiveModal({ archives, destDir, onClose, onExtracted }: ArchiveModalProps) {
+  const [listings, setListings] = useState<ArchiveListing[]>([]);
+  const [loading, setLoading] = useState(false);
+  const [busy, setBusy] = useState(false);
+
+  // 只列出字幕文件，默认全部选中
+  useEffect(() => {
+    if (archives.length === 0) return;
+    let canceled = false;
+    setLoading(true);
+    Promise.all(
+      archives.map(async (archive): Promise<ArchiveListing> => {
+        try {
+          const entries = (await listArchiveEntries(archive.path)).filter((e) => e.is_subtitle);
+          return { archive, entries, selected: entries.map((e) => e.name) };
+        } catch (e) {
+          return { archive, entries: [], selected: [], error: String(e) };
+        }
+      }),
+    )
+      .then((result) => {
+        if (!canceled) setListings(result);
+      })
+      .finally(() => {
+        if (!canceled) setLoading(false);
+      });
+    return () => {
+      canceled = true;
+    };
+  }, [archives]);
+
+  const setSelected = (index: number, selected: string[]) => {
+    setListings((prev) => prev.map((l, i) => (i === index ? { ...l, selected } : l)));
+  };
+
+  const selectedCount = listings.reduce((n, l) => n + l.selected.length, 0);
+
+  const extract = async () => {
+    setBusy(true);
+    const extracted: FileInfo[] = [];
+    const next: ArchiveListing[] = [];
+    for (const listing of listings) {
+      if (listing.selected.length === 0) {
+        next.push(listing);
+        continue;
+      }
+      try {
+        const results = await extractArchiveSubtitles(listing.archive.path, listing.selected, destDir ?? undefined);
+        results
+          .filter((r) => r.target)
+          .forEach((r) => extracted.push({ name: fileName(r.target!), path: r.target!, is_video: false }));
+        next.push({ ...listing, results });
+      } catch (e) {
+        next.push({ ...listing, error: String(e) });
+      }
+    }
+    setListings(next);
+    setBusy(false);
+    if (extracted.length) onExtracted(extracted);
+    const failed = next.some((l) => l.error || l.results?.some((r) => r.error));
+    if (failed) {
+      message.warning(`已解压${extracted.length}个字幕，部分文件失败`);
+    } else {
+      message.success(`已解压${extracted.length}个字幕`);
+      onClose();
+    }
+  };
+
+  return (
+    <Modal
+      open={archives.length > 0}
+      title="解压字幕包"
+      onCancel={onClose}
+      width={720}
+      footer={[
+        <Button key="cancel" onClick={onClose}>
+          关闭
+        </Button>,
+        <Button key="extract" type="primary" loading={busy} disabled={loading || selectedCount === 0} onClick={extract}>
+          解压{selectedCount}个字幕
+        </Button>,
+      ]}
+    >
+      <Alert
+        type="info"
+        showIcon
+        style={{ marginBottom: 12 }}
+        message={`解压到 ${destDir ?? '字幕包所在文件夹'}，不保留包内的文件夹；不同文件夹中的同名字幕会在扩展名前加上文件夹名`}
+      />
+      <Spin spinning={loading}>
+        <div style={{ maxHeight: 440, overflowY: 'auto' }}>
+          {listings.map((listing, index) => {
+            const errors = new Map((listing.results ?? []).filter((r) => r.error).map((r) => [r.entry, r.error]));
+            const done = new Set((listing.results ?? []).filter((r) => r.target).map((r) => r.entry));
+            return (
+              <div key={listing.archive.path} style={{ marginBottom: 12 }}>
+                <Space style={{ marginBottom: 4 }}>
+                  <Checkbox
+                    checked={listing.entries.length > 0 && listing.selected.length === listing.entries.length}
+                    indeterminate={listing.selected.length > 0 && listing.selected.length < listing.entries.length}
+                    disabled={listing.entries.length === 0}
+                    onChange={(e) => setSelected(index, e.target.checked ? listing.entries.map((en) => en.name) : [])}
+                  />
+                  <Typography.Text strong ellipsis style={{ maxWidth: 560 }}>{listing.archive.name}</Typography.Text>
+                  {listing.error && <Tag color="error">{listing.error}</Tag>}
+                </Space>
+                {listing.entries.length === 0 && !listing.error ? (
+                  <Empty image={Empty.PRESENTED_IMAGE_SIMPLE} description="包内没有字幕文件" />
+                ) : (
+                  <List<ArchiveEntry>
+                    size="small"
+                    bordered
+                    dataSource={listing.entries}
+                    renderItem={(entry) => (
+                      <List.Item>
+                        <Checkbox
+                          checked={listing.selected.includes(entry.name)}
+                          onChange={(e) =>
+                            setSelected(
+                              index,
+                              e.target.checked ? [...listing.selected, entry.name] : listing.selected.filter((n) => n !== entry.name),
+                            )
+                          }
+                        >
+                          <Typography.Text ellipsis style={{ maxWidth: 440 }}>{entry.name}</Typography.Text>
+                        </Checkbox>
+                        <Space>
+                          {done.has(entry.name) && <Tag color="success">已解压</Tag>}
+                          {errors.has(entry.name) && <Tag color="error">{errors.get(entry.name)}</Tag>}
+                          <Typography.Text type="secondary">{formatSize(entry.size)}</Typography.Text>
+                        </Space>
+                      </List.Item>
+                    )}
+                  />
+                )}
+              </div>
+            );
+          })}
+        </div>
+      </Spin>
+    </Modal>
+  );
+}
@@ -0,0 +1,38 @@
+import React, { useEffect, useState } from 'react';
+import { getCoverImage } from '../api/tauri';
+
+interface CoverImageProps {
+  url?: string;
+  alt: string;
+}
+
+// 同一封面在页面内只请求一次，后端负责磁盘缓存
+const loaded = new Map<string, Promise<string>>();
+
+const placeholder = <div style={{ width: '100%', height: '100%', background: 'var(--ant-color-fill-tertiary)' }} />;
+
+export default function CoverImage({ url, alt }: CoverImageProps) {
+  const [src, setSrc] = useState<string | null>(null);
+
+  useEffect(() => {
+    setSrc(null);
+    if (!url) return;
+    let canceled = false;
+    let pending = loaded.get(url);
+    if (!pending) {
+      pending = getCoverImage(url);
+      loaded.set(url, pending);
+      pending.catch(() => loaded.delete(url));
+    }
+    pending
+      .then((data) => {
+        if (!canceled) setSrc(data);
+      })
+      .catch(() => {});
+    return () => {
+      canceled = true;
+    };
+  }, [url]);
+
+  return src ? <img src={src} alt={alt} /> : placeholder;
+}
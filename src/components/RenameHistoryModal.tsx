@@ -0,0 +1,97 @@
+import React, { useEffect, useState } from 'react';
+import { Button, List, Modal, Space, Tag, Typography, message } from 'antd';
+import { listRenameHistory, redoRenameBatch, undoRenameBatch, RenameBatch, RenameOperation } from '../api/tauri';
+
+interface RenameHistoryModalProps {
+  open: boolean;
+  onClose: () => void;
+  // 撤销/重做成功后回传实际执行的操作，便于页面同步文件路径
+  onChanged?: (ops: RenameOperation[]) => void;
+}
+
+const formatTime = (secs: number) => new Date(secs * 1000).toLocaleString();
+
+export default function RenameHistoryModal({ open, onClose, onChanged }: RenameHistoryModalProps) {
+  const [batches, setBatches] = useState<RenameBatch[]>([]);
+  const [busyId, setBusyId] = useState<number | null>(null);
+
+  const refresh = async () => {
+    try {
+      setBatches(await listRenameHistory());
+    } catch (e) {
+      message.error(`读取重命名历史失败: ${e}`);
+    }
+  };
+
+  useEffect(() => {
+    if (open) refresh();
+  }, [open]);
+
+  const handleToggle = async (batch: RenameBatch) => {
+    setBusyId(batch.id);
+    try {
+      const undo = batch.status === 'applied';
+      const response = undo ? await undoRenameBatch(batch.id) : await redoRenameBatch(batch.id);
+      if (response.success) {
+        const ops = undo
+          ? [...batch.operations].reverse().map((op) => ({ from_path: op.to_path, to_path: op.from_path }))
+          : batch.operations;
+        onChanged?.(ops);
+        message.success(response.message);
+      } else {
+        message.error(response.message);
+      }
+      await refresh();
+    } catch (e) {
+      message.error(`操作失败: ${e}`);
+    } finally {
+      setBusyId(null);
+    }
+  };
+
+  return (
+    <Modal open={open} title="重命名历史" footer={null} onCancel={onClose} width={720}>
+      <List<RenameBatch>
+        size="small"
+        locale={{ emptyText: '暂无重命名记录' }}
+        dataSource={batches}
+        style={{ maxHeight: 480, overflowY: 'auto' }}
+        renderItem={(batch) => (
+          <List.Item
+            actions={[
+              <Button
+                key="toggle"
+                size="small"
+                loading={busyId === batch.id}
+                onClick={() => handleToggle(batch)}
+              >
+                {batch.status === 'applied' ? '撤销' : '重做'}
+              </Button>,
+            ]}
+          >
+            <Space orientation="vertical" size={2} style={{ width: '100%', minWidth: 0 }}>
+              <Space>
+                <Typography.Text strong>{batch.description}</Typography.Text>
+                <Tag color={batch.status === 'applied' ? 'green' : 'default'}>
+                  {batch.status === 'applied' ? '已应用' : '已撤销'}
+                </Tag>
+                <Typography.Text type="secondary">{formatTime(batch.created_at)}</Typography.Text>
+              </Space>
+              {batch.operations.slice(0, 3).map((op) => (
+                <Typography.Text key={op.from_path} type="secondary" ellipsis>
+                  {op.from_path} → {op.to_path}
+                </Typography.Text>
+              ))}
+              {batch.operations.length > 3 && (
+                <Typography.Text type="secondary">… 共 {batch.operations.length} 个文件</Typography.Text>
+              )}
+              {!!batch.created_files?.length && (
+                <Typography.Text type="secondary">另生成 {batch.created_files.length} 个字幕，撤销时删除</Typography.Text>
+              )}
+            </Space>
+          </List.Item>
+        )}
+      />
+    </Modal>
+  );
+}
@@ -0,0 +1,113 @@
+import React, { useEffect, useState } from 'react';
+import { Alert, Button, InputNumber, List, Modal, Select, Space, Table, Tag, Typography, message } from 'antd';
+import { framerateOptions, RenameRequest, retimePairedSubtitles, RetimeOptions, RetimeReport } from '../api/tauri';
+
+interface RetimeModalProps {
+  open: boolean;
+  onClose: () => void;
+  // 已配对的视频与字幕，与重命名使用同一请求
+  request: RenameRequest | null;
+}
+
+const formatMs = (ms: number) => {
+  const pad = (n: number, width = 2) => String(n).padStart(width, '0');
+  return `${pad(Math.floor(ms / 3600000))}:${pad(Math.floor(ms / 60000) % 60)}:${pad(Math.floor(ms / 1000) % 60)}.${pad(ms % 1000, 3)}`;
+};
+
+const fileName = (path: string) => path.split(/[\\/]/).pop();
+
+export default function RetimeModal({ open, onClose, request }: RetimeModalProps) {
+  const [offsetSeconds, setOffsetSeconds] = useState(0);
+  const [fromFps, setFromFps] = useState<number | null>(null);
+  const [toFps, setToFps] = useState<number | null>(null);
+  const [reports, setReports] = useState<RetimeReport[]>([]);
+  const [busy, setBusy] = useState(false);
+
+  useEffect(() => {
+    if (open) setReports([]);
+  }, [open]);
+
+  const options = (): RetimeOptions => ({
+    offset_ms: Math.round(offsetSeconds * 1000),
+    from_fps: fromFps,
+    to_fps: toFps,
+  });
+
+  const run = async (dryRun: boolean) => {
+    if (!request) return;
+    setBusy(true);
+    try {
+      const result = await retimePairedSubtitles(request, options(), dryRun);
+      setReports(result);
+      if (!dryRun) {
+        const applied = result.filter((r) => r.applied).length;
+        const failed = result.filter((r) => r.error).length;
+        if (failed > 0) message.warning(`已调整${applied}个字幕，${failed}个失败`);
+        else message.success(`已调整${applied}个字幕的时间轴`);
+      }
+    } catch (e) {
+      message.error(`调整时间轴失败: ${e}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const unchanged = offsetSeconds === 0 && (fromFps === null || toFps === null || fromFps === toFps);
+
+  return (
+    <Modal
+      open={open}
+      title="调整字幕时间轴"
+      onCancel={onClose}
+      width={760}
+      footer={[
+        <Button key="preview" loading={busy} disabled={!request || unchanged} onClick={() => run(true)}>
+          预览
+        </Button>,
+        <Button key="apply" type="primary" loading={busy} disabled={!request || unchanged} onClick={() => run(false)}>
+          应用到{request?.tracks?.length ?? 0}个字幕
+        </Button>,
+      ]}
+    >
+      <Space wrap style={{ marginBottom: 12 }}>
+        <span>偏移</span>
+        <InputNumber value={offsetSeconds} step={0.1} precision={3} addonAfter="秒" onChange={(v) => setOffsetSeconds(v ?? 0)} />
+        <span>帧率</span>
+        <Select allowClear placeholder="字幕帧率" style={{ width: 130 }} value={fromFps ?? undefined} onChange={(v) => setFromFps(v ?? null)} options={framerateOptions} />
+        <span>→</span>
+        <Select allowClear placeholder="视频帧率" style={{ width: 130 }} value={toFps ?? undefined} onChange={(v) => setToFps(v ?? null)} options={framerateOptions} />
+      </Space>
+      <Alert type="info" showIcon style={{ marginBottom: 12 }} message="负数偏移使字幕提前；帧率换算适用于 PAL 25fps 与 23.976fps 片源之间的字幕。直接改写原文件，撤销需反向调整。" />
+      <List<RetimeReport>
+        size="small"
+        dataSource={reports}
+        style={{ maxHeight: 420, overflowY: 'auto' }}
+        renderItem={(r) => (
+          <List.Item>
+            <Space orientation="vertical" size={4} style={{ width: '100%', minWidth: 0 }}>
+              <Space>
+                <Typography.Text strong ellipsis style={{ maxWidth: 480 }}>{fileName(r.path)}</Typography.Text>
+                {r.error && <Tag color="error">{r.error}</Tag>}
+                {r.applied && <Tag color="success">已调整</Tag>}
+                {r.clamped > 0 && <Tag color="warning">{r.clamped}条提前到 0 以前</Tag>}
+              </Space>
+              {r.preview.length > 0 && (
+                <Table
+                  size="small"
+                  pagination={false}
+                  rowKey={(_, i) => String(i)}
+                  dataSource={r.preview}
+                  columns={[
+                    { title: '原时间', render: (_, c) => `${formatMs(c.start)} → ${formatMs(c.end)}`, width: 220 },
+                    { title: '新时间', render: (_, c) => `${formatMs(c.new_start)} → ${formatMs(c.new_end)}`, width: 220 },
+                    { title: '内容', dataIndex: 'text', ellipsis: true },
+                  ]}
+                />
+              )}
+            </Space>
+          </List.Item>
+        )}
+      />
+    </Modal>
+  );
+}
@@ -1,11 +1,12 @@
 import React, { useState, useCallback, useEffect } from 'react';
 import './llm-recognition.css';
-import { Card, Button, List, Tag, Space, message, Typography, Flex, AutoComplete, Input, Row, Col, Modal } from 'antd';
-import { FolderOpenOutlined, PlayCircleOutlined, SearchOutlined } from '@ant-design/icons';
-import { FileInfo, RecognitionResult } from '../types/llm';
-import { pickFilesAndGetInfo, pickDirectoryAndGetInfo, analyzeFilename, batchAnalyzeFilenames, loadSettings, searchBangumiSubjects, getBangumiSubjectDetail, BangumiSubjectDetail, Settings, BangumiSubject } from '../api/tauri';
+import { Card, Button, List, Tag, Space, message, Typography, Flex, AutoComplete, Input, Row, Col, Modal, Select, Checkbox } from 'antd';
+import { FolderOpenOutlined, HistoryOutlined, PlayCircleOutlined, SearchOutlined } from '@ant-design/icons';
+import { AnimeInfo, FileInfo, RecognitionResult } from '../types/llm';
+import { pickFilesAndGetInfo, pickDirectoryAndGetInfo, analyzeFilename, batchAnalyzeFilenames, loadSettings, searchMetadata, getMetadataDetail, rankMetadataCandidates, MetadataSubjectDetail, Settings, MetadataSubject, executeRenamePlan, undoRenameBatch, planVideoRename, analyzeFiles, cancelAnalyzeJob, onAnalyzeProgress, NamingInfo, RenamePlan, MediaServerPreset, mediaServerPresetOptions, renameProblemText, lookupEpisodeTitles, EpisodeTitle, mapEpisodeNumbers, MetadataProviderKind, RenameOperation as HistoryOperation } from '../api/tauri';
+import RenameHistoryModal from '../components/RenameHistoryModal';
+import CoverImage from '../components/CoverImage';
 import { useRef } from 'react';
-import { rename as renameFile, exists } from '@tauri-apps/plugin-fs';
 
 type RenameOperation = {
   fromPath: string;
@@ -19,24 +20,40 @@ const getExtension = (name: string) => {
   return i >= 0 ? name.slice(i) : '';
 };
 
-const buildPreviewName = (file: FileInfo, result: RecognitionResult | null) => {
-  if (!result || !result.info) return '';
-  const info = result.info;
-  const title = info.title.trim();
-  const yearPart = info.year ? `.${info.year}` : '';
-  const epPart = `.S01E${info.episode}`;
-  const group = info.group ? `.${info.group}` : '';
-  const codec = info.codec ? `.${info.codec}` : '';
-  const base = `${title}${yearPart}${epPart}${group}${codec}`;
-  return `${base}${getExtension(file.name)}`;
+type EpisodeTitleLang = 'none' | 'cn' | 'jp';
+
+const episodeTitleOptions: { label: string; value: EpisodeTitleLang }[] = [
+  { label: '不加分集标题', value: 'none' },
+  { label: '中文分集标题', value: 'cn' },
+  { label: '日文分集标题', value: 'jp' },
+];
+
+// 所选语言的标题缺失时退回另一种语言
+const pickEpisodeTitle = (title: EpisodeTitle | undefined, lang: EpisodeTitleLang) => {
+  if (!title || lang === 'none') return undefined;
+  return lang === 'cn' ? title.name_cn || title.name : title.name || title.name_cn;
+};
+
+const toNamingInfo = (file: FileInfo, info: AnimeInfo, epTitle?: string): NamingInfo => ({
+  ...info,
+  title: info.title.trim(),
+  season: info.season ?? 1,
+  ep_title: epTitle,
+  ext: getExtension(file.name).slice(1),
+});
+
+const fileStem = (name: string) => {
+  const i = name.lastIndexOf('.');
+  return i > 0 ? name.slice(0, i) : name;
 };
 
 interface FileItemProps {
   file: FileInfo;
   result: RecognitionResult | null;
+  previewName?: string;
 }
 
-function FileItem({ file, result }: FileItemProps) {
+function FileItem({ file, result, previewName }: FileItemProps) {
   const { Text } = Typography;
   const renderMetaLine = (res: RecognitionResult | null) => {
     const info = res?.info || null;
@@ -46,7 +63,7 @@ function FileItem({ file, result }: FileItemProps) {
           <Space size="small" wrap>
             <Tag color="blue">{info.title}</Tag>
             {info.year && <Tag color="orange">{info.year}</Tag>}
-            <Tag color="green">{`S01E${info.episode}`}</Tag>
+            <Tag color="green">{`S${String(info.season ?? 1).padStart(2, '0')}E${info.episode}`}</Tag>
             {info.codec && <Tag color="cyan">{info.codec}</Tag>}
             {info.group && <Tag color="magenta">{info.group}</Tag>}
           </Space>
@@ -62,7 +79,7 @@ function FileItem({ file, result }: FileItemProps) {
 
       <div style={{ display: 'flex', flexDirection: 'column', gap: 6, width: '100%' }}>
         <Text ellipsis>{file.path}</Text>
-        <Text ellipsis>{buildPreviewName(file, result) || '暂无预览'}</Text>
+        <Text ellipsis>{previewName || '暂无预览'}</Text>
         {renderMetaLine(result)}
       </div>
 
@@ -73,21 +90,35 @@ export default function LLMRecognition() {
   const [files, setFiles] = useState<FileInfo[]>([]);
   const [results, setResults] = useState<Map<string, RecognitionResult>>(new Map());
   const [lastRenameOps, setLastRenameOps] = useState<RenameOperation[] | null>(null);
+  const [lastBatchId, setLastBatchId] = useState<number | null>(null);
+  const [historyOpen, setHistoryOpen] = useState(false);
   const [renaming, setRenaming] = useState(false);
   const [undoing, setUndoing] = useState(false);
   const [modelUrl, setModelUrl] = useState('http://localhost:11434/v1/chat/completions');
   const [modelName, setModelName] = useState('qwen/qwen3-vl-8b');
+  const [namingTemplate, setNamingTemplate] = useState<string | null>(null);
+  const [namingPreset, setNamingPreset] = useState<MediaServerPreset | null>(null);
+  const [metadataProvider, setMetadataProvider] = useState<MetadataProviderKind>('bangumi');
+  const [libraryRoot, setLibraryRoot] = useState<string | null>(null);
+  const [createDirs, setCreateDirs] = useState(false);
+  const [bypassCache, setBypassCache] = useState(false);
+  const [episodeTitleLang, setEpisodeTitleLang] = useState<EpisodeTitleLang>('cn');
+  const [episodeTitles, setEpisodeTitles] = useState<Map<string, EpisodeTitle>>(new Map());
+  const [analyzeJob, setAnalyzeJob] = useState<{ id: string; done: number; total: number } | null>(null);
+  const [renamePlan, setRenamePlan] = useState<RenamePlan | null>(null);
+  const [previewError, setPreviewError] = useState<string | null>(null);
   const [searchQuery, setSearchQuery] = useState('');
   const [searchOptions, setSearchOptions] = useState<{ value: string; label: string }[]>([]);
-  const [selectedDetail, setSelectedDetail] = useState<BangumiSubjectDetail | null>(null);
+  const [selectedDetail, setSelectedDetail] = useState<MetadataSubjectDetail | null>(null);
   const [bangumiModalOpen, setBangumiModalOpen] = useState(false);
-  const [bangumiCandidates, setBangumiCandidates] = useState<BangumiSubject[]>([]);
-  const [candidateDetails, setCandidateDetails] = useState<Record<number, BangumiSubjectDetail>>({});
+  const [bangumiCandidates, setBangumiCandidates] = useState<MetadataSubject[]>([]);
+  const [candidateDetails, setCandidateDetails] = useState<Record<number, MetadataSubjectDetail>>({});
   const searchAreaRef = useRef<HTMLDivElement | null>(null);
   const fileListRef = useRef<HTMLDivElement | null>(null);
   const resetPageData = useCallback(() => {
     setFiles([]);
     setResults(new Map());
+    setEpisodeTitles(new Map());
     setLastRenameOps(null);
     setSelectedDetail(null);
     setSearchQuery('');
@@ -103,6 +134,11 @@ export default function LLMRecognition() {
         const s = await loadSettings();
         setModelUrl(s.model_url);
         setModelName(s.model_name);
+        setNamingTemplate(s.naming_template);
+        setNamingPreset(s.naming_preset ?? null);
+        setMetadataProvider(s.metadata_provider ?? 'bangumi');
+        setLibraryRoot(s.media_library_root ?? null);
+        setCreateDirs(!!s.naming_preset);
       } catch {}
     };
     init();
@@ -112,11 +148,57 @@ export default function LLMRecognition() {
       if (!detail) return;
       setModelUrl(detail.model_url);
       setModelName(detail.model_name);
+      setNamingTemplate(detail.naming_template);
+      setNamingPreset(detail.naming_preset ?? null);
+      setMetadataProvider(detail.metadata_provider ?? 'bangumi');
+      setLibraryRoot(detail.media_library_root ?? null);
     };
     window.addEventListener('settings-updated', onSettingsUpdated as EventListener);
     return () => window.removeEventListener('settings-updated', onSettingsUpdated as EventListener);
   }, []);
 
+  // 识别结果或命名方式变化时，由后端重新生成重命名计划作为预览
+  useEffect(() => {
+    let canceled = false;
+    const subtitles = files.filter(f => !f.is_video && !f.is_archive);
+    const items = files
+      .filter(f => f.is_video)
+      .flatMap(file => {
+        const info = results.get(file.path)?.info;
+        if (!info) return [];
+        // 与视频同名前缀的字幕随视频一起改名/移动
+        const stem = fileStem(file.name);
+        const companions = subtitles.filter(sub => sub.name.startsWith(`${stem}.`)).map(sub => sub.path);
+        const epTitle = pickEpisodeTitle(episodeTitles.get(file.path), episodeTitleLang);
+        return [{ source_path: file.path, info: toNamingInfo(file, info, epTitle), companions }];
+      });
+    if (items.length === 0) {
+      setRenamePlan(null);
+      setPreviewError(null);
+      return;
+    }
+    planVideoRename({
+      items,
+      template: namingTemplate,
+      preset: namingPreset,
+      target_root: libraryRoot,
+      create_dirs: createDirs,
+    })
+      .then(plan => {
+        if (canceled) return;
+        setRenamePlan(plan);
+        setPreviewError(null);
+      })
+      .catch(e => {
+        if (canceled) return;
+        setRenamePlan(null);
+        setPreviewError(String(e));
+      });
+    return () => {
+      canceled = true;
+    };
+  }, [files, results, episodeTitles, episodeTitleLang, namingTemplate, namingPreset, libraryRoot, createDirs]);
+
   useEffect(() => {
     if (bangumiCandidates.length === 0) {
       setCandidateDetails({});
@@ -126,14 +208,14 @@ export default function LLMRecognition() {
       const entries = await Promise.all(
         bangumiCandidates.map(async (item) => {
           try {
-            const detail = await getBangumiSubjectDetail(item.id);
+            const detail = await getMetadataDetail(item.id);
             return detail;
           } catch {
             return null;
           }
         })
       );
-      const detailMap: Record<number, BangumiSubjectDetail> = {};
+      const detailMap: Record<number, MetadataSubjectDetail> = {};
       entries.forEach((detail) => {
         if (detail) detailMap[detail.id] = detail;
       });
@@ -194,6 +276,7 @@ export default function LLMRecognition() {
         filename: file.name,
         model_url: modelUrl,
         model_name: modelName,
+        bypass_cache: bypassCache,
       });
 
       setResults(prev => {
@@ -249,7 +332,7 @@ export default function LLMRecognition() {
       return;
     }
     try {
-      const subjects = await searchBangumiSubjects(q, 10);
+      const subjects = await searchMetadata(q, 10);
       const opts = subjects.map(s => ({
         value: String(s.id),
         label: `${s.name_cn || s.name} ${s.date ? `(${s.date})` : ''}`.trim(),
@@ -264,7 +347,7 @@ export default function LLMRecognition() {
     const item = searchOptions.find(o => o.value === value);
     if (!item) return;
     const id = Number(value);
-    getBangumiSubjectDetail(id)
+    getMetadataDetail(id)
       .then((detail) => {
         setSelectedDetail(detail);
       })
@@ -274,7 +357,7 @@ export default function LLMRecognition() {
   };
 
   const BangumiCard: React.FC<{
-    detail: BangumiSubjectDetail | null;
+    detail: MetadataSubjectDetail | null;
     fallbackName?: string;
     fallbackDate?: string;
     onClick?: () => void;
@@ -302,17 +385,14 @@ export default function LLMRecognition() {
         <div className="bangumi-detail-content">
           <div className="bangumi-cover">
             <div className="cover-box cover-box--portrait">
-              {cover ? (
-                <img src={cover} alt={titleCn} />
-              ) : (
-                <div style={{ width: '100%', height: '100%', background: 'var(--ant-color-fill-tertiary)' }} />
-              )}
+              <CoverImage url={cover} alt={titleCn} />
             </div>
           </div>
           <div className="bangumi-info">
             <div className="bangumi-title-cn">{titleCn}</div>
             {titleEn ? <div className="bangumi-title-en">{titleEn}</div> : null}
             <div className="bangumi-meta">
+              {detail?.platform && <span>{detail.platform}</span>}
               {typeof episodes === 'number' && <span>共{episodes}集</span>}
               {typeof year === 'number' && <span>{year}年</span>}
               {!year && dateText && <span>{dateText}</span>}
@@ -324,7 +404,7 @@ export default function LLMRecognition() {
   };
 
   const BangumiModalCard: React.FC<{
-    detail: BangumiSubjectDetail | null;
+    detail: MetadataSubjectDetail | null;
     fallbackName?: string;
     fallbackDate?: string;
     onClick: () => void;
@@ -347,13 +427,14 @@ export default function LLMRecognition() {
       >
         <div className="bangumi-modal-card-cover">
           <div className="cover-box">
-            {cover ? <img src={cover} alt={titleCn} /> : <div style={{ width: '100%', height: '100%', background: 'var(--ant-color-fill-tertiary)' }} />}
+            <CoverImage url={cover} alt={titleCn} />
           </div>
         </div>
         <div className="bangumi-modal-card-info">
           <div className="bangumi-modal-card-title-cn">{titleCn}</div>
           {titleEn ? <div className="bangumi-modal-card-title-en">{titleEn}</div> : null}
           <div className="bangumi-modal-card-meta">
+            {detail?.platform && <span>{detail.platform}</span>}
             {typeof episodes === 'number' && <span>共{episodes}集</span>}
             {typeof year === 'number' && <span>{year}年</span>}
             {!year && dateText && <span>{dateText}</span>}
@@ -363,99 +444,164 @@ export default function LLMRecognition() {
     );
   };
 
-  const applyBangumiDetailToFiles = async (detail: BangumiSubjectDetail) => {
+  // 没有季信息的文件按所选条目的前传/续集换算季与集数，绝对集数（如 142）会落到对应的季
+  // 返回用于查询分集标题的识别结果，落在其他季的文件不属于所选条目，不再查询标题
+  const applySeasonMapping = async (subjectId: number, recognized: Map<string, AnimeInfo>) => {
+    // 前传/续集关系只有 Bangumi 提供
+    if (metadataProvider !== 'bangumi') return recognized;
+    const entries = Array.from(recognized.entries()).filter(([, info]) => info.episode && info.season == null);
+    if (entries.length === 0) return recognized;
+    try {
+      const mappings = await mapEpisodeNumbers(subjectId, entries.map(([, info]) => info.episode!));
+      const mapped = new Map<string, AnimeInfo>();
+      const forTitles = new Map(recognized);
+      entries.forEach(([path, info], i) => {
+        const m = mappings[i];
+        if (m?.season == null || !m.seasonal_episode) return;
+        const next = { ...info, season: m.season, episode: m.seasonal_episode };
+        mapped.set(path, next);
+        if (m.subject_id === subjectId) {
+          forTitles.set(path, next);
+        } else {
+          forTitles.delete(path);
+        }
+      });
+      setResults(prev => {
+        const newResults = new Map(prev);
+        mapped.forEach((info, path) => {
+          const res = newResults.get(path);
+          if (res?.info) {
+            newResults.set(path, { ...res, info: { ...res.info, season: info.season, episode: info.episode } });
+          }
+        });
+        return newResults;
+      });
+      return forTitles;
+    } catch (e) {
+      message.warning(`换算季与集数失败: ${e}`);
+      return recognized;
+    }
+  };
+
+  // 按识别出的集数获取分集标题，第 0 季的文件在 SP 中查找
+  const loadEpisodeTitles = async (subjectId: number, recognized: Map<string, AnimeInfo>) => {
+    const entries = Array.from(recognized.entries()).filter(([, info]) => info.episode);
+    if (entries.length === 0) return;
+    try {
+      const titles = await lookupEpisodeTitles(
+        subjectId,
+        entries.map(([, info]) => ({ episode: info.episode!, special: info.season === 0, season: info.season })),
+      );
+      setEpisodeTitles(new Map(entries.map(([path], i) => [path, titles[i]])));
+    } catch (e) {
+      message.warning(`获取分集标题失败: ${e}`);
+    }
+  };
+
+  const applyBangumiDetailToFiles = async (detail: MetadataSubjectDetail) => {
     const bangumiTitle = detail.name_cn || detail.name;
     if (!bangumiTitle || bangumiTitle.trim() === '') {
       message.error('所选条目缺少标题');
       return;
     }
 
+    const videoFiles = files.filter(f => f.is_video);
+    if (videoFiles.length === 0) {
+      message.warning('没有视频文件需要识别');
+      return;
+    }
+
     message.info('开始分析文件信息...');
-    let analyzedCount = 0;
-    let successCount = 0;
+    const jobId = `analyze-${Date.now()}`;
+    setAnalyzeJob({ id: jobId, done: 0, total: videoFiles.length });
+    setResults(prev => {
+      const newResults = new Map(prev);
+      videoFiles.forEach(file => {
+        newResults.set(file.path, {
+          file,
+          info: newResults.get(file.path)?.info || null,
+          loading: true,
+          error: null,
+        });
+      });
+      return newResults;
+    });
 
-    for (const file of files) {
-      if (!file.is_video) continue;
+    // 取消或出错时，未完成的文件结束加载状态
+    const clearLoading = () => {
+      setResults(prev => {
+        const newResults = new Map(prev);
+        newResults.forEach((res, key) => {
+          if (res.loading) newResults.set(key, { ...res, loading: false });
+        });
+        return newResults;
+      });
+    };
 
-      try {
-        setResults(prev => {
-          const newResults = new Map(prev);
-          const existing = newResults.get(file.path);
+    setEpisodeTitles(new Map());
+    const recognized = new Map<string, AnimeInfo>();
+    const unlisten = await onAnalyzeProgress(({ job_id, done, file, result }) => {
+      if (job_id !== jobId) return;
+      if (result.success && result.data) {
+        recognized.set(file.path, result.data);
+      }
+      setAnalyzeJob(job => (job && job.id === jobId ? { ...job, done } : job));
+      setResults(prev => {
+        const newResults = new Map(prev);
+        if (result.success && result.data) {
           newResults.set(file.path, {
             file,
-            info: existing?.info || null,
-            loading: true,
+            info: {
+              ...result.data,
+              title: bangumiTitle,
+              year: detail.year ?? result.data.year,
+            },
+            loading: false,
             error: null,
           });
-          return newResults;
-        });
-
-        const fileResponse = await analyzeFilename({
-          filename: file.name,
-          model_url: modelUrl,
-          model_name: modelName,
-        });
-
-        analyzedCount++;
-
-        if (fileResponse.success && fileResponse.data) {
-          const updatedInfo = {
-            ...fileResponse.data,
-            title: bangumiTitle,
-            year: detail.year ?? fileResponse.data.year,
-          };
-
-          setResults(prev => {
-            const newResults = new Map(prev);
-            newResults.set(file.path, {
-              file,
-              info: updatedInfo,
-              loading: false,
-              error: null,
-            });
-            return newResults;
-          });
-
-          successCount++;
         } else {
-          setResults(prev => {
-            const newResults = new Map(prev);
-            newResults.set(file.path, {
-              file,
-              info: null,
-              loading: false,
-              error: fileResponse.error || '识别失败',
-            });
-            return newResults;
-          });
-        }
-
-        await new Promise(resolve => setTimeout(resolve, 300));
-      } catch (error) {
-        setResults(prev => {
-          const newResults = new Map(prev);
           newResults.set(file.path, {
             file,
             info: null,
             loading: false,
-            error: `分析错误: ${error}`,
+            error: result.error || '识别失败',
           });
-          return newResults;
-        });
-      }
-    }
+        }
+        return newResults;
+      });
+    });
 
-    if (successCount > 0) {
-      message.success(`文件分析完成！成功识别 ${successCount}/${analyzedCount} 个文件，使用标题: ${bangumiTitle}`);
-    } else {
-      message.warning('文件分析完成，但未成功识别任何文件信息');
+    try {
+      const summary = await analyzeFiles({
+        job_id: jobId,
+        files: videoFiles,
+        model_url: modelUrl,
+        model_name: modelName,
+        bypass_cache: bypassCache,
+      });
+      if (summary.canceled) {
+        clearLoading();
+        message.info(`已取消识别，完成 ${summary.done}/${summary.total} 个文件`);
+      } else if (summary.succeeded > 0) {
+        message.success(`文件分析完成！成功识别 ${summary.succeeded}/${summary.total} 个文件，使用标题: ${bangumiTitle}`);
+        const mapped = await applySeasonMapping(detail.id, recognized);
+        await loadEpisodeTitles(detail.id, mapped);
+      } else {
+        message.warning('文件分析完成，但未成功识别任何文件信息');
+      }
+    } catch (error) {
+      clearLoading();
+      message.error(`分析错误: ${error}`);
+    } finally {
+      unlisten();
+      setAnalyzeJob(null);
     }
   };
 
-  const handlePickBangumiCandidate = async (subject: BangumiSubject) => {
+  const handlePickBangumiCandidate = async (subject: MetadataSubject) => {
     setBangumiModalOpen(false);
     try {
-      const detail = await getBangumiSubjectDetail(subject.id);
+      const detail = await getMetadataDetail(subject.id);
       setSelectedDetail(detail);
       const displayName = subject.name_cn || subject.name;
       setSearchQuery(displayName);
@@ -486,6 +632,7 @@ export default function LLMRecognition() {
         filenames,
         model_url: modelUrl,
         model_name: modelName,
+        bypass_cache: bypassCache,
       });
 
       if (response.success && response.data) {
@@ -500,19 +647,26 @@ export default function LLMRecognition() {
         const query = title.trim();
         setSearchQuery(query);
         
-        // 搜索 Bangumi
+        // 搜索并为候选作品打分，匹配度足够高时直接选择
         try {
-          const list = await searchBangumiSubjects(query, 10);
-          const opts = list.map(s => ({ 
-            value: String(s.id), 
-            label: `${s.name_cn || s.name} ${s.date ? `(${s.date})` : ''}`.trim() 
+          const ranking = await rankMetadataCandidates(query, videoFiles.map(f => f.name), 5);
+          const list = ranking.candidates.map(c => c.detail);
+          const opts = list.map(s => ({
+            value: String(s.id),
+            label: `${s.name_cn || s.name} ${s.year ? `(${s.year})` : ''}`.trim()
           }));
           setSearchOptions(opts);
-          
-          const topFive = list.slice(0, 5);
 
-          if (topFive.length > 0) {
-            setBangumiCandidates(topFive);
+          const best = ranking.candidates.find(c => c.detail.id === ranking.selected);
+          if (best) {
+            const detail = best.detail;
+            const displayName = detail.name_cn || detail.name;
+            setSelectedDetail(detail);
+            setSearchQuery(displayName);
+            message.success(`已自动选择作品：${displayName}（匹配度 ${Math.round(best.score * 100)}%）`);
+            await applyBangumiDetailToFiles(detail);
+          } else if (list.length > 0) {
+            setBangumiCandidates(list.map(d => ({ id: d.id, name: d.name, name_cn: d.name_cn, date: d.air_date, platform: d.platform })));
             setBangumiModalOpen(true);
             message.info('选择匹配的动画作品以继续');
           } else {
@@ -531,67 +685,41 @@ export default function LLMRecognition() {
     }
   };
 
-  const buildNewPathFromFile = (file: FileInfo, newName: string) => {
-    const slashIndex = Math.max(file.path.lastIndexOf('/'), file.path.lastIndexOf('\\'));
-    if (slashIndex < 0) return '';
-    return `${file.path.slice(0, slashIndex + 1)}${newName}`;
-  };
-
   const handleRenameWithPreview = async () => {
     if (renaming) return;
-    const videoFiles = files.filter(f => f.is_video);
-    if (videoFiles.length === 0) {
-      message.warning('暂无可重命名的视频文件');
+    if (!renamePlan) {
+      message.warning('没有可依据预览命名的文件');
       return;
     }
-
-    const plans: RenameOperation[] = [];
-    const skipped: string[] = [];
-
-    videoFiles.forEach(file => {
-      const previewName = buildPreviewName(file, results.get(file.path) || null);
-      if (!previewName) {
-        skipped.push(file.name);
-        return;
-      }
-      const targetPath = buildNewPathFromFile(file, previewName);
-      if (!targetPath) {
-        skipped.push(file.name);
-        return;
-      }
-      if (targetPath === file.path) {
-        return;
-      }
-      plans.push({
-        fromPath: file.path,
-        toPath: targetPath,
-        oldFile: file,
-        newFile: { ...file, name: previewName, path: targetPath },
-      });
-    });
-
-    if (plans.length === 0) {
-      message.warning('没有可依据预览命名的文件');
+    const problemItem = renamePlan.items.find(item => item.problems.length > 0);
+    if (problemItem) {
+      message.error(`${problemItem.source_name}: ${renameProblemText[problemItem.problems[0]]}`);
       return;
     }
 
-    const uniqueTargets = new Set(plans.map(p => p.toPath));
-    if (uniqueTargets.size !== plans.length) {
-      message.error('存在重复的目标文件名，请检查识别结果');
+    const fileMap = new Map(files.map(f => [f.path, f]));
+    const plans: RenameOperation[] = renamePlan.items
+      .filter(item => item.source_path !== item.target_path)
+      .map(item => {
+        const oldFile = fileMap.get(item.source_path) ?? { name: item.source_name, path: item.source_path, is_video: true };
+        return {
+          fromPath: item.source_path,
+          toPath: item.target_path,
+          oldFile,
+          newFile: { ...oldFile, name: item.target_name, path: item.target_path },
+        };
+      });
+    if (plans.length === 0) {
+      message.info('文件名已符合预览，无需重命名');
       return;
     }
+    const skipped = files.filter(f => f.is_video && !renamePlan.items.some(item => item.source_path === f.path));
 
     setRenaming(true);
     try {
-      for (const plan of plans) {
-        const targetExists = await exists(plan.toPath);
-        if (targetExists) {
-          throw new Error(`目标文件已存在：${plan.toPath}`);
-        }
-      }
-
-      for (const plan of plans) {
-        await renameFile(plan.fromPath, plan.toPath);
+      const response = await executeRenamePlan(renamePlan);
+      if (!response.success) {
+        throw new Error(response.message);
       }
 
       const planMap = new Map(plans.map(p => [p.fromPath, p]));
@@ -610,6 +738,7 @@ export default function LLMRecognition() {
         return newMap;
       });
       setLastRenameOps(plans);
+      setLastBatchId(response.batch_id ?? null);
       if (skipped.length > 0) {
         message.info(`已重命名 ${plans.length} 个文件，跳过 ${skipped.length} 个无预览文件`);
       } else {
@@ -624,26 +753,16 @@ export default function LLMRecognition() {
 
   const handleUndoRename = async () => {
     if (undoing) return;
-    if (!lastRenameOps || lastRenameOps.length === 0) {
+    if (!lastRenameOps || lastRenameOps.length === 0 || lastBatchId === null) {
       message.info('暂无可撤销的重命名记录');
       return;
     }
 
     setUndoing(true);
     try {
-      for (const op of lastRenameOps) {
-        const currentExists = await exists(op.toPath);
-        if (!currentExists) {
-          throw new Error(`文件不存在，无法撤销：${op.toPath}`);
-        }
-        const targetOccupied = await exists(op.fromPath);
-        if (targetOccupied) {
-          throw new Error(`原路径已存在同名文件：${op.fromPath}`);
-        }
-      }
-
-      for (const op of [...lastRenameOps].reverse()) {
-        await renameFile(op.toPath, op.fromPath);
+      const response = await undoRenameBatch(lastBatchId);
+      if (!response.success) {
+        throw new Error(response.message);
       }
 
       const opMap = new Map(lastRenameOps.map(op => [op.toPath, op]));
@@ -662,6 +781,7 @@ export default function LLMRecognition() {
       });
 
       setLastRenameOps(null);
+      setLastBatchId(null);
       message.success('已撤销上次重命名');
     } catch (error) {
       message.error(`撤销失败: ${error}`);
@@ -670,6 +790,28 @@ export default function LLMRecognition() {
     }
   };
 
+  // 历史记录中撤销/重做后，同步列表中的文件路径
+  const applyHistoryOperations = (ops: HistoryOperation[]) => {
+    const opMap = new Map(ops.map(op => [op.from_path, op.to_path]));
+    const moveFile = (file: FileInfo): FileInfo => {
+      const toPath = opMap.get(file.path);
+      if (!toPath) return file;
+      const slashIndex = Math.max(toPath.lastIndexOf('/'), toPath.lastIndexOf('\\'));
+      return { ...file, path: toPath, name: toPath.slice(slashIndex + 1) };
+    };
+    setFiles(prev => sortFiles(prev.map(moveFile)));
+    setResults(prev => {
+      const newMap = new Map<string, RecognitionResult>();
+      prev.forEach((res, key) => {
+        const moved = moveFile(res.file);
+        newMap.set(opMap.has(key) ? moved.path : key, { ...res, file: moved });
+      });
+      return newMap;
+    });
+    setLastRenameOps(null);
+    setLastBatchId(null);
+  };
+
   const handleClearFiles = () => {
     setFiles([]);
     setResults(new Map());
@@ -683,10 +825,17 @@ export default function LLMRecognition() {
     return res ? !res.loading : false;
   });
   const previewReadyCount = videoFilesInList.reduce((count, file) => {
-    const preview = buildPreviewName(file, results.get(file.path) || null);
-    return preview ? count + 1 : count;
+    return renamePlan?.items.some(item => item.source_path === file.path) ? count + 1 : count;
   }, 0);
   const canRename = recognitionCompleted && previewReadyCount > 0;
+  // 目标与源文件不在同一目录时显示完整目标路径
+  const previewByPath = new Map(
+    (renamePlan?.items ?? []).map(item => {
+      const sameDir = item.target_path.slice(0, -item.target_name.length) === item.source_path.slice(0, -item.source_name.length);
+      const problem = item.problems.length > 0 ? `（${renameProblemText[item.problems[0]]}）` : '';
+      return [item.source_path, `${sameDir ? item.target_name : item.target_path}${problem}`];
+    })
+  );
 
   useEffect(() => {}, []);
 
@@ -695,6 +844,9 @@ export default function LLMRecognition() {
       <Flex align="center" justify="space-between" style={{ width: '100%' }}>
         <Typography.Title level={3} style={{ margin: 0 }}>BDRip重命名</Typography.Title>
         <Space>
+          <Button icon={<HistoryOutlined />} onClick={() => setHistoryOpen(true)} aria-label="重命名历史">
+            重命名历史
+          </Button>
           <Button icon={<FolderOpenOutlined />} onClick={handlePickDirectory} aria-label="选择文件夹">
             选择文件夹
           </Button>
@@ -746,6 +898,40 @@ export default function LLMRecognition() {
             {files.length > 0 && (
               <Tag color="blue">{files.length}</Tag>
             )}
+            {previewError && (
+              <Typography.Text type="danger" style={{ fontWeight: 'normal' }}>{previewError}</Typography.Text>
+            )}
+          </Space>
+        }
+        extra={
+          <Space size="small">
+            {analyzeJob && (
+              <Button size="small" danger onClick={() => cancelAnalyzeJob(analyzeJob.id)}>
+                取消识别 {analyzeJob.done}/{analyzeJob.total}
+              </Button>
+            )}
+            <Select
+              size="small"
+              allowClear
+              placeholder="命名模板"
+              value={namingPreset ?? undefined}
+              onChange={(value) => setNamingPreset(value ?? null)}
+              options={mediaServerPresetOptions}
+              style={{ width: 120 }}
+            />
+            <Select
+              size="small"
+              value={episodeTitleLang}
+              onChange={setEpisodeTitleLang}
+              options={episodeTitleOptions}
+              style={{ width: 120 }}
+            />
+            <Checkbox checked={createDirs} onChange={(e) => setCreateDirs(e.target.checked)}>
+              创建目录并移动
+            </Checkbox>
+            <Checkbox checked={bypassCache} onChange={(e) => setBypassCache(e.target.checked)}>
+              忽略识别缓存
+            </Checkbox>
           </Space>
         }
       >
@@ -789,6 +975,7 @@ export default function LLMRecognition() {
                       <FileItem
                         file={file}
                         result={results.get(file.path) || null}
+                        previewName={previewByPath.get(file.path)}
                       />
                     </div>
                   </Col>
@@ -821,6 +1008,12 @@ export default function LLMRecognition() {
           </Card>
         )}
       </Modal>
+
+      <RenameHistoryModal
+        open={historyOpen}
+        onClose={() => setHistoryOpen(false)}
+        onChanged={applyHistoryOperations}
+      />
     </div>
   );
 }
@@ -2,27 +2,55 @@ import React, { useEffect, useRef, useState } from "react";
 import "./rename.css";
 import { invoke } from "@tauri-apps/api/core";
 import { listen, TauriEvent } from "@tauri-apps/api/event";
-import { Button, Card, Col, Divider, Empty, Input, List, Row, Space, Tag, Tooltip, Typography, message, } from "antd";
+import { Button, Card, Checkbox, Col, Divider, Dropdown, Empty, Input, List, Modal, Row, Select, Space, Tag, Tooltip, Typography, message, } from "antd";
 import {
   ClearOutlined,
   FileTextOutlined,
+  FieldTimeOutlined,
+  HistoryOutlined,
   FolderOpenOutlined,
   FolderOutlined,
   PlayCircleOutlined,
   ReloadOutlined,
+  TranslationOutlined,
   UploadOutlined,
 } from "@ant-design/icons";
 import { FileInfo } from "../types/llm";
-import { pickFilesAndGetInfo, pickDirectoryAndGetInfo, loadSettings, Settings } from "../api/tauri";
+import {
+  pickFilesAndGetInfo,
+  pickDirectoryAndGetInfo,
+  loadSettings,
+  Settings,
+  planRename,
+  executeRenamePlan,
+  RenamePlan,
+  renameProblemText,
+  RenameOperation,
+  matchEpisodes,
+  EncodingConversion,
+  SubtitleEncodingTarget,
+  subtitleEncodingOptions,
+  SubtitleConversion,
+  SubtitleFormat,
+  subtitleFormatOptions,
+  ChineseConversion,
+  chineseConversionOptions,
+  convertChineseScript,
+} from "../api/tauri";
+import RenameHistoryModal from "../components/RenameHistoryModal";
+import RetimeModal from "../components/RetimeModal";
+import ArchiveModal from "../components/ArchiveModal";
 
 interface DragDropPayload {
   paths: string[];
 }
 
-interface RenameResponse {
-  success: boolean;
-  message: string;
-  renamed_files: string[];
+interface EpisodeItem {
+  episode: string;
+  video?: FileInfo;
+  subtitles: FileInfo[];
+  // 同一集存在多个视频或字幕，需要手动处理
+  ambiguous?: boolean;
 }
 
 export default function Rename() {
@@ -34,17 +62,21 @@ export default function Rename() {
   const [customSuffix, setCustomSuffix] = useState("");
   const [selectedSuffix, setSelectedSuffix] = useState("");
   const [loading, setLoading] = useState(false);
+  const [plan, setPlan] = useState<RenamePlan | null>(null);
+  const [historyOpen, setHistoryOpen] = useState(false);
+  const [retimeOpen, setRetimeOpen] = useState(false);
+  const [pendingArchives, setPendingArchives] = useState<FileInfo[]>([]);
+  const [keepLanguageTag, setKeepLanguageTag] = useState(true);
+  const [subtitleEncoding, setSubtitleEncoding] = useState<SubtitleEncodingTarget | null>(null);
+  const [subtitleFormat, setSubtitleFormat] = useState<SubtitleFormat | null>(null);
 
   const leftScrollRef = useRef<HTMLDivElement | null>(null);
   const rightScrollRef = useRef<HTMLDivElement | null>(null);
 
   const defaultEpisodeRegex = "\\[(\\d{2})\\]";
   const [episodeRegexStr, setEpisodeRegexStr] = useState<string>(defaultEpisodeRegex);
-  const [episodeRegex, setEpisodeRegex] = useState<RegExp>(() => new RegExp(defaultEpisodeRegex));
 
-  const [episodeItems, setEpisodeItems] = useState<
-    { episode: string; video?: FileInfo; subtitle?: FileInfo }[]
-  >([]);
+  const [episodeItems, setEpisodeItems] = useState<EpisodeItem[]>([]);
 
   const suffixOptions = [
     { label: "chs", value: "chs" },
@@ -72,35 +104,73 @@ export default function Rename() {
     };
   }, []);
 
+  // 由后端按集数配对视频与字幕
   useEffect(() => {
-    try {
-      setEpisodeRegex(new RegExp(episodeRegexStr));
-    } catch (err) {
-      console.error("无效的剧集匹配正则:", err);
+    if (videoFiles.length === 0 && subtitleFiles.length === 0) {
+      setEpisodeItems([]);
+      return;
     }
-  }, [episodeRegexStr]);
-
+    let canceled = false;
+    matchEpisodes(videoFiles, subtitleFiles, episodeRegexStr)
+      .then((result) => {
+        if (canceled) return;
+        const items: EpisodeItem[] = [
+          ...result.pairs.map((p) => ({ episode: p.episode, video: p.video, subtitles: p.subtitles })),
+          ...result.unmatched_videos
+            .filter((u) => u.episode)
+            .map((u) => ({ episode: u.episode as string, video: u.file, subtitles: [] })),
+          ...result.unmatched_subtitles
+            .filter((u) => u.episode)
+            .map((u) => ({ episode: u.episode as string, subtitles: [u.file] })),
+          ...result.ambiguous.map((a) => ({ episode: a.episode, video: a.videos[0], subtitles: [], ambiguous: true })),
+        ];
+        items.sort((a, b) => Number(a.episode) - Number(b.episode));
+        setEpisodeItems(items);
+      })
+      .catch((err) => {
+        console.error("剧集配对失败:", err);
+        if (!canceled) setEpisodeItems([]);
+      });
+    return () => {
+      canceled = true;
+    };
+  }, [videoFiles, subtitleFiles, episodeRegexStr]);
+
+  const matchedPairs = () =>
+    episodeItems
+      .filter((it) => it.video && it.subtitles.length > 0)
+      .map((it) => ({ video: it.video as FileInfo, subtitles: it.subtitles }));
+
+  // 每个视频可对应多个字幕轨道
+  const buildRenameRequest = (pairs: { video: FileInfo; subtitles: FileInfo[] }[]) => ({
+    video_files: pairs.map((p) => p.video),
+    subtitle_files: [],
+    suffix: (selectedSuffix || customSuffix).trim(),
+    tracks: pairs.flatMap((p, idx) => p.subtitles.map((subtitle) => ({ video_index: idx, subtitle }))),
+    keep_language_tag: keepLanguageTag,
+    subtitle_encoding: subtitleEncoding,
+    subtitle_format: subtitleFormat,
+  });
+
+  // 由后端生成预览计划，保证预览与执行结果一致
   useEffect(() => {
-    const vMap = new Map<string, FileInfo>();
-    const sMap = new Map<string, FileInfo>();
-    for (const v of videoFiles) {
-      const ep = extractEpisode(v.name);
-      if (ep) vMap.set(ep, v);
-    }
-    for (const s of subtitleFiles) {
-      const ep = extractEpisode(s.name);
-      if (ep) sMap.set(ep, s);
+    const pairs = matchedPairs();
+    if (pairs.length === 0) {
+      setPlan(null);
+      return;
     }
-    const episodes = Array.from(new Set([...vMap.keys(), ...sMap.keys()])).sort(
-      (a, b) => Number(a) - Number(b)
-    );
-    const items = episodes.map((ep) => ({
-      episode: ep,
-      video: vMap.get(ep),
-      subtitle: sMap.get(ep),
-    }));
-    setEpisodeItems(items);
-  }, [videoFiles, subtitleFiles, episodeRegex]);
+    let canceled = false;
+    planRename(buildRenameRequest(pairs))
+      .then((p) => {
+        if (!canceled) setPlan(p);
+      })
+      .catch(() => {
+        if (!canceled) setPlan(null);
+      });
+    return () => {
+      canceled = true;
+    };
+  }, [episodeItems, selectedSuffix, customSuffix, keepLanguageTag, subtitleEncoding, subtitleFormat]);
 
   useEffect(() => {
     let unlistenDrop: (() => void) | undefined;
@@ -131,14 +201,14 @@ export default function Rename() {
     message.open({ type, content: text, duration });
   };
 
-  const extractEpisode = (name: string): string | null => {
-    const match = name.match(episodeRegex);
-    return match ? match[1] : null;
-  };
-
   const updateFileLists = (newFiles: FileInfo[]) => {
     const newVideos = newFiles.filter((file) => file.is_video);
-    const newSubtitles = newFiles.filter((file) => !file.is_video);
+    const newSubtitles = newFiles.filter((file) => !file.is_video && !file.is_archive);
+    const newArchives = newFiles.filter((file) => file.is_archive);
+    // 字幕包先解压，解压出的字幕再通过本函数加入列表
+    if (newArchives.length > 0) {
+      setPendingArchives((prev) => Array.from(new Map([...prev, ...newArchives].map((a) => [a.path, a])).values()));
+    }
     if (newVideos.length === 0 && newSubtitles.length === 0) return;
 
     setVideoFiles((prevVideos) => {
@@ -167,25 +237,8 @@ export default function Rename() {
   };
 
   const handleRename = async () => {
-    const vMap = new Map<string, FileInfo>();
-    const sMap = new Map<string, FileInfo>();
-    for (const v of videoFiles) {
-      const ep = extractEpisode(v.name);
-      if (ep) vMap.set(ep, v);
-    }
-    for (const s of subtitleFiles) {
-      const ep = extractEpisode(s.name);
-      if (ep) sMap.set(ep, s);
-    }
-    const episodes = Array.from(new Set([...vMap.keys(), ...sMap.keys()])).sort((a, b) => Number(a) - Number(b));
-    const pairs: { video: FileInfo; subtitle: FileInfo }[] = [];
-    const missing: string[] = [];
-    for (const ep of episodes) {
-      const v = vMap.get(ep);
-      const s = sMap.get(ep);
-      if (v && s) pairs.push({ video: v, subtitle: s });
-      else if (v && !s) missing.push(ep);
-    }
+    const pairs = matchedPairs();
+    const missing = episodeItems.filter((it) => it.video && it.subtitles.length === 0 && !it.ambiguous).map((it) => it.episode);
     if (missing.length) showMessage(`已跳过缺失字幕的剧集: ${missing.join(", ")}`, "info");
     if (pairs.length === 0) {
       showMessage("请先添加视频文件和对应的字幕文件", "error");
@@ -194,36 +247,34 @@ export default function Rename() {
 
     setLoading(true);
     try {
-      const suffix = selectedSuffix || customSuffix;
-      const response = await invoke<RenameResponse>("rename_subtitle_files", {
-        request: {
-          video_files: pairs.map((p) => p.video),
-          subtitle_files: pairs.map((p) => p.subtitle),
-          suffix,
-        },
-      });
+      const currentPlan = await planRename(buildRenameRequest(pairs));
+      setPlan(currentPlan);
+      if (!currentPlan.valid) {
+        const bad = currentPlan.items.filter((it) => it.problems.length > 0);
+        showMessage(
+          `有 ${bad.length} 个文件无法重命名: ${bad[0].source_name}（${renameProblemText[bad[0].problems[0]]}）`,
+          "error"
+        );
+        return;
+      }
 
+      const response = await executeRenamePlan(currentPlan);
       if (response.success) {
-        if (response.renamed_files.length !== pairs.length) {
-          showMessage("返回的重命名数量与匹配的文件数量不一致", "error");
-        } else {
-          const renameMap = new Map<string, string>();
-          pairs.forEach((p, idx) => {
-            renameMap.set(p.subtitle.path, response.renamed_files[idx]);
-          });
-          const updatedSubtitles = subtitleFiles.map((subtitle) => {
-            const newName = renameMap.get(subtitle.path);
-            if (!newName) return subtitle;
-            const sepIndex = Math.max(subtitle.path.lastIndexOf("/"), subtitle.path.lastIndexOf("\\"));
-            const parent = sepIndex >= 0 ? subtitle.path.slice(0, sepIndex + 1) : "";
-            const newPath = parent ? parent + newName : newName;
-            return { ...subtitle, name: newName, path: newPath };
-          });
-          setSubtitleFiles(updatedSubtitles);
-          showMessage(response.message, "success");
-        }
+        const renameMap = new Map(currentPlan.items.map((it) => [it.source_path, it]));
+        const updatedSubtitles = subtitleFiles.map((subtitle) => {
+          const item = renameMap.get(subtitle.path);
+          if (!item) return subtitle;
+          return { ...subtitle, name: item.target_name, path: item.target_path };
+        });
+        setSubtitleFiles(updatedSubtitles);
+        showMessage(response.message, "success");
+        if (response.encoding_report.some((r) => r.converted || r.error)) showEncodingReport(response.encoding_report);
+        if (response.subtitle_conversions.some((r) => r.error)) showConversionReport(response.subtitle_conversions);
       } else {
         showMessage(response.message, "error");
+        if (response.rollback_errors.length > 0) {
+          console.error("回滚失败的文件:", response.rollback_errors);
+        }
       }
     } catch (error) {
       console.error("重命名时出错:", error);
@@ -233,6 +284,81 @@ export default function Rename() {
     }
   };
 
+  // 列出转换过或转换失败的字幕，已是目标编码的不显示
+  const showEncodingReport = (report: EncodingConversion[]) => {
+    const changed = report.filter((r) => r.converted || r.error);
+    Modal.info({
+      title: "字幕编码转换结果",
+      width: 640,
+      content: (
+        <List
+          size="small"
+          dataSource={changed}
+          renderItem={(r) => (
+            <List.Item>
+              <Text ellipsis style={{ maxWidth: 420 }}>{r.path.split(/[\\/]/).pop()}</Text>
+              {r.error ? <Tag color="error">{r.error}</Tag> : <Tag color="success">{r.detected} → UTF-8</Tag>}
+            </List.Item>
+          )}
+        />
+      ),
+    });
+  };
+
+  // 只列出格式转换失败的字幕，成功的数量已在提示消息中
+  const showConversionReport = (report: SubtitleConversion[]) => {
+    Modal.warning({
+      title: "字幕格式转换失败",
+      width: 640,
+      content: (
+        <List
+          size="small"
+          dataSource={report.filter((r) => r.error)}
+          renderItem={(r) => (
+            <List.Item>
+              <Text ellipsis style={{ maxWidth: 420 }}>{r.source.split(/[\\/]/).pop()}</Text>
+              <Tag color="error">{r.error}</Tag>
+            </List.Item>
+          )}
+        />
+      ),
+    });
+  };
+
+  // 转换列表中的全部字幕，生成的文件加入列表参与配对
+  const handleConvertScript = async (direction: ChineseConversion) => {
+    try {
+      const report = await convertChineseScript(subtitleFiles.map((s) => s.path), direction);
+      const created: FileInfo[] = report
+        .filter((r) => r.target)
+        .map((r) => ({ name: r.target!.split(/[\\/]/).pop() || r.target!, path: r.target!, is_video: false }));
+      if (created.length) updateFileLists(created);
+      else showMessage("没有生成新的字幕文件", "info");
+      const failed = report.filter((r) => r.error);
+      if (failed.length) {
+        Modal.warning({
+          title: "部分字幕未转换",
+          width: 640,
+          content: (
+            <List
+              size="small"
+              dataSource={failed}
+              renderItem={(r) => (
+                <List.Item>
+                  <Text ellipsis style={{ maxWidth: 420 }}>{r.source.split(/[\\/]/).pop()}</Text>
+                  <Tag color="warning">{r.error}</Tag>
+                </List.Item>
+              )}
+            />
+          ),
+        });
+      }
+    } catch (e) {
+      console.error(e);
+      showMessage(`简繁转换失败: ${e}`, "error");
+    }
+  };
+
   const handlePickFiles = async () => {
     try {
       const infos = await pickFilesAndGetInfo();
@@ -251,18 +377,17 @@ export default function Rename() {
       setSubtitleFiles([]);
       if (result.files && result.files.length) {
         const files = result.files;
-        const videoEpisodes = new Set<string>();
-        for (const f of files) {
-          if (f.is_video) {
-            const ep = extractEpisode(f.name);
-            if (ep) videoEpisodes.add(ep);
-          }
-        }
-        const filtered = files.filter((f) => {
-          if (f.is_video) return true;
-          const ep = extractEpisode(f.name);
-          return !!ep && videoEpisodes.has(ep);
-        });
+        const matched = await matchEpisodes(
+          files.filter((f) => f.is_video),
+          files.filter((f) => !f.is_video),
+          episodeRegexStr
+        );
+        // 只保留能对应到视频集数的字幕
+        const keep = new Set<string>([
+          ...matched.pairs.flatMap((p) => p.subtitles.map((s) => s.path)),
+          ...matched.ambiguous.flatMap((a) => (a.videos.length > 0 ? a.subtitles.map((s) => s.path) : [])),
+        ]);
+        const filtered = files.filter((f) => f.is_video || keep.has(f.path));
         updateFileLists(filtered);
       } else showMessage("所选文件夹中未找到视频或字幕文件", "info");
     } catch (e) {
@@ -271,6 +396,19 @@ export default function Rename() {
     }
   };
 
+  // 历史记录中撤销/重做后，同步列表中的文件路径
+  const applyHistoryOperations = (ops: RenameOperation[]) => {
+    const opMap = new Map(ops.map((op) => [op.from_path, op.to_path]));
+    const moveFile = (file: FileInfo): FileInfo => {
+      const toPath = opMap.get(file.path);
+      if (!toPath) return file;
+      const sepIndex = Math.max(toPath.lastIndexOf("/"), toPath.lastIndexOf("\\"));
+      return { ...file, path: toPath, name: toPath.slice(sepIndex + 1) };
+    };
+    setVideoFiles((prev) => prev.map(moveFile));
+    setSubtitleFiles((prev) => prev.map(moveFile));
+  };
+
   const clearFileLists = () => {
     setVideoFiles([]);
     setSubtitleFiles([]);
@@ -304,7 +442,12 @@ export default function Rename() {
     return i >= 0 ? name.slice(i + 1).toLowerCase() : "";
   };
 
+  const planItemFor = (subtitle: FileInfo) => plan?.items.find((it) => it.source_path === subtitle.path);
+
   const previewName = (video: FileInfo, subtitle: FileInfo) => {
+    const item = planItemFor(subtitle);
+    if (item) return item.target_name;
+    // 计划尚未返回时的本地预览，不含语言标记检测
     const sfx = (selectedSuffix || customSuffix).trim();
     const stem = videoStem(video.name);
     const e = ext(subtitle.name);
@@ -324,6 +467,19 @@ export default function Rename() {
           <Title level={4} style={{ margin: 0 }}>字幕重命名</Title>
         </Space>
         <Space>
+          <Button icon={<HistoryOutlined />} onClick={() => setHistoryOpen(true)}>重命名历史</Button>
+          <Button icon={<FieldTimeOutlined />} onClick={() => setRetimeOpen(true)} disabled={matchedPairs().length === 0}>
+            调整时间轴
+          </Button>
+          <Dropdown
+            disabled={subtitleFiles.length === 0}
+            menu={{
+              items: chineseConversionOptions.map((o) => ({ key: o.value, label: o.label })),
+              onClick: ({ key }) => handleConvertScript(key as ChineseConversion),
+            }}
+          >
+            <Button icon={<TranslationOutlined />} disabled={subtitleFiles.length === 0}>简繁转换</Button>
+          </Dropdown>
           <Button icon={<FolderOpenOutlined />} onClick={handlePickFiles}>选择文件</Button>
           <Button icon={<FolderOutlined />} onClick={handlePickFolder}>选择文件夹</Button>
           <Button
@@ -373,7 +529,7 @@ export default function Rename() {
                       right.scrollTop = ratio * toMax;
                     }}
                   >
-                    <List<{ episode: string; video?: FileInfo; subtitle?: FileInfo }>
+                    <List<EpisodeItem>
                       size="small"
                       bordered
                       dataSource={episodeItems}
@@ -399,7 +555,7 @@ export default function Rename() {
                   <FileTextOutlined />
                   字幕文件
                   {episodeItems.length > 0 && (
-                    <Tag color="green">{episodeItems.filter((it) => it.subtitle).length}/{episodeItems.length}</Tag>
+                    <Tag color="green">{episodeItems.filter((it) => it.subtitles.length > 0).length}/{episodeItems.length}</Tag>
                   )}
                 </Space>
               }
@@ -423,31 +579,46 @@ export default function Rename() {
                       left.scrollTop = ratio * toMax;
                     }}
                   >
-                    <List<{ episode: string; video?: FileInfo; subtitle?: FileInfo }>
+                    <List<EpisodeItem>
                       size="small"
                       bordered
                       dataSource={episodeItems}
                       renderItem={(item) => (
                         <List.Item>
                           <div style={{ display: "flex", flexDirection: "column", gap: 4, width: "100%" }}>
-                            <Space>
-                              <Tag>{item.episode}</Tag>
-                              {item.subtitle ? (
-                                <Text ellipsis>{item.subtitle.name}</Text>
-                              ) : (
+                            {item.ambiguous ? (
+                              <Space>
+                                <Tag>{item.episode}</Tag>
+                                <Tag color="orange">存在多个匹配文件</Tag>
+                              </Space>
+                            ) : item.subtitles.length === 0 ? (
+                              <Space>
+                                <Tag>{item.episode}</Tag>
                                 <Tag color="red">缺失</Tag>
-                              )}
-                            </Space>
-                            {item.video && item.subtitle && (
-                              <div className="rename-connect-row">
-                                <span className="rename-connector">└</span>
-                                <span
-                                  className="rename-preview"
-                                  title={previewName(item.video, item.subtitle)}
-                                >
-                                  {previewName(item.video, item.subtitle)}
-                                </span>
-                              </div>
+                              </Space>
+                            ) : (
+                              item.subtitles.map((subtitle) => (
+                                <React.Fragment key={subtitle.path}>
+                                  <Space>
+                                    <Tag>{item.episode}</Tag>
+                                    <Text ellipsis>{subtitle.name}</Text>
+                                  </Space>
+                                  {item.video && (
+                                    <div className="rename-connect-row">
+                                      <span className="rename-connector">└</span>
+                                      <span
+                                        className="rename-preview"
+                                        title={previewName(item.video, subtitle)}
+                                      >
+                                        {previewName(item.video, subtitle)}
+                                      </span>
+                                      {planItemFor(subtitle)?.problems.map((p) => (
+                                        <Tag key={p} color="red">{renameProblemText[p]}</Tag>
+                                      ))}
+                                    </div>
+                                  )}
+                                </React.Fragment>
+                              ))
                             )}
                           </div>
                         </List.Item>
@@ -507,6 +678,29 @@ export default function Rename() {
                     {opt.label}
                   </Button>
                 ))}
+                <Checkbox checked={keepLanguageTag} onChange={(e) => setKeepLanguageTag(e.target.checked)}>
+                  保留原语言标记
+                </Checkbox>
+                <Tooltip title="重命名后检测字幕编码（GBK/GB18030、Big5 等）并转为 UTF-8">
+                  <Select
+                    allowClear
+                    placeholder="不转换编码"
+                    value={subtitleEncoding ?? undefined}
+                    onChange={(v) => setSubtitleEncoding(v ?? null)}
+                    options={subtitleEncodingOptions}
+                    style={{ width: 140 }}
+                  />
+                </Tooltip>
+                <Tooltip title="重命名后在字幕旁另存为所选格式，原字幕保留">
+                  <Select
+                    allowClear
+                    placeholder="不转换格式"
+                    value={subtitleFormat ?? undefined}
+                    onChange={(v) => setSubtitleFormat(v ?? null)}
+                    options={subtitleFormatOptions}
+                    style={{ width: 130 }}
+                  />
+                </Tooltip>
                 <Tooltip title="Ctrl+R">
                   <Button
                     size="middle"
@@ -525,6 +719,25 @@ export default function Rename() {
         </Row>
       </Card>
 
+      <RenameHistoryModal
+        open={historyOpen}
+        onClose={() => setHistoryOpen(false)}
+        onChanged={applyHistoryOperations}
+      />
+
+      <RetimeModal
+        open={retimeOpen}
+        onClose={() => setRetimeOpen(false)}
+        request={retimeOpen ? buildRenameRequest(matchedPairs()) : null}
+      />
+
+      <ArchiveModal
+        archives={pendingArchives}
+        destDir={videoFiles.length > 0 ? videoFiles[0].path.replace(/[\\/][^\\/]*$/, "") : null}
+        onClose={() => setPendingArchives([])}
+        onExtracted={updateFileLists}
+      />
+
       {dragging && (
         <div className="rename-drop-overlay">
           <div className="rename-drop-overlay-content">
@@ -1,13 +1,35 @@
 import React, { useEffect, useState } from 'react';
 import './settings.css';
-import { Card, Form, Input, Typography, Space, Button, message, Segmented } from 'antd';
-import { loadSettings, saveSettings, Settings } from '../api/tauri';
+import { Card, Form, Input, InputNumber, Typography, Space, Button, message, Segmented, Select, Switch } from 'antd';
+import { loadSettings, saveSettings, renderName, clearRecognitionCache, clearMetadataCache, Settings, DEFAULT_NAMING_TEMPLATE, mediaServerPresetOptions, llmProviderOptions, metadataProviderOptions } from '../api/tauri';
 
 const { Title } = Typography;
 
 export default function SettingsPage() {
   const [form] = Form.useForm<Settings>();
   const [loading, setLoading] = useState(false);
+  const [templatePreview, setTemplatePreview] = useState<{ name?: string; error?: string }>({});
+  const namingTemplate = Form.useWatch('naming_template', form);
+
+  useEffect(() => {
+    if (!namingTemplate) {
+      setTemplatePreview({});
+      return;
+    }
+    renderName(namingTemplate, {
+      title: 'Sousou no Frieren',
+      year: 2023,
+      season: 1,
+      episode: '7',
+      ep_title: '魔法使いの秘密',
+      group: 'VCB-Studio',
+      codec: 'HEVC',
+      resolution: '1080p',
+      ext: 'mkv',
+    })
+      .then((name) => setTemplatePreview({ name }))
+      .catch((e) => setTemplatePreview({ error: String(e) }));
+  }, [namingTemplate]);
 
   useEffect(() => {
     const init = async () => {
@@ -39,6 +61,24 @@ export default function SettingsPage() {
     }
   };
 
+  const onClearCache = async () => {
+    try {
+      const count = await clearRecognitionCache();
+      message.success(`已清除 ${count} 条识别缓存`);
+    } catch (e) {
+      message.error(`清空识别缓存失败: ${e}`);
+    }
+  };
+
+  const onClearMetadataCache = async () => {
+    try {
+      const count = await clearMetadataCache();
+      message.success(`已清除 ${count} 个元数据缓存文件`);
+    } catch (e) {
+      message.error(`清空元数据缓存失败: ${e}`);
+    }
+  };
+
   return (
     <div className="settings-page" style={{ display: 'flex', flexDirection: 'column', gap: 16 }}>
       <Space>
@@ -77,12 +117,173 @@ export default function SettingsPage() {
 
       <Card className="section-card" size="small" title="LLM模型配置">
         <Form form={form} layout="vertical">
+          <Form.Item name="llm_provider" label="接口格式">
+            <Select
+              style={{ width: 200 }}
+              options={llmProviderOptions.map(({ label, value }) => ({ label, value }))}
+              onChange={(value) => {
+                const option = llmProviderOptions.find((o) => o.value === value);
+                if (option) form.setFieldsValue({ model_url: option.url });
+              }}
+            />
+          </Form.Item>
           <Form.Item name="model_url" label="模型地址" rules={[{ required: true }]}>
             <Input placeholder="http://localhost:11434/v1/chat/completions" />
           </Form.Item>
+          <Form.Item name="llm_api_key" label="API Key" extra="本地模型通常无需填写">
+            <Input.Password placeholder="留空则不发送认证信息" autoComplete="off" />
+          </Form.Item>
+          <Form.Item
+            name="llm_structured_output"
+            label="结构化输出"
+            valuePropName="checked"
+            extra="请求时附带 JSON Schema 约束模型输出；接口报错时可关闭，改为从输出文本中提取 JSON"
+          >
+            <Switch />
+          </Form.Item>
           <Form.Item name="model_name" label="模型名称" rules={[{ required: true }]}>
             <Input placeholder="qwen/qwen3-vl-8b" />
           </Form.Item>
+          <Form.Item
+            name="parser_confidence_threshold"
+            label="规则解析置信度阈值"
+            tooltip="规则解析的置信度达到该值时直接使用解析结果，不再调用 LLM"
+          >
+            <InputNumber min={0} max={1} step={0.05} style={{ width: 160 }} />
+          </Form.Item>
+          <Space align="end" wrap>
+            <Form.Item name="llm_cache_ttl_days" label="识别缓存有效期（天）" tooltip="0 表示不过期">
+              <InputNumber min={0} precision={0} style={{ width: 160 }} />
+            </Form.Item>
+            <Form.Item name="llm_cache_max_entries" label="识别缓存最大条目数">
+              <InputNumber min={1} precision={0} style={{ width: 160 }} />
+            </Form.Item>
+            <Form.Item>
+              <Button onClick={onClearCache}>清空识别缓存</Button>
+            </Form.Item>
+          </Space>
+          <Form.Item name="llm_concurrency" label="批量识别并发数">
+            <InputNumber min={1} max={16} precision={0} style={{ width: 160 }} />
+          </Form.Item>
+        </Form>
+      </Card>
+
+      <Card className="section-card" size="small" title="文件夹扫描">
+        <Form form={form} layout="vertical">
+          <Space align="start" wrap>
+            <Form.Item name="scan_max_depth" label="子文件夹层数" tooltip="0 表示只扫描所选文件夹，BD 资源通常需要 2-3 层">
+              <InputNumber min={0} max={16} precision={0} style={{ width: 160 }} />
+            </Form.Item>
+            <Form.Item name="scan_include_subtitles" label="包含字幕文件" valuePropName="checked">
+              <Switch />
+            </Form.Item>
+            <Form.Item name="scan_follow_symlinks" label="跟随符号链接" valuePropName="checked">
+              <Switch />
+            </Form.Item>
+          </Space>
+          <Form.Item
+            name="scan_exclude"
+            label="排除规则"
+            extra="gitignore 风格：以 / 结尾只匹配文件夹，含 / 时相对所选文件夹匹配，* 不跨越文件夹，** 匹配任意层，! 开头重新包含"
+          >
+            <Select mode="tags" tokenSeparators={[',', '\n']} placeholder="如 SPs/、*sample*" />
+          </Form.Item>
+        </Form>
+      </Card>
+
+      <Card className="section-card" size="small" title="网络请求">
+        <Form form={form} layout="vertical">
+          <Space align="start" wrap>
+            <Form.Item name="llm_timeout_secs" label="LLM 请求超时（秒）">
+              <InputNumber min={1} precision={0} style={{ width: 160 }} />
+            </Form.Item>
+            <Form.Item name="api_timeout_secs" label="Bangumi 等接口超时（秒）">
+              <InputNumber min={1} precision={0} style={{ width: 160 }} />
+            </Form.Item>
+          </Space>
+          <Space align="start" wrap>
+            <Form.Item name="http_max_retries" label="失败重试次数" tooltip="连接失败、429 或 5xx 时重试，0 表示不重试">
+              <InputNumber min={0} max={10} precision={0} style={{ width: 160 }} />
+            </Form.Item>
+            <Form.Item name="http_retry_base_ms" label="首次重试等待（毫秒）" tooltip="之后每次翻倍，服务端返回 Retry-After 时以其为准">
+              <InputNumber min={0} step={500} precision={0} style={{ width: 160 }} />
+            </Form.Item>
+          </Space>
+        </Form>
+      </Card>
+
+      <Card className="section-card" size="small" title="元数据来源">
+        <Form form={form} layout="vertical">
+          <Form.Item name="metadata_provider" label="默认来源" extra="季度映射依赖 Bangumi 的前传/续集关系，其他来源不做映射">
+            <Select options={metadataProviderOptions} style={{ width: 200 }} />
+          </Form.Item>
+          <Form.Item name="bangumi_api_url" label="Bangumi API 地址">
+            <Input placeholder="https://api.bgm.tv" />
+          </Form.Item>
+          <Form.Item name="anilist_api_url" label="AniList API 地址">
+            <Input placeholder="https://graphql.anilist.co" />
+          </Form.Item>
+          <Form.Item name="tmdb_api_url" label="TMDB API 地址">
+            <Input placeholder="https://api.themoviedb.org/3" />
+          </Form.Item>
+          <Form.Item name="tmdb_api_key" label="TMDB API Key" tooltip="v3 API Key 或 v4 读取令牌均可">
+            <Input.Password placeholder="使用 TMDB 时必填" />
+          </Form.Item>
+          <Form.Item name="anidb_api_url" label="AniDB HTTP API 地址">
+            <Input placeholder="http://api.anidb.net:9001/httpapi" />
+          </Form.Item>
+          <Space align="start" wrap>
+            <Form.Item name="anidb_client" label="AniDB 客户端名称" tooltip="需在 AniDB 注册客户端，AniDB 只支持按条目 ID 查询">
+              <Input style={{ width: 200 }} />
+            </Form.Item>
+            <Form.Item name="anidb_client_version" label="客户端版本">
+              <InputNumber min={1} precision={0} style={{ width: 160 }} />
+            </Form.Item>
+          </Space>
+          <Space align="end" wrap>
+            <Form.Item name="metadata_cache_ttl_days" label="元数据缓存有效期（天）" tooltip="0 表示不过期；接口无法访问时仍会使用过期的缓存">
+              <InputNumber min={0} precision={0} style={{ width: 160 }} />
+            </Form.Item>
+            <Form.Item name="offline_mode" label="离线模式" valuePropName="checked" tooltip="只使用已缓存的条目、章节和封面，不访问元数据接口">
+              <Switch />
+            </Form.Item>
+            <Form.Item>
+              <Button onClick={onClearMetadataCache}>清空元数据缓存</Button>
+            </Form.Item>
+          </Space>
+        </Form>
+      </Card>
+
+      <Card className="section-card" size="small" title="命名模板">
+        <Form form={form} layout="vertical">
+          <Form.Item
+            name="naming_template"
+            label="视频命名模板"
+            rules={[{ required: true }]}
+            extra="变量: {title} {year} {season} {episode} {ep_label} {ep_title} {group} {codec} {resolution} {source} {crc32} {version} {ext}；{episode:02} 补零；{title|upper} / lower / title 转换大小写；{ep_label} 为 第01话 / SP01；{? - {ep_title}} 中变量为空时整段省略；/ 表示目录"
+          >
+            <Input
+              placeholder={DEFAULT_NAMING_TEMPLATE}
+              addonAfter={
+                <Typography.Link onClick={() => form.setFieldsValue({ naming_template: DEFAULT_NAMING_TEMPLATE })}>
+                  重置
+                </Typography.Link>
+              }
+            />
+          </Form.Item>
+          {templatePreview.name && <Typography.Text type="secondary">预览: {templatePreview.name}</Typography.Text>}
+          {templatePreview.error && <Typography.Text type="danger">{templatePreview.error}</Typography.Text>}
+          <Form.Item
+            name="naming_preset"
+            label="媒体服务器预设"
+            extra="选择预设后按对应媒体服务器的目录结构命名，SP/OVA 放入 Specials，并忽略上方模板"
+            style={{ marginTop: 16 }}
+          >
+            <Select allowClear placeholder="不使用预设" options={mediaServerPresetOptions} style={{ width: 200 }} />
+          </Form.Item>
+          <Form.Item name="media_library_root" label="媒体库根目录">
+            <Input allowClear placeholder="留空则在视频所在目录下整理" />
+          </Form.Item>
         </Form>
       </Card>
 
@@ -2,6 +2,8 @@ export interface FileInfo {
   name: string;
   path: string;
   is_video: boolean;
+  // zip/7z/rar 字幕包，解压后才参与配对
+  is_archive?: boolean;
 }
 
 export interface AnimeInfo {
@@ -10,6 +12,17 @@ export interface AnimeInfo {
   codec?: string;
   group?: string;
   year?: number;
+  // 0 表示特别篇（SP/OVA）
+  season?: number;
+  resolution?: string;
+  source?: string;
+  crc32?: string;
+  version?: number;
+}
+
+export interface ParsedFilename {
+  info: AnimeInfo;
+  confidence: number;
 }
 
 export interface RecognitionResult {
@@ -23,18 +36,25 @@ export interface LLMRequest {
   filename: string;
   model_url: string;
   model_name: string;
+  // 跳过规则解析，直接调用 LLM
+  force_llm?: boolean;
+  // 不读取识别缓存，结果仍会写回缓存
+  bypass_cache?: boolean;
 }
 
 export interface LLMResponse {
   success: boolean;
   data?: AnimeInfo;
   error?: string;
+  // 'parser' 规则解析，'llm' 模型识别，'cache' 命中识别缓存
+  engine?: 'parser' | 'llm' | 'cache';
 }
 
 export interface BatchLLMRequest {
   filenames: string[];
   model_url: string;
   model_name: string;
+  bypass_cache?: boolean;
 }
 
 export interface BatchLLMResponse {